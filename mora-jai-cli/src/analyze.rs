@@ -0,0 +1,209 @@
+//! Reports on every puzzle of a pack, such as one written by `import-image --out`, for
+//! reviewing collections of puzzles.
+
+use std::path::Path;
+use std::time::Duration;
+
+use mora_jai_core::{Color, ColorLabels, Difficulty, RuleSet, SolveOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::batch;
+
+/// How the report is written to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// One row per puzzle after a header row, with the colors used separated by spaces
+    #[default]
+    Csv,
+    /// An array with one object per puzzle
+    Json,
+}
+
+#[derive(Deserialize)]
+struct Pack {
+    puzzles: Vec<PackPuzzle>,
+}
+
+/// A puzzle of a pack, either as notation or an entry with a `notation` field.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PackPuzzle {
+    Notation(String),
+    Entry { notation: String },
+}
+
+impl PackPuzzle {
+    fn text(&self) -> &str {
+        match self {
+            PackPuzzle::Notation(text) | PackPuzzle::Entry { notation: text } => text,
+        }
+    }
+}
+
+/// What was found about one puzzle. Every field but `puzzle` is left empty if the puzzle
+/// could not be parsed or solved, with `error` saying why.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Analysis {
+    puzzle: String,
+    optimal_length: Option<usize>,
+    optimal_solutions: Option<u64>,
+    difficulty: Option<Difficulty>,
+    /// [`DifficultyReport::human_score`](mora_jai_core::DifficultyReport::human_score),
+    /// to one decimal place
+    human_score: Option<f64>,
+    /// The colors whose rules an optimal solution uses, in the order first used
+    rules: Vec<&'static str>,
+    error: Option<String>,
+}
+
+fn analyze(
+    text: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+    timeout: Option<Duration>,
+) -> Analysis {
+    let failed = |error: String| Analysis {
+        puzzle: text.to_string(),
+        error: Some(error),
+        ..Default::default()
+    };
+    let puzzle = match batch::parse_puzzle(text, rules, labels) {
+        Ok(puzzle) => puzzle,
+        Err((_, e)) => return failed(e),
+    };
+    let options = SolveOptions {
+        time_limit: timeout,
+        ..Default::default()
+    };
+    let (solution, count) = match puzzle.solve_counting_with(&options) {
+        Ok(Some(solved)) => solved,
+        Ok(None) => return failed(String::from("puzzle has no solution")),
+        Err(e) => return failed(e.to_string()),
+    };
+
+    let mut used: Vec<Color> = vec![];
+    for press in puzzle.trace(&solution) {
+        if press.rule.is_some() && !used.contains(&press.color) {
+            used.push(press.color);
+        }
+    }
+    Analysis {
+        puzzle: text.to_string(),
+        optimal_length: Some(solution.len()),
+        optimal_solutions: Some(count),
+        difficulty: Some(Difficulty::from_solution_length(solution.len())),
+        human_score: Some(
+            (puzzle.difficulty_report_for(&solution).human_score() * 10.0).round() / 10.0,
+        ),
+        rules: used.iter().map(Color::name).collect(),
+        error: None,
+    }
+}
+
+/// Quotes a CSV field if it holds a comma, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn to_csv(analyses: &[Analysis]) -> String {
+    let mut csv = String::from(
+        "puzzle,optimal_length,optimal_solutions,difficulty,human_score,rules,error\n",
+    );
+    for analysis in analyses {
+        let fields = [
+            analysis.puzzle.clone(),
+            analysis
+                .optimal_length
+                .map_or_else(String::new, |n| n.to_string()),
+            analysis
+                .optimal_solutions
+                .map_or_else(String::new, |n| n.to_string()),
+            analysis
+                .difficulty
+                .map_or_else(String::new, |d| d.to_string()),
+            analysis
+                .human_score
+                .map_or_else(String::new, |n| n.to_string()),
+            analysis.rules.join(" "),
+            analysis.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Prints a report on each puzzle of the pack at `input`, a JSON object whose `puzzles`
+/// are notation or share codes, or objects with a `notation` field. Notation is read with
+/// the codes of `labels`.
+pub fn analyze_pack(
+    input: &Path,
+    format: ReportFormat,
+    timeout: Option<Duration>,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    let pack: Pack =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", input.display()))?;
+    let analyses: Vec<Analysis> = pack
+        .puzzles
+        .iter()
+        .map(|puzzle| analyze(puzzle.text(), rules, labels, timeout))
+        .collect();
+
+    match format {
+        ReportFormat::Csv => print!("{}", to_csv(&analyses)),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&analyses)?),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzles_are_analyzed_or_explained() {
+        let pack: Pack = serde_json::from_str(
+            r#"{"puzzles": ["wwwwwww-w---w", {"notation": "wwwwwww-w---x", "sources": []}]}"#,
+        )
+        .unwrap();
+        let rules = RuleSet::standard();
+        let analyses: Vec<Analysis> = pack
+            .puzzles
+            .iter()
+            .map(|puzzle| analyze(puzzle.text(), &rules, &ColorLabels::default(), None))
+            .collect();
+
+        assert_eq!(
+            analyses[0],
+            Analysis {
+                puzzle: String::from("wwwwwww-w---w"),
+                optimal_length: Some(2),
+                optimal_solutions: Some(2),
+                difficulty: Some(Difficulty::Easy),
+                human_score: analyses[0].human_score,
+                rules: vec!["white"],
+                error: None,
+            }
+        );
+        assert!(analyses[0].human_score.unwrap() > 2.0);
+        assert!(analyses[1].error.is_some());
+        assert_eq!(analyses[1].optimal_length, None);
+
+        let csv = to_csv(&analyses);
+        let mut lines = csv.lines().skip(1);
+        let score = analyses[0].human_score.unwrap();
+        assert_eq!(
+            lines.next(),
+            Some(format!("wwwwwww-w---w,2,2,easy,{score},white,").as_str())
+        );
+        assert!(lines.next().unwrap().starts_with("wwwwwww-w---x,,,,,,"));
+    }
+}
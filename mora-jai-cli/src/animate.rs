@@ -0,0 +1,54 @@
+//! Playing solutions out step by step on the terminal.
+
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+use crossterm::cursor::MoveToPreviousLine;
+use crossterm::queue;
+use crossterm::terminal::{Clear, ClearType};
+use mora_jai_core::{Move, Puzzle};
+
+use crate::output::Output;
+use crate::play;
+
+/// Draws a puzzle before each move, picking out the move about to be made, and then once
+/// more when solved, waiting `delay` between moves. On a terminal each drawing replaces
+/// the last.
+pub fn animate(
+    puzzle: &Puzzle,
+    moves: &[Move],
+    delay: Duration,
+    output: &Output,
+) -> io::Result<()> {
+    let mut puzzle = puzzle.clone();
+    let mut stdout = io::stdout();
+    let redraw = stdout.is_terminal();
+    let mut last_lines = 0;
+
+    for step in 0..=moves.len() {
+        let action = moves.get(step).copied();
+        let mut frame = output.render_puzzle_highlighted(&puzzle, action);
+        match action {
+            Some(action) => frame.push_str(&format!("Pressing {}\n", play::key_for(action))),
+            None => frame.push_str("Solved\n"),
+        }
+
+        if redraw && last_lines > 0 {
+            queue!(
+                stdout,
+                MoveToPreviousLine(last_lines),
+                Clear(ClearType::FromCursorDown)
+            )?;
+        }
+        write!(stdout, "{frame}")?;
+        stdout.flush()?;
+        last_lines = frame.lines().count() as u16;
+
+        if let Some(action) = action {
+            thread::sleep(delay);
+            puzzle.apply(action);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Advice for a puzzle part way through: whether to carry on or reset, and how to
+//! finish from there.
+
+use mora_jai_core::{ColorLabels, Corner, Move, Puzzle, RuleSet};
+use mora_jai_io::notation;
+
+use crate::batch;
+use crate::output::Output;
+use crate::repl::{move_label, parse_corner, parse_tile};
+
+const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+/// What to do next with a puzzle part way through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Advice {
+    Solved,
+    /// Carry on with these moves
+    Continue(Vec<Move>),
+    /// Press this corner to go back to the start, then make these moves
+    Reset(Corner, Vec<Move>),
+    /// No solution from here, and the start is already no better
+    Unsolvable,
+}
+
+/// Weighs finishing `current` against resetting to the start of `start`, which costs a
+/// press of a corner whose tile does not match its goal.
+fn advise(start: &Puzzle, current: &Puzzle) -> Advice {
+    if current.is_solved() {
+        return Advice::Solved;
+    }
+    let onward = current.remaining_moves();
+
+    let reset_corner = CORNERS
+        .into_iter()
+        .find(|&corner| !current.would_lock(corner));
+    let fresh = Puzzle::with_rules(
+        *start.goals(),
+        start.original().clone(),
+        start.rules().clone(),
+    );
+    let restart = reset_corner.zip(fresh.remaining_moves());
+
+    match (onward, restart) {
+        (Some(onward), Some((_, restart))) if onward.len() <= restart.len() + 1 => {
+            Advice::Continue(onward)
+        }
+        (_, Some((corner, restart))) => Advice::Reset(corner, restart),
+        (Some(onward), None) => Advice::Continue(onward),
+        (None, None) => Advice::Unsolvable,
+    }
+}
+
+/// Replays `moves`, such as `3 2 nw`, on a fresh copy of `start`.
+fn replay(start: &Puzzle, moves: &[String]) -> Result<Puzzle, String> {
+    let mut puzzle = start.clone();
+    for word in moves.iter().flat_map(|moves| moves.split_whitespace()) {
+        let action = match parse_tile(word) {
+            Ok((row, col)) => Move::Tile(row, col),
+            Err(_) => Move::Corner(parse_corner(word).map_err(|_| {
+                format!("expected a tile from 1 to 9 or a corner nw, ne, sw or se, not {word:?}")
+            })?),
+        };
+        puzzle.apply(action);
+    }
+    Ok(puzzle)
+}
+
+/// The puzzle as it stands with `grid`, nine color codes of `labels` top row first, and
+/// `locked` corners locked.
+fn from_grid(
+    start: &Puzzle,
+    grid: &str,
+    locked: &[String],
+    labels: &ColorLabels,
+) -> Result<Puzzle, String> {
+    if grid.chars().count() != 9 {
+        return Err(format!(
+            "expected 9 tiles in the grid, not {}",
+            grid.chars().count()
+        ));
+    }
+    let goals: String = start
+        .goals()
+        .iter()
+        .map(|goal| labels.code(*goal))
+        .collect();
+    let parsed = notation::try_parse_puzzle_with_labels(&(goals + grid), start.rules(), labels)
+        .map_err(|e| format!("in the grid: {e}"))?;
+    let mut puzzle = Puzzle::with_rules(
+        *start.goals(),
+        parsed.original().clone(),
+        start.rules().clone(),
+    );
+
+    for word in locked {
+        let corner = parse_corner(word)?;
+        puzzle.press_corner(corner);
+        if puzzle.get_corner(corner) != puzzle.goal(corner) {
+            return Err(format!(
+                "the {} corner cannot be locked, as its tile does not match its goal",
+                move_label(Move::Corner(corner))
+            ));
+        }
+    }
+    Ok(puzzle)
+}
+
+fn labels(moves: &[Move]) -> String {
+    let labels: Vec<String> = moves.iter().copied().map(move_label).collect();
+    labels.join(" ")
+}
+
+/// Prints advice for `puzzle` after `moves`, or as it stands with `grid` and `locked`
+/// corners.
+pub fn assist(
+    puzzle: &str,
+    moves: &[String],
+    grid: Option<&str>,
+    locked: &[String],
+    rules: &RuleSet,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let colors = output.color_labels();
+    let start = batch::parse_puzzle(puzzle, rules, colors).map_err(|(_, e)| e)?;
+    let current = match grid {
+        Some(grid) => from_grid(&start, grid, locked, colors)?,
+        None => replay(&start, moves)?,
+    };
+    output.print_puzzle(&current);
+
+    match advise(&start, &current) {
+        Advice::Solved => println!("Already solved"),
+        Advice::Continue(moves) => {
+            println!("Carry on, {} moves from here", moves.len());
+            println!("Solution: {}", labels(&moves));
+        }
+        Advice::Reset(corner, moves) => {
+            println!(
+                "Reset by pressing {}, then {} moves from the start",
+                move_label(Move::Corner(corner)),
+                moves.len()
+            );
+            println!(
+                "Solution: {} {}",
+                move_label(Move::Corner(corner)),
+                labels(&moves)
+            );
+        }
+        Advice::Unsolvable => println!("No solution, even from the start"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> Puzzle {
+        notation::parse_puzzle("wwwwwww-w---w", &RuleSet::standard()).unwrap()
+    }
+
+    #[test]
+    fn short_ways_on_beat_resetting() {
+        let start = start();
+        let current = replay(&start, &[String::from("3 2 nw")]).unwrap();
+        assert_eq!(
+            advise(&start, &current),
+            Advice::Continue(vec![
+                Move::Corner(Corner::NE),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ])
+        );
+        assert_eq!(
+            advise(
+                &start,
+                &replay(&start, &[String::from("3 2 nw ne sw se")]).unwrap()
+            ),
+            Advice::Solved
+        );
+    }
+
+    #[test]
+    fn long_ways_on_reset() {
+        let start = start();
+        // Pressing the center after 3 scatters the white tiles, so starting over is quicker
+        let current = replay(&start, &[String::from("3"), String::from("5 5 5")]).unwrap();
+        match advise(&start, &current) {
+            Advice::Reset(_, moves) => assert_eq!(moves, start.remaining_moves().unwrap()),
+            advice => panic!("expected a reset, not {advice:?}"),
+        }
+    }
+
+    #[test]
+    fn grids_are_read_with_locked_corners() {
+        let start = start();
+        let current = from_grid(
+            &start,
+            "www-ww---",
+            &[String::from("nw")],
+            &ColorLabels::default(),
+        )
+        .unwrap();
+        assert_eq!(current.get_corner(Corner::NW), current.goal(Corner::NW));
+        assert!(
+            from_grid(
+                &start,
+                "www-ww---",
+                &[String::from("sw")],
+                &ColorLabels::default()
+            )
+            .is_err()
+        );
+        assert!(from_grid(&start, "www-ww", &[], &ColorLabels::default()).is_err());
+        assert!(from_grid(&start, "www-wwx--", &[], &ColorLabels::default()).is_err());
+    }
+}
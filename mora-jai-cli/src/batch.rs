@@ -0,0 +1,1131 @@
+//! Solving files of puzzles, one per line, across threads.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use mora_jai_core::{
+    ColorLabels, Corner, Diagnosis, PartialPuzzle, PartialSolution, Puzzle, RuleSet, RuleUse,
+    SHARE_CODE_LEN, SearchStats, SolveError, SolveOptions, TieBreak,
+};
+use mora_jai_io::board;
+use mora_jai_io::notation::{
+    has_unknowns, try_parse_partial_with_labels, try_parse_puzzle_with_labels,
+};
+use mora_jai_render::{SolutionFormatter, plain};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animate;
+use crate::output::Output;
+
+/// Lines are solved in chunks of this many, so output keeps flowing on long inputs
+/// without holding the whole file in memory.
+const CHUNK_LINES: usize = 1024;
+
+/// How problems with individual puzzles are reported on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// `file:line:column: message`
+    #[default]
+    Text,
+    /// One JSON object per line with `file`, `line`, `column`, `kind` and `error` fields
+    Json,
+}
+
+/// How much is printed for each puzzle solved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputProfile {
+    /// Only each solution's presses, one line per puzzle, with an empty line for each
+    /// puzzle without one so lines match the input. Animations, explanations and
+    /// alternative solutions are left out
+    Quiet,
+    /// Puzzles and solutions as uncolored text
+    Plain,
+    /// Puzzles drawn in color, as configured, with their solutions
+    #[default]
+    Pretty,
+}
+
+/// Which of several optimal solutions is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TieBreakChoice {
+    /// The solution pressing the lowest keypad numbers first
+    #[default]
+    Keypad,
+    /// The solution pressing corner tiles earliest, then the lowest keypad numbers
+    CornersFirst,
+    /// The solution pressing tiles earliest in reading order, from the top left
+    Reading,
+}
+
+impl From<TieBreakChoice> for TieBreak {
+    fn from(choice: TieBreakChoice) -> Self {
+        match choice {
+            TieBreakChoice::Keypad => TieBreak::Keypad,
+            TieBreakChoice::CornersFirst => TieBreak::CornersFirst,
+            TieBreakChoice::Reading => TieBreak::Reading,
+        }
+    }
+}
+
+/// Why a puzzle has no solution in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum FailureKind {
+    /// The line is not a puzzle
+    Invalid,
+    /// The puzzle can't be solved
+    Unsolvable,
+    /// The search ran out of time before deciding either way
+    TimedOut,
+    /// The search found no solution after dropping states to stay within its memory cap
+    MemoryCapped,
+}
+
+/// A puzzle which could not be parsed or solved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct LineError {
+    file: String,
+    line: usize,
+    /// Only known for parse errors
+    column: Option<usize>,
+    /// The `id` of a puzzle given as a JSON object, if it had one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    kind: FailureKind,
+    error: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:", self.file, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, "{column}:")?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, " {id}:")?;
+        }
+        write!(f, " {}", self.error)
+    }
+}
+
+/// How a solution compares with the shortest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Optimality {
+    /// The solution is optimal, one of this many optimal solutions
+    Counted(u64),
+    /// The solution was found by a search capped in memory, which may have dropped
+    /// states on the way to it
+    Bounded(SearchStats),
+}
+
+/// A puzzle with a solution, how it compares with the shortest, and the `id` it was
+/// given as a JSON object, if any.
+type Solved = (Puzzle, Vec<(usize, usize)>, Optimality, Option<String>);
+
+/// What solving a line found.
+#[derive(Debug)]
+enum Answer {
+    Solved(Solved),
+    /// A puzzle with unknown tiles, what solves it, and the `id` it was given as a JSON
+    /// object, if any
+    Partial(PartialPuzzle, PartialSolution, Option<String>),
+}
+
+/// A puzzle given as a JSON object on one line, such as
+/// `{"goals": "wwww", "grid": "www-w---w", "id": "trunk-3"}`.
+#[derive(Deserialize)]
+struct JsonPuzzle {
+    /// The goals in notation, NW, NE, SW then SE
+    goals: String,
+    /// The tiles in notation, top row first
+    grid: String,
+    /// Anything identifying the puzzle, echoed with its solution
+    id: Option<serde_json::Value>,
+}
+
+/// Reads a line holding a puzzle as a JSON object into notation and its `id`, if it
+/// has one, describing any problem with the column where it was found.
+fn parse_json_line(line: &str) -> Result<(String, Option<String>), (Option<usize>, String)> {
+    let puzzle: JsonPuzzle =
+        serde_json::from_str(line).map_err(|e| (Some(e.column()), e.to_string()))?;
+    let id = puzzle.id.map(|id| match id {
+        serde_json::Value::String(id) => id,
+        id => id.to_string(),
+    });
+    Ok((puzzle.goals + &puzzle.grid, id))
+}
+
+/// Parses a puzzle in notation, as a share code, or laid out as a board over several
+/// lines, describing any problem with the column where it was found, if known.
+/// Notation is read with the codes of `labels`.
+pub fn parse_puzzle(
+    text: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<Puzzle, (Option<usize>, String)> {
+    if board::is_board(text) {
+        return board::try_parse_board_with_labels(text, rules, labels)
+            .map_err(|e| (None, e.to_string()));
+    }
+    // Share codes are shorter than notation, so the two can't be confused
+    if text.chars().count() == SHARE_CODE_LEN {
+        Puzzle::from_share_code_with_rules(text, rules.clone()).map_err(|e| (None, e.to_string()))
+    } else {
+        try_parse_puzzle_with_labels(text, rules, labels)
+            .map_err(|e| (Some(e.column()), e.to_string()))
+    }
+}
+
+/// Parses a timeout such as `5s`, `500ms` or `2m`. A bare number is in seconds.
+pub fn parse_timeout(text: &str) -> Result<Duration, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a duration such as 5s or 500ms, got {text:?}"))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(format!("unknown unit {unit:?}, expected ms, s or m")),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+const SIZE_UNITS: [(&str, usize); 4] = [("", 1), ("K", 1 << 10), ("M", 1 << 20), ("G", 1 << 30)];
+
+/// Parses an amount of memory such as `512M`, `64KiB` or `2G`, in powers of 1024. A
+/// bare number is in bytes.
+pub fn parse_size(text: &str) -> Result<usize, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a size such as 512M or 2G, got {text:?}"))?;
+    let prefix = unit
+        .strip_suffix("iB")
+        .or_else(|| unit.strip_suffix('B'))
+        .unwrap_or(unit);
+    let (_, scale) = SIZE_UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(prefix))
+        .ok_or_else(|| format!("unknown unit {unit:?}, expected K, M or G"))?;
+    Ok((number * *scale as f64) as usize)
+}
+
+/// An amount of memory in the largest unit it has at least one of, such as `1.5 MiB`.
+pub fn format_size(bytes: usize) -> String {
+    let (name, scale) = SIZE_UNITS
+        .iter()
+        .rev()
+        .find(|&&(_, scale)| bytes >= scale)
+        .unwrap_or(&SIZE_UNITS[0]);
+    match *scale {
+        1 => format!("{bytes} B"),
+        scale => format!("{:.1} {name}iB", bytes as f64 / scale as f64),
+    }
+}
+
+/// Details of a puzzle with no solution, to help find a mistyped tile.
+fn describe_unsolvable(diagnosis: &Diagnosis) -> String {
+    let matched: Vec<Corner> = CORNERS
+        .into_iter()
+        .zip(diagnosis.matched)
+        .filter_map(|(corner, matched)| matched.then_some(corner))
+        .collect();
+    let grids = match diagnosis.visited {
+        1 => String::from("1 reachable grid"),
+        visited => format!("{visited} reachable grids"),
+    };
+    if matched.is_empty() {
+        return format!("puzzle has no solution: no corner can be matched in {grids}");
+    }
+    let closest = match diagnosis.presses.as_slice() {
+        [] => format!(
+            "the closest is the start, matching {}",
+            corner_list(&matched)
+        ),
+        presses => format!(
+            "the closest matches {} after pressing {}",
+            corner_list(&matched),
+            SolutionFormatter::Keypad.format(presses)
+        ),
+    };
+    let never: Vec<String> = diagnosis
+        .never_together()
+        .iter()
+        .map(|corners| match corners.as_slice() {
+            [corner] => format!("{} never matches", corner_name(*corner)),
+            _ => format!("{} never match at once", corner_list(corners)),
+        })
+        .collect();
+    format!(
+        "puzzle has no solution: of {grids} {closest}, and {}",
+        never.join(", ")
+    )
+}
+
+/// How many presses followed each color's rule, such as `3 black, 1 blue as orange`.
+fn describe_usage(usage: &[RuleUse], labels: &ColorLabels) -> String {
+    let uses: Vec<String> = usage
+        .iter()
+        .map(|rule| match rule.acting_as {
+            Some(acting_as) => format!(
+                "{} {} as {}",
+                rule.presses,
+                labels.name(rule.color),
+                labels.name(acting_as)
+            ),
+            None => format!("{} {}", rule.presses, labels.name(rule.color)),
+        })
+        .collect();
+    uses.join(", ")
+}
+
+const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+/// Corners as a list in words, such as `NW, NE and SE`.
+fn corner_list(corners: &[Corner]) -> String {
+    let names: Vec<&str> = corners.iter().map(|&corner| corner_name(corner)).collect();
+    match names.as_slice() {
+        [] => String::from("none"),
+        [name] => name.to_string(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+fn corner_name(corner: Corner) -> &'static str {
+    match corner {
+        Corner::NW => "NW",
+        Corner::NE => "NE",
+        Corner::SW => "SW",
+        Corner::SE => "SE",
+    }
+}
+
+/// Parses and solves one line of notation, a share code or a JSON object with `goals`
+/// and `grid` in notation, describing any problem with the line and column where it
+/// was found. Problems within the notation of a JSON object are not given a column.
+/// Notation with unknown tiles is solved for every color they could be.
+fn solve_line(
+    name: &str,
+    line_no: usize,
+    line: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+    options: &SolveOptions,
+) -> Result<Answer, LineError> {
+    let (text, id) = if line.trim_start().starts_with('{') {
+        parse_json_line(line).map_err(|(column, e)| LineError {
+            file: name.to_string(),
+            line: line_no,
+            column,
+            id: None,
+            kind: FailureKind::Invalid,
+            error: e,
+        })?
+    } else {
+        (line.to_string(), None)
+    };
+    let json = id.is_some() || text != line;
+    let error = |column, kind, error: String| LineError {
+        file: name.to_string(),
+        line: line_no,
+        column,
+        id: id.clone(),
+        kind,
+        error,
+    };
+
+    if has_unknowns(&text) && !board::is_board(&text) {
+        let puzzle = try_parse_partial_with_labels(&text, rules, labels).map_err(|e| {
+            let column = Some(e.column()).filter(|_| !json);
+            error(column, FailureKind::Invalid, e.to_string())
+        })?;
+        let solution = match puzzle.solve_with(options) {
+            Ok(PartialSolution::PerCompletion(solved))
+                if solved.iter().all(|(_, solution)| solution.is_none()) =>
+            {
+                let e = "puzzle has no solution whatever the unknown tiles are";
+                return Err(error(None, FailureKind::Unsolvable, e.to_string()));
+            }
+            Ok(solution) => solution,
+            Err(SolveError::TimeLimitReached) => {
+                let limit = options
+                    .time_limit
+                    .expect("only searches with a time limit run out of time");
+                let e = format!("timed out after {limit:?}");
+                return Err(error(None, FailureKind::TimedOut, e));
+            }
+            Err(e) => return Err(error(None, FailureKind::Unsolvable, e.to_string())),
+        };
+        return Ok(Answer::Partial(puzzle, solution, id));
+    }
+
+    let puzzle = parse_puzzle(&text, rules, labels)
+        .map_err(|(column, e)| error(column.filter(|_| !json), FailureKind::Invalid, e))?;
+    // Counting optimal solutions explores every grid, so a search capped in memory only
+    // finds one
+    let found = match options.memory_bound {
+        Some(_) => match puzzle.solve_with_stats(options) {
+            Ok((Some(solution), stats)) => Ok(Some((solution.presses, Optimality::Bounded(stats)))),
+            Ok((None, stats)) if stats.dropped > 0 => {
+                let e = format!(
+                    "no solution found within the memory cap, after dropping {} states",
+                    stats.dropped
+                );
+                return Err(error(None, FailureKind::MemoryCapped, e));
+            }
+            Ok((None, _)) => Ok(None),
+            Err(e) => Err(e),
+        },
+        None => puzzle
+            .solve_counting_with(options)
+            .map(|found| found.map(|(solution, count)| (solution, Optimality::Counted(count)))),
+    };
+    let (solution, optimality) = match found {
+        Ok(Some(solved)) => solved,
+        Ok(None) => {
+            let e = match puzzle.diagnose_with(options) {
+                Ok(diagnosis) => describe_unsolvable(&diagnosis),
+                Err(_) => String::from("puzzle has no solution"),
+            };
+            return Err(error(None, FailureKind::Unsolvable, e));
+        }
+        Err(SolveError::TimeLimitReached) => {
+            let limit = options
+                .time_limit
+                .expect("only searches with a time limit run out of time");
+            let e = format!("timed out after {limit:?}");
+            return Err(error(None, FailureKind::TimedOut, e));
+        }
+        Err(e) => return Err(error(None, FailureKind::Unsolvable, e.to_string())),
+    };
+    Ok(Answer::Solved((puzzle, solution, optimality, id)))
+}
+
+/// How results are reported, and a tally of those reported so far.
+pub struct Report {
+    pub errors: ErrorFormat,
+    /// Solutions are played out with this delay between moves, if given
+    pub animate: Option<Duration>,
+    /// Explain what each press of a solution does
+    pub explain: bool,
+    /// Show when to press each corner along with the tile presses
+    pub corners: bool,
+    /// List up to this many solutions, with how many presses longer than optimal they
+    /// may be, instead of one
+    pub alternatives: Option<(usize, usize)>,
+    /// Count the distinct solutions of each length up to this many presses
+    pub lengths_up_to: Option<usize>,
+    /// Give up on a puzzle after searching for this long, if given
+    pub timeout: Option<Duration>,
+    /// Hold at most this many states in each search, if given, at the cost of optimal
+    /// solutions
+    pub memory_bound: Option<usize>,
+    /// Which optimal solution is shown when there are several
+    pub tie_break: TieBreak,
+    /// How much is printed for each puzzle
+    pub profile: OutputProfile,
+    /// Puzzles which could not be parsed or solved
+    pub failures: usize,
+    /// Of the `failures`, puzzles given up on after `timeout`
+    pub timeouts: usize,
+    /// The most states any search held at once under a `memory_bound`
+    pub peak_states: usize,
+    /// Puzzles whose searches dropped states to stay within `memory_bound`, so their
+    /// solutions may be longer than optimal or none was found
+    pub capped: usize,
+    /// The labels of each solution printed, if they are kept to be copied
+    pub solutions: Option<Vec<String>>,
+}
+
+impl Report {
+    /// Reports errors in the given format, and solutions with only their presses.
+    pub fn new(errors: ErrorFormat) -> Self {
+        Self {
+            errors,
+            animate: None,
+            explain: false,
+            corners: false,
+            alternatives: None,
+            lengths_up_to: None,
+            timeout: None,
+            memory_bound: None,
+            tie_break: TieBreak::default(),
+            profile: OutputProfile::default(),
+            failures: 0,
+            timeouts: 0,
+            peak_states: 0,
+            capped: 0,
+            solutions: None,
+        }
+    }
+
+    /// The limits and tie-break each puzzle is solved with.
+    pub fn options(&self) -> SolveOptions {
+        SolveOptions {
+            time_limit: self.timeout,
+            memory_bound: self.memory_bound,
+            tie_break: self.tie_break,
+            ..Default::default()
+        }
+    }
+
+    /// Prints a puzzle and its solution, or several solutions if asked for
+    /// `alternatives`, and how it compares with the shortest, after the puzzle's `id` if
+    /// it has one. Other details are only given for `solution`.
+    pub fn solved(
+        &mut self,
+        puzzle: &Puzzle,
+        solution: &[(usize, usize)],
+        optimality: Optimality,
+        id: Option<&str>,
+        output: &Output,
+    ) -> io::Result<()> {
+        if let Optimality::Bounded(stats) = optimality {
+            self.peak_states = self.peak_states.max(stats.peak_states);
+            if stats.dropped > 0 {
+                self.capped += 1;
+            }
+        }
+        let line = match self.corners {
+            true => output
+                .labels()
+                .format_moves(&puzzle.schedule_corners(solution)),
+            false => output.labels().format(solution),
+        };
+        if let Some(solutions) = &mut self.solutions {
+            solutions.push(line.clone());
+        }
+        if self.profile == OutputProfile::Quiet {
+            match id {
+                Some(id) => println!("{id}\t{line}"),
+                None => println!("{line}"),
+            }
+            return Ok(());
+        }
+        if let Some(id) = id {
+            println!("Id: {id}");
+        }
+
+        let print = |solution: &[(usize, usize)]| {
+            if self.corners {
+                output.print_moves(&puzzle.schedule_corners(solution));
+            } else {
+                output.print_solution(solution);
+            }
+        };
+        match self.animate {
+            Some(delay) => {
+                let moves = if self.corners {
+                    puzzle.schedule_corners(solution)
+                } else {
+                    puzzle.moves_for(solution)
+                };
+                animate::animate(puzzle, &moves, delay, output)?
+            }
+            None => output.print_puzzle(puzzle),
+        }
+        match self.alternatives {
+            Some((count, slack)) => {
+                for alternative in puzzle.solutions(count, slack) {
+                    print(&alternative);
+                }
+            }
+            None => print(solution),
+        }
+        match optimality {
+            Optimality::Counted(1) => println!("The only optimal solution"),
+            Optimality::Counted(count) => println!("1 of {count} optimal solutions"),
+            Optimality::Bounded(stats) if stats.dropped > 0 => println!(
+                "May not be optimal: {} states were dropped to stay within the memory cap",
+                stats.dropped
+            ),
+            Optimality::Bounded(_) => println!("Optimal"),
+        }
+        let usage = puzzle.rule_usage(solution);
+        if !usage.is_empty() {
+            let usage = describe_usage(&usage, output.color_labels());
+            println!("Rules used: {usage}");
+        }
+        if let Some(max_length) = self.lengths_up_to {
+            println!("Solutions by number of presses:");
+            for (length, count) in puzzle.solution_counts(max_length).iter().enumerate() {
+                println!("{length:>4}: {count}");
+            }
+        }
+        if self.explain {
+            let labels = output.labels();
+            print!("{}", plain::render_explanation(puzzle, solution, labels));
+        }
+        Ok(())
+    }
+
+    /// Prints a puzzle with unknown tiles and what solves it: presses solving it whatever
+    /// they are, or else each color they could be with that puzzle's own solution.
+    fn partial(
+        &mut self,
+        puzzle: &PartialPuzzle,
+        solution: &PartialSolution,
+        id: Option<&str>,
+        output: &Output,
+    ) {
+        let labels = output.labels();
+        let unknowns = puzzle.unknowns();
+        if self.profile == OutputProfile::Quiet {
+            let line = match solution {
+                PartialSolution::Universal(presses) => labels.format(presses),
+                PartialSolution::PerCompletion(_) => String::new(),
+            };
+            match id {
+                Some(id) => println!("{id}\t{line}"),
+                None => println!("{line}"),
+            }
+            return;
+        }
+        if let Some(id) = id {
+            println!("Id: {id}");
+        }
+
+        println!("Unknown tiles: {}", labels.format(&unknowns));
+        match solution {
+            PartialSolution::Universal(presses) => {
+                output.print_solution(presses);
+                println!("Solves the puzzle whatever colors the unknown tiles are");
+            }
+            PartialSolution::PerCompletion(solved) => {
+                for (completion, presses) in solved {
+                    let colors: Vec<String> = unknowns
+                        .iter()
+                        .map(|&(row, col)| {
+                            let color = *completion.original().get(row, col);
+                            let name = output.color_labels().name(color);
+                            format!("{} {name}", labels.format(&[(row, col)]))
+                        })
+                        .collect();
+                    let presses = match presses {
+                        Some(presses) => labels.format(presses),
+                        None => String::from("no solution"),
+                    };
+                    println!("{}: {presses}", colors.join(", "));
+                }
+            }
+        }
+    }
+
+    fn failed(&mut self, e: &LineError) {
+        self.failures += 1;
+        if self.profile == OutputProfile::Quiet {
+            match &e.id {
+                Some(id) => println!("{id}\t"),
+                None => println!(),
+            }
+        }
+        match e.kind {
+            FailureKind::TimedOut => self.timeouts += 1,
+            // Searches only drop states once they hold as many as they are allowed
+            FailureKind::MemoryCapped => {
+                self.capped += 1;
+                self.peak_states = self.peak_states.max(self.memory_bound.unwrap_or(0));
+            }
+            _ => {}
+        }
+        match self.errors {
+            ErrorFormat::Text => eprintln!("{}", e),
+            ErrorFormat::Json => eprintln!(
+                "{}",
+                serde_json::to_string(e).expect("errors always serialize")
+            ),
+        }
+    }
+}
+
+/// Prints each result.
+fn print_results(
+    results: Vec<Result<Answer, LineError>>,
+    report: &mut Report,
+    output: &Output,
+) -> io::Result<()> {
+    for result in results {
+        match result {
+            Ok(Answer::Solved((puzzle, solution, optimality, id))) => {
+                report.solved(&puzzle, &solution, optimality, id.as_deref(), output)?
+            }
+            Ok(Answer::Partial(puzzle, solution, id)) => {
+                report.partial(&puzzle, &solution, id.as_deref(), output)
+            }
+            Err(e) => report.failed(&e),
+        }
+    }
+    Ok(())
+}
+
+/// Solves each puzzle in `text`, whose lines are numbered from `first_line`, printing
+/// problems on stdout under the line they were found on instead of reporting them, for
+/// puzzles being edited as they are solved.
+pub fn solve_inline(
+    name: &str,
+    first_line: usize,
+    text: &str,
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> io::Result<()> {
+    let options = report.options();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        match solve_line(
+            name,
+            first_line + i,
+            line,
+            rules,
+            output.color_labels(),
+            &options,
+        ) {
+            Ok(Answer::Solved((puzzle, solution, optimality, id))) => {
+                report.solved(&puzzle, &solution, optimality, id.as_deref(), output)?
+            }
+            Ok(Answer::Partial(puzzle, solution, id)) => {
+                report.partial(&puzzle, &solution, id.as_deref(), output)
+            }
+            Err(e) => print!("{}", inline_error(line, &e)),
+        }
+    }
+    Ok(())
+}
+
+/// A line with a problem, marking the column it was found at if known.
+fn inline_error(line: &str, e: &LineError) -> String {
+    match e.column {
+        Some(column) => format!("{line}\n{:>column$}\n{e}\n", "^"),
+        None => format!("{line}\n{e}\n"),
+    }
+}
+
+/// The puzzles in `input` with the line each starts on: each non-blank line, or with
+/// `board` each run of non-blank lines, holding a puzzle laid out as a board.
+fn read_puzzles(
+    input: impl BufRead,
+    board: bool,
+) -> impl Iterator<Item = io::Result<(usize, String)>> {
+    let mut lines = input.lines().enumerate();
+    std::iter::from_fn(move || {
+        let mut puzzle: Option<(usize, String)> = None;
+        for (i, line) in lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if line.trim_end().is_empty() {
+                if puzzle.is_some() {
+                    break;
+                }
+                continue;
+            }
+            match &mut puzzle {
+                Some((_, text)) => {
+                    text.push('\n');
+                    text.push_str(&line);
+                }
+                None => puzzle = Some((i + 1, line)),
+            }
+            if !board {
+                break;
+            }
+        }
+        puzzle.map(Ok)
+    })
+}
+
+/// Solves each puzzle in `input`, reporting results in input order. With `board`,
+/// puzzles are laid out as boards separated by blank lines rather than one per line.
+fn solve_lines(
+    name: &str,
+    input: impl BufRead,
+    board: bool,
+    chunk_lines: usize,
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut chunk = Vec::with_capacity(chunk_lines);
+    let mut puzzles = read_puzzles(input, board);
+
+    loop {
+        chunk.clear();
+        for puzzle in puzzles.by_ref() {
+            chunk.push(puzzle?);
+            if chunk.len() == chunk_lines {
+                break;
+            }
+        }
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        let options = report.options();
+        let results = chunk
+            .par_iter()
+            .map(|(line_no, line)| {
+                solve_line(
+                    name,
+                    *line_no,
+                    line.trim_end(),
+                    rules,
+                    output.color_labels(),
+                    &options,
+                )
+            })
+            .collect();
+        print_results(results, report, output)?;
+    }
+}
+
+/// Solves the puzzles in each file in turn, where `-` or no files at all means stdin.
+/// With `board`, puzzles are laid out as boards separated by blank lines.
+pub fn solve_files(
+    files: &[PathBuf],
+    jobs: Option<usize>,
+    board: bool,
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()?;
+    }
+    let chunk_lines = if jobs == Some(1) { 1 } else { CHUNK_LINES };
+
+    if files.is_empty() {
+        return solve_lines(
+            "<stdin>",
+            io::stdin().lock(),
+            board,
+            chunk_lines,
+            report,
+            output,
+            rules,
+        );
+    }
+
+    for path in files {
+        if path.as_os_str() == "-" {
+            solve_lines(
+                "<stdin>",
+                io::stdin().lock(),
+                board,
+                chunk_lines,
+                report,
+                output,
+                rules,
+            )?;
+        } else {
+            let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let name = path.display().to_string();
+            solve_lines(
+                &name,
+                BufReader::new(file),
+                board,
+                chunk_lines,
+                report,
+                output,
+                rules,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Solves the puzzles in some text, one per line or as boards with `board`, such as
+/// text from the clipboard.
+#[cfg_attr(not(feature = "clipboard"), expect(dead_code))]
+pub fn solve_text(
+    name: &str,
+    text: &str,
+    board: bool,
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> Result<(), Box<dyn std::error::Error>> {
+    solve_lines(
+        name,
+        text.as_bytes(),
+        board,
+        CHUNK_LINES,
+        report,
+        output,
+        rules,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Color;
+
+    fn solved(answer: Answer) -> Solved {
+        match answer {
+            Answer::Solved(solved) => solved,
+            Answer::Partial(..) => panic!("the puzzle has no unknown tiles"),
+        }
+    }
+
+    #[test]
+    fn errors_name_the_line_and_column() {
+        let rules = RuleSet::standard();
+
+        let (_, solution, _, _) = solved(
+            solve_line(
+                "in.txt",
+                1,
+                "wwwwwww-w---w",
+                &rules,
+                &ColorLabels::default(),
+                &SolveOptions::default(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(solution, [(0, 2), (0, 1)]);
+        let err = solve_line(
+            "in.txt",
+            3,
+            "wwwwx",
+            &rules,
+            &ColorLabels::default(),
+            &SolveOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "in.txt:3:5: unknown color code 'x'");
+        assert_eq!(
+            serde_json::to_string(&err).unwrap(),
+            r#"{"file":"in.txt","line":3,"column":5,"kind":"invalid","error":"unknown color code 'x'"}"#
+        );
+    }
+
+    #[test]
+    fn json_lines_carry_their_ids() {
+        let rules = RuleSet::standard();
+        let solve = |line| {
+            solve_line(
+                "in.jsonl",
+                4,
+                line,
+                &rules,
+                &ColorLabels::default(),
+                &SolveOptions::default(),
+            )
+        };
+
+        let (_, solution, _, id) =
+            solved(solve(r#"{"goals": "wwww", "grid": "www-w---w", "id": "trunk-3"}"#).unwrap());
+        assert_eq!(solution, [(0, 2), (0, 1)]);
+        assert_eq!(id.as_deref(), Some("trunk-3"));
+
+        let err = solve(r#"{"goals": "wwww", "grid": "wwx-w---w", "id": 7}"#).unwrap_err();
+        assert_eq!(err.to_string(), "in.jsonl:4: 7: unknown color code 'x'");
+    }
+
+    #[test]
+    fn boards_are_read_between_blank_lines() {
+        let input = "wwww\nwww\nw-w\n--w\n\n\nwwwwwww-w---w\n";
+        let puzzles: Vec<(usize, String)> = read_puzzles(input.as_bytes(), true)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            puzzles,
+            [
+                (1, String::from("wwww\nwww\nw-w\n--w")),
+                (7, String::from("wwwwwww-w---w"))
+            ]
+        );
+
+        let (_, solution, _, _) = solved(
+            solve_line(
+                "in.txt",
+                1,
+                &puzzles[0].1,
+                &RuleSet::standard(),
+                &ColorLabels::default(),
+                &SolveOptions::default(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(solution, [(0, 2), (0, 1)]);
+    }
+
+    #[test]
+    fn timeouts_are_reported_apart() {
+        let rules = RuleSet::standard();
+
+        let err = solve_line(
+            "in.txt",
+            2,
+            "wwwwwww-w---w",
+            &rules,
+            &ColorLabels::default(),
+            &SolveOptions {
+                time_limit: Some(Duration::ZERO),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "in.txt:2: timed out after 0ns");
+        assert_eq!(err.kind, FailureKind::TimedOut);
+
+        let mut report = Report::new(ErrorFormat::Text);
+        report.failed(&err);
+        assert_eq!((report.failures, report.timeouts), (1, 1));
+    }
+
+    #[test]
+    fn unsolvable_puzzles_say_what_was_closest() {
+        let rules = RuleSet::standard();
+        let err = solve_line(
+            "in.txt",
+            1,
+            "wwwkwww-w---w",
+            &rules,
+            &ColorLabels::default(),
+            &SolveOptions::default(),
+        );
+        let err = err.unwrap_err();
+        assert_eq!(err.kind, FailureKind::Unsolvable);
+        assert!(
+            err.error.ends_with(
+                "the closest matches NW, NE and SW after pressing 3 2, and SE never matches"
+            ),
+            "{}",
+            err.error
+        );
+
+        let options = SolveOptions::default();
+        let solve = |line| solve_line("in.txt", 1, line, &rules, &ColorLabels::default(), &options);
+        let err = solve("wwwrw-w---w--").unwrap_err();
+        assert!(
+            err.error.ends_with(
+                "the closest is the start, matching NW, NE and SW, and SE never matches"
+            ),
+            "{}",
+            err.error
+        );
+        let err = solve("rrrr---------").unwrap_err();
+        assert_eq!(
+            err.error,
+            "puzzle has no solution: no corner can be matched in 1 reachable grid"
+        );
+    }
+
+    #[test]
+    fn unknown_tiles_are_solved_for_every_color() {
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White));
+        let options = SolveOptions::default();
+        let solve = |line| solve_line("in.txt", 1, line, &rules, &ColorLabels::default(), &options);
+
+        let Ok(Answer::Partial(puzzle, solution, _)) = solve("wwwwwww-?---w") else {
+            panic!("the puzzle has an unknown tile");
+        };
+        assert_eq!(puzzle.unknowns(), [(1, 1)]);
+        assert_eq!(solution, PartialSolution::Universal(vec![(0, 2), (0, 1)]));
+
+        let err = solve("ww?wwww-w---w").unwrap_err();
+        assert_eq!(err.kind, FailureKind::Invalid);
+        assert_eq!(err.column, Some(3));
+    }
+
+    #[test]
+    fn rule_usage_reads_as_a_list() {
+        let usage = [
+            RuleUse {
+                color: Color::Black,
+                acting_as: None,
+                presses: 3,
+            },
+            RuleUse {
+                color: Color::Blue,
+                acting_as: Some(Color::Orange),
+                presses: 1,
+            },
+        ];
+        assert_eq!(
+            describe_usage(&usage, &ColorLabels::default()),
+            "3 black, 1 blue as orange"
+        );
+    }
+
+    #[test]
+    fn inline_errors_point_at_the_column() {
+        let rules = RuleSet::standard();
+        let options = SolveOptions::default();
+        let line = "wwwwwwx-w---w";
+        let err =
+            solve_line("in.txt", 4, line, &rules, &ColorLabels::default(), &options).unwrap_err();
+        assert_eq!(
+            inline_error(line, &err),
+            "wwwwwwx-w---w\n      ^\nin.txt:4:7: unknown color code 'x'\n"
+        );
+    }
+
+    #[test]
+    fn timeouts_parse_with_units() {
+        assert_eq!(parse_timeout("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_timeout("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_timeout("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_timeout("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_timeout("5h").is_err());
+        assert!(parse_timeout("soon").is_err());
+    }
+
+    #[test]
+    fn sizes_parse_with_units() {
+        assert_eq!(parse_size("512M"), Ok(512 << 20));
+        assert_eq!(parse_size("64KiB"), Ok(64 << 10));
+        assert_eq!(parse_size("1.5g"), Ok(3 << 29));
+        assert_eq!(parse_size("100"), Ok(100));
+        assert!(parse_size("5T").is_err());
+        assert_eq!(format_size(3 << 19), "1.5 MiB");
+        assert_eq!(format_size(100), "100 B");
+    }
+
+    #[test]
+    fn memory_capped_searches_say_whether_they_dropped_states() {
+        let rules = RuleSet::standard();
+        let solve = |line, bound| {
+            let options = SolveOptions {
+                memory_bound: Some(bound),
+                ..Default::default()
+            };
+            solve_line("in.txt", 1, line, &rules, &ColorLabels::default(), &options)
+        };
+
+        let (_, solution, optimality, _) = solved(solve("wwwwwww-w---w", 100_000).unwrap());
+        assert_eq!(solution, [(0, 2), (0, 1)]);
+        let Optimality::Bounded(stats) = optimality else {
+            panic!("{optimality:?}");
+        };
+        assert_eq!(stats.dropped, 0);
+
+        let err = solve("wwwkwww-w---w", 1).unwrap_err();
+        assert_eq!(err.kind, FailureKind::MemoryCapped);
+        let mut report = Report {
+            memory_bound: Some(1),
+            ..Report::new(ErrorFormat::Text)
+        };
+        report.failed(&err);
+        assert_eq!((report.capped, report.peak_states), (1, 1));
+    }
+}
@@ -0,0 +1,106 @@
+//! A fixed suite of puzzles to time the solver with, so results can be compared between
+//! machines and versions.
+
+use std::time::{Duration, Instant};
+
+use mora_jai_core::{Grid, Puzzle, RuleSet};
+use mora_jai_io::notation;
+
+/// The puzzles timed by the benchmark in notation, needing from 3 to 15 presses. They
+/// were generated once at random, as generating them on every run would take far longer
+/// than solving them.
+const SUITE: [&str; 24] = [
+    "kkbykgw-wrgby",
+    "vvkrvvwrwk-bg",
+    "bypopbykkkpyo",
+    "gbkogo-kbgoov",
+    "vkrw-bvkprwgk",
+    "gbpy-gbbpypww",
+    "bkoggr--ryowb",
+    "yrbvrwkovygby",
+    "bwkbgobwbvykv",
+    "ggwrkwrg-yg-k",
+    "oopwgowo-w-pp",
+    "yvwkkwvybkkyw",
+    "ggykprggygryk",
+    "brvrgyyvwwbrr",
+    "bpvrvrygprbyp",
+    "bkvg-gw-rvvbo",
+    "orpbrppg-bovk",
+    "krykyrgwykkbr",
+    "ogpbov-pkwb-g",
+    "pvgpvppr-v-gv",
+    "rrbgwbrr-bgbw",
+    "kryyopgywyyrv",
+    "wkobpywb-owkr",
+    "wogwv-yrw-kog",
+];
+
+fn suite() -> Vec<Puzzle> {
+    let rules = RuleSet::standard();
+    SUITE
+        .iter()
+        .map(|text| notation::parse_puzzle(text, &rules).expect("suite puzzles parse"))
+        .collect()
+}
+
+/// The duration at or below which `percent` percent of sorted `times` fall, by the
+/// nearest-rank method.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Solves the suite `rounds` times, printing throughput and latency percentiles.
+pub fn bench(rounds: usize) {
+    let suite = suite();
+
+    let mut times = Vec::with_capacity(suite.len() * rounds);
+    let started = Instant::now();
+    for _ in 0..rounds {
+        for puzzle in &suite {
+            let start = Instant::now();
+            std::hint::black_box(puzzle.solve());
+            times.push(start.elapsed());
+        }
+    }
+    let total = started.elapsed();
+    times.sort();
+
+    println!(
+        "Solved {} puzzles {rounds} times in {total:.2?}",
+        suite.len()
+    );
+    println!(
+        "Throughput: {:.1} puzzles/s",
+        times.len() as f64 / total.as_secs_f64()
+    );
+    println!(
+        "Latency: p50 {:.2?}  p90 {:.2?}  p99 {:.2?}  max {:.2?}",
+        percentile(&times, 50),
+        percentile(&times, 90),
+        percentile(&times, 99),
+        percentile(&times, 100)
+    );
+    println!("Grid size: {} bytes", size_of::<Grid>());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suite_parses() {
+        assert_eq!(suite().len(), SUITE.len());
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let times: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&times, 50), Duration::from_millis(5));
+        assert_eq!(percentile(&times, 90), Duration::from_millis(9));
+        assert_eq!(percentile(&times, 99), Duration::from_millis(10));
+        assert_eq!(percentile(&times, 0), Duration::from_millis(1));
+        assert_eq!(percentile(&times[..1], 50), Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,191 @@
+//! Solving the box on screen as it is played, by capturing a region of the screen with
+//! the platform's screenshot tool and reading it as [`image_import`] reads screenshots.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use mora_jai_core::{Move, RuleSet};
+use mora_jai_io::{image_import, notation};
+
+use crate::output::Output;
+
+/// A rectangle of the screen in pixels, from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Reads a region written `X,Y,W,H`, such as `100,200,640,640`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers: Vec<u32> = s
+            .split(',')
+            .map(|n| n.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("expected a region as X,Y,W,H, got {s:?}"))?;
+        let [x, y, width, height] = numbers[..] else {
+            return Err(format!("expected a region as X,Y,W,H, got {s:?}"));
+        };
+        if width == 0 || height == 0 {
+            return Err(String::from("the region must not be empty"));
+        }
+        Ok(Self {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+}
+
+/// The command capturing a region to a PNG file on this platform, with the placeholders
+/// of [`fill_command`] to be filled in: `grim` on Wayland, ImageMagick's `import` on
+/// X11, and `screencapture` on macOS.
+fn default_command() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("screencapture -x -R {x},{y},{width},{height} {file}")
+    } else if cfg!(unix) && env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some("grim -g {geometry} {file}")
+    } else if cfg!(unix) {
+        Some("import -window root -crop {width}x{height}+{x}+{y} {file}")
+    } else {
+        None
+    }
+}
+
+/// The program and arguments of a capture command, split on whitespace before filling
+/// in `{x}`, `{y}`, `{width}`, `{height}`, `{geometry}` and `{file}`, so that paths with
+/// spaces stay one argument. `{geometry}` is the region written `X,Y WxH`, as `grim`
+/// takes it.
+fn fill_command(template: &str, region: Region, file: &Path) -> Vec<String> {
+    let geometry = format!(
+        "{},{} {}x{}",
+        region.x, region.y, region.width, region.height
+    );
+    template
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{x}", &region.x.to_string())
+                .replace("{y}", &region.y.to_string())
+                .replace("{width}", &region.width.to_string())
+                .replace("{height}", &region.height.to_string())
+                .replace("{geometry}", &geometry)
+                .replace("{file}", &file.display().to_string())
+        })
+        .collect()
+}
+
+/// Runs the capture command, leaving a PNG of the region in `file`.
+fn grab(command: &[String]) -> Result<(), String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or("the capture command is empty")?;
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} failed: {status}"));
+    }
+    Ok(())
+}
+
+/// Captures `region` every `interval` until interrupted, solving the box in it whenever
+/// what it shows changes: a new box, or a press made on the current one. The region
+/// should be cropped to the face of the box, as for `import-image`. Captures read less
+/// certainly than `min_confidence` are taken to show no box and skipped.
+pub fn capture(
+    region: Region,
+    interval: Duration,
+    command: Option<&str>,
+    min_confidence: f32,
+    rules: &RuleSet,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let template = match command {
+        Some(command) => command,
+        None => default_command()
+            .ok_or("no screen capture tool is known for this platform, pass --command")?,
+    };
+    let file: PathBuf =
+        env::temp_dir().join(format!("mora-jai-capture-{}.png", std::process::id()));
+    let command = fill_command(template, region, &file);
+
+    println!("Watching the screen for a box, press Ctrl-C to stop");
+    let mut shown: Option<String> = None;
+    loop {
+        grab(&command)?;
+        let parse = image_import::parse_image_file(&file);
+        let _ = fs::remove_file(&file);
+        let parse = parse.map_err(|e| format!("reading the capture: {e}"))?;
+
+        let text = parse.notation_with_labels(output.color_labels());
+        if parse.confidence() >= min_confidence && shown.as_ref() != Some(&text) {
+            execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+            match notation::try_parse_puzzle_with_labels(&text, rules, output.color_labels()) {
+                Ok(puzzle) => match puzzle.solve() {
+                    Some(solution) => {
+                        let first = solution
+                            .presses
+                            .first()
+                            .map(|&(row, col)| Move::Tile(row, col));
+                        output.print_puzzle_highlighted(&puzzle, first);
+                        output.print_solution(&solution.presses);
+                    }
+                    None => {
+                        output.print_puzzle(&puzzle);
+                        println!("No solution, check the box was read right: {text}");
+                    }
+                },
+                Err(e) => println!("{text}: {e}"),
+            }
+            shown = Some(text);
+        }
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_fill_the_command() {
+        let region: Region = "100, 200,640,480".parse().unwrap();
+        assert_eq!(
+            region,
+            Region {
+                x: 100,
+                y: 200,
+                width: 640,
+                height: 480
+            }
+        );
+        assert!("1,2,3".parse::<Region>().is_err());
+        assert!("1,2,0,4".parse::<Region>().is_err());
+
+        let file = Path::new("/tmp/box.png");
+        assert_eq!(
+            fill_command("grim -g {geometry} {file}", region, file),
+            ["grim", "-g", "100,200 640x480", "/tmp/box.png"]
+        );
+        assert_eq!(
+            fill_command("my_capture.sh --hide_cursor {x}_{y} {file}", region, file),
+            ["my_capture.sh", "--hide_cursor", "100_200", "/tmp/box.png"]
+        );
+    }
+}
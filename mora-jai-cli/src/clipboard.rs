@@ -0,0 +1,16 @@
+//! Reading puzzles from and copying solutions to the system clipboard.
+
+use arboard::Clipboard;
+
+pub fn paste() -> Result<String, Box<dyn std::error::Error>> {
+    let text = Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| format!("could not read the clipboard: {e}"))?;
+    Ok(text)
+}
+
+pub fn copy(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| format!("could not copy to the clipboard: {e}").into())
+}
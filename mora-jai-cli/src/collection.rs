@@ -0,0 +1,204 @@
+//! Listing, adding to and validating puzzle collections.
+
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use mora_jai_core::{ColorLabels, Difficulty, RuleSet};
+use mora_jai_io::collection::{Collection, CollectionError, Entry};
+use mora_jai_io::notation;
+
+use crate::batch;
+
+#[derive(Subcommand)]
+pub enum CollectionCommand {
+    /// List the puzzles of a collection
+    List { file: PathBuf },
+    /// Add a puzzle to a collection, creating the file if it does not exist
+    Add {
+        file: PathBuf,
+        /// The puzzle in notation or as a share code
+        puzzle: String,
+        /// The puzzle's name [default: Puzzle N, for the Nth puzzle]
+        #[arg(long)]
+        name: Option<String>,
+        /// Who made the puzzle, if not the collection's author
+        #[arg(long)]
+        author: Option<String>,
+        /// How hard the puzzle is meant to be: easy, medium or hard
+        #[arg(long)]
+        difficulty: Option<Difficulty>,
+        #[arg(long)]
+        notes: Option<String>,
+    },
+    /// Check that every puzzle of a collection parses, has a solution and a unique
+    /// name, and is as hard as its difficulty says
+    Validate { file: PathBuf },
+}
+
+fn list(collection: &Collection) {
+    let count = collection.puzzles.len();
+    match &collection.author {
+        Some(author) => println!("{} by {author}, {count} puzzles", collection.name),
+        None => println!("{}, {count} puzzles", collection.name),
+    }
+    for entry in &collection.puzzles {
+        let mut line = format!("  {}  {}", entry.notation, entry.name);
+        if let Some(difficulty) = entry.difficulty {
+            line += &format!(" ({difficulty})");
+        }
+        if let Some(author) = &entry.author {
+            line += &format!(" by {author}");
+        }
+        println!("{line}");
+    }
+}
+
+/// Reads the collection at `file`, or starts one named after the file if it does not
+/// exist yet.
+fn load_or_new(file: &Path) -> Result<Collection, CollectionError> {
+    if file.exists() {
+        return Collection::load(file);
+    }
+    Ok(Collection {
+        name: file
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+        ..Default::default()
+    })
+}
+
+fn add(collection: &mut Collection, entry: Entry) -> Result<(), String> {
+    if collection.entry(&entry.name).is_some() {
+        return Err(format!(
+            "the collection already has a puzzle named {:?}",
+            entry.name
+        ));
+    }
+    collection.puzzles.push(entry);
+    Ok(())
+}
+
+/// The problems with each entry of a collection, as the entry's name and a description.
+fn problems(collection: &Collection, rules: &RuleSet) -> Vec<(String, String)> {
+    let mut problems = vec![];
+    for (i, entry) in collection.puzzles.iter().enumerate() {
+        let mut problem = |text: String| problems.push((entry.name.clone(), text));
+        if collection.puzzles[..i]
+            .iter()
+            .any(|other| other.name == entry.name)
+        {
+            problem(String::from("another puzzle has the same name"));
+        }
+        let puzzle = match entry.puzzle(rules) {
+            Ok(puzzle) => puzzle,
+            Err(e) => {
+                problem(format!("column {}: {e}", e.column()));
+                continue;
+            }
+        };
+        let Some(solution) = puzzle.solve() else {
+            problem(String::from("puzzle has no solution"));
+            continue;
+        };
+        let actual = Difficulty::from_solution_length(solution.presses.len());
+        if let Some(difficulty) = entry.difficulty
+            && difficulty != actual
+        {
+            problem(format!(
+                "marked {difficulty} but its optimal solution of {} presses is {actual}",
+                solution.presses.len()
+            ));
+        }
+    }
+    problems
+}
+
+/// Runs a collection subcommand, returning whether it found no problems. Puzzles being
+/// added are read with the codes of `labels`, but collections are always written in
+/// standard notation so they can be shared.
+pub fn run(
+    command: CollectionCommand,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    match command {
+        CollectionCommand::List { file } => list(&Collection::load(&file)?),
+        CollectionCommand::Add {
+            file,
+            puzzle,
+            name,
+            author,
+            difficulty,
+            notes,
+        } => {
+            let puzzle = batch::parse_puzzle(&puzzle, rules, labels).map_err(|(_, e)| e)?;
+            let mut collection = load_or_new(&file)?;
+            let name = name.unwrap_or_else(|| format!("Puzzle {}", collection.puzzles.len() + 1));
+            let entry = Entry {
+                name,
+                author,
+                difficulty,
+                notation: notation::to_notation(&puzzle),
+                notes,
+            };
+            add(&mut collection, entry)?;
+            collection.save(&file)?;
+        }
+        CollectionCommand::Validate { file } => {
+            let collection = Collection::load(&file)?;
+            let problems = problems(&collection, rules);
+            for (name, problem) in &problems {
+                eprintln!("{}: {name}: {problem}", file.display());
+            }
+            if !problems.is_empty() {
+                return Ok(false);
+            }
+            println!("{} puzzles are valid", collection.puzzles.len());
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, difficulty: Option<Difficulty>, notation: &str) -> Entry {
+        Entry {
+            name: name.to_string(),
+            author: None,
+            difficulty,
+            notation: notation.to_string(),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn problems_name_their_entries() {
+        let collection = Collection {
+            name: String::from("pack"),
+            author: None,
+            puzzles: vec![
+                entry("fine", Some(Difficulty::Easy), "wwwwwww-w---w"),
+                entry("fine", None, "wwwwwww-w---w"),
+                entry("misjudged", Some(Difficulty::Hard), "wwwwwww-w---w"),
+                entry("typo", None, "wwwwwww-w---x"),
+            ],
+        };
+        let problems = problems(&collection, &RuleSet::standard());
+        let names: Vec<&str> = problems.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["fine", "misjudged", "typo"]);
+        assert_eq!(
+            problems[1].1,
+            "marked hard but its optimal solution of 2 presses is easy"
+        );
+    }
+
+    #[test]
+    fn added_names_are_unique() {
+        let mut collection = Collection::default();
+        add(&mut collection, entry("a", None, "wwwwwww-w---w")).unwrap();
+        assert!(add(&mut collection, entry("a", None, "kkbykgw-wrgby")).is_err());
+        assert_eq!(collection.puzzles.len(), 1);
+    }
+}
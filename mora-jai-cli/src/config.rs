@@ -0,0 +1,256 @@
+//! The user's settings file, `$XDG_CONFIG_HOME/mora-jai/config.toml` or
+//! `~/.config/mora-jai/config.toml`.
+//!
+//! ```toml
+//! palette = "high-contrast"
+//! color-depth = "256"
+//! symbols = true
+//! labels = "chess"
+//! graphics = "auto"
+//!
+//! [colors.black]
+//! fg = "#000000"
+//! bg = "#404040"
+//! ansi256 = { fg = 16, bg = 238 }
+//! ansi16 = { fg = "black", bg = "bright-black" }
+//!
+//! [colors.violet]
+//! name = "purple"
+//! code = "u"
+//!
+//! [keys]
+//! undo = "z"
+//! ```
+//!
+//! See [`keys`](crate::keys) for rebinding keys.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use mora_jai_core::{Color, ColorLabels};
+use mora_jai_render::palette::{ANSI16_NAMES, Indexed, Palette, Swatch};
+use serde::Deserialize;
+
+use crate::keys::{KeySpec, Keymap};
+use crate::output::{DepthChoice, GraphicsChoice, LabelChoice, PaletteChoice};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct UserConfig {
+    /// The palette to start from before applying `colors`
+    pub palette: Option<PaletteChoice>,
+    /// Detected from the environment when absent
+    pub color_depth: Option<DepthChoice>,
+    #[serde(default)]
+    pub symbols: bool,
+    /// Describe puzzles in sentences for screen readers
+    #[serde(default)]
+    pub accessible: bool,
+    /// How solutions label tiles, keypad numbers when absent
+    pub labels: Option<LabelChoice>,
+    /// Whether puzzles are drawn as images, never when absent
+    pub graphics: Option<GraphicsChoice>,
+    /// Overrides for individual colors, by their standard color name
+    #[serde(default)]
+    colors: BTreeMap<String, SwatchSpec>,
+    /// Keys rebound during play, by action name
+    #[serde(default)]
+    keys: BTreeMap<String, KeySpec>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SwatchSpec {
+    /// A `#rrggbb` color
+    fg: Option<String>,
+    bg: Option<String>,
+    ansi256: Option<IndexedSpec<u8>>,
+    ansi16: Option<IndexedSpec<String>>,
+    /// The name the color goes by in output and `--colors`
+    name: Option<String>,
+    /// The character the color is written as in puzzle notation
+    code: Option<char>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IndexedSpec<T> {
+    fg: T,
+    bg: Option<T>,
+}
+
+fn parse_hex(s: &str) -> Result<[u8; 3], String> {
+    let hex = s
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.is_ascii())
+        .ok_or_else(|| format!("expected a #rrggbb color but found {s:?}"))?;
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid color {s:?}"))
+    };
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn parse_ansi16(name: &str) -> Result<u8, String> {
+    ANSI16_NAMES
+        .iter()
+        .position(|&n| n == name)
+        .map(|i| i as u8)
+        .ok_or_else(|| format!("unknown terminal color {name:?}"))
+}
+
+/// Where the settings file is looked for when `--config` is not given.
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_home.join("mora-jai").join("config.toml"))
+}
+
+impl UserConfig {
+    /// Reads the settings file at `path`, or the default location if `path` is `None`.
+    /// A missing file at the default location gives the default settings.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(e) => return Err(format!("{}: {e}", path.display()).into()),
+        };
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        // Check the color overrides now rather than when they are first drawn
+        config.apply_colors(Palette::standard())?;
+        config.color_labels()?;
+        config.keymap()?;
+        Ok(config)
+    }
+
+    /// The default keys for play with any rebound in the settings file.
+    pub fn keymap(&self) -> Result<Keymap, String> {
+        Keymap::from_config(&self.keys)
+    }
+
+    /// The names and notation codes of colors, with any renamed in the settings file.
+    pub fn color_labels(&self) -> Result<ColorLabels, String> {
+        let mut labels = vec![];
+        for (name, spec) in &self.colors {
+            if spec.name.is_none() && spec.code.is_none() {
+                continue;
+            }
+            let color = Color::from_name(name).ok_or_else(|| format!("unknown color {name:?}"))?;
+            labels.push((
+                color,
+                spec.name
+                    .clone()
+                    .unwrap_or_else(|| color.name().to_string()),
+                spec.code.unwrap_or_else(|| color.code()),
+            ));
+        }
+        ColorLabels::new(labels).map_err(|e| e.to_string())
+    }
+
+    /// Applies the color overrides to a palette.
+    pub fn apply_colors(&self, mut palette: Palette) -> Result<Palette, String> {
+        for (name, spec) in &self.colors {
+            let color = Color::from_name(name).ok_or_else(|| format!("unknown color {name:?}"))?;
+
+            if spec.fg.is_some() || spec.bg.is_some() {
+                let current = palette.get(color);
+                let fg = spec.fg.as_deref().map(parse_hex).transpose()?;
+                let bg = spec.bg.as_deref().map(parse_hex).transpose()?;
+                palette.set(
+                    color,
+                    Swatch {
+                        fg: fg.unwrap_or(current.fg),
+                        bg: bg.or(current.bg),
+                    },
+                );
+            }
+            if let Some(IndexedSpec { fg, bg }) = &spec.ansi256 {
+                palette.set_ansi256(color, Indexed { fg: *fg, bg: *bg });
+            }
+            if let Some(IndexedSpec { fg, bg }) = &spec.ansi16 {
+                let fg = parse_ansi16(fg)?;
+                let bg = bg.as_deref().map(parse_ansi16).transpose()?;
+                palette.set_ansi16(color, Indexed { fg, bg });
+            }
+        }
+        Ok(palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_override_the_palette() {
+        let config = UserConfig::parse(
+            r##"
+            palette = "high-contrast"
+            color-depth = "16"
+            labels = "row-col"
+
+            [colors.red]
+            fg = "#c00000"
+
+            [colors.pink]
+            ansi256 = { fg = 218 }
+            ansi16 = { fg = "bright-magenta", bg = "black" }
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.palette, Some(PaletteChoice::HighContrast));
+        assert_eq!(config.color_depth, Some(DepthChoice::Ansi16));
+        assert_eq!(config.labels, Some(LabelChoice::RowCol));
+
+        let palette = config.apply_colors(Palette::standard()).unwrap();
+        assert_eq!(palette.get(Color::Red), Swatch::fg([192, 0, 0]));
+        assert_eq!(
+            palette.get_ansi256(Color::Pink),
+            Indexed { fg: 218, bg: None }
+        );
+        assert_eq!(
+            palette.get_ansi16(Color::Pink),
+            Indexed {
+                fg: 13,
+                bg: Some(0)
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_colors_are_rejected() {
+        assert!(UserConfig::parse("[colors.teal]\nfg = \"#008080\"").is_err());
+        assert!(UserConfig::parse("[colors.red]\nfg = \"red\"").is_err());
+        assert!(UserConfig::parse("[colors.red]\nansi16 = { fg = \"mauve\" }").is_err());
+        assert!(UserConfig::parse("[keys]\nundo = \"pgup\"").is_err());
+        assert!(UserConfig::parse("[colors.violet]\ncode = \"p\"").is_err());
+    }
+
+    #[test]
+    fn colors_can_be_renamed() {
+        let config = UserConfig::parse(
+            "[colors.violet]\nname = \"purple\"\ncode = \"u\"\n\n[colors.pink]\nname = \"rosa\"",
+        )
+        .unwrap();
+        let labels = config.color_labels().unwrap();
+        assert_eq!(labels.from_code('u'), Some(Color::Violet));
+        assert_eq!(labels.from_name("purple"), Some(Color::Violet));
+        assert_eq!(labels.name(Color::Pink), "rosa");
+        assert_eq!(labels.code(Color::Pink), 'p');
+    }
+}
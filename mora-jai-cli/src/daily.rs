@@ -0,0 +1,86 @@
+//! The daily puzzle, which is the same for everyone on a given UTC date.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use mora_jai_core::{Puzzle, RuleSet};
+
+use crate::output::Output;
+use crate::play::{self, Game, Outcome};
+use crate::stats::Stats;
+
+/// A calendar date in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("the clock is after 1970")
+            .as_secs();
+        Self::from_days((secs / 86_400) as i64)
+    }
+
+    /// The date a number of days after 1970-01-01, using Howard Hinnant's
+    /// `civil_from_days` algorithm.
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+
+    /// The seed for this date's puzzle, such as 20250410 for 2025-04-10.
+    fn seed(&self) -> u64 {
+        (self.year * 10_000) as u64 + u64::from(self.month * 100 + self.day)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+pub fn daily(rules: &RuleSet, output: &Output) -> Result<(), Box<dyn std::error::Error>> {
+    let date = Date::today();
+
+    println!("Generating the puzzle for {date}...");
+    let puzzle = Puzzle::new_seeded(rules.clone(), date.seed());
+    let outcome = play::play(&mut Game::new(puzzle, None), output)?;
+    Stats::record_to_file(outcome.score());
+    let Outcome::Solved(score) = outcome else {
+        return Ok(());
+    };
+
+    println!();
+    println!("Mora Jai daily {date}");
+    println!("{score}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_from_days() {
+        let date = |year, month, day| Date { year, month, day };
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days(20_742), date(2026, 10, 16));
+        assert_eq!(date(2026, 10, 16).to_string(), "2026-10-16");
+        assert_eq!(date(2026, 10, 16).seed(), 20_261_016);
+    }
+}
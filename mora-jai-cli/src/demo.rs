@@ -0,0 +1,98 @@
+//! Generating random puzzles and playing out their solutions on a loop, as a screensaver
+//! and as a long-running check that every generated puzzle is solved as promised.
+
+use std::error::Error;
+use std::io::{self, IsTerminal};
+use std::ops::RangeInclusive;
+use std::thread;
+use std::time::Duration;
+
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use mora_jai_core::{Move, Puzzle, RuleSet};
+use mora_jai_io::notation;
+
+use crate::animate;
+use crate::output::Output;
+
+/// The moves solving a generated puzzle, after checking that its optimal solution has a
+/// length in `lengths` and that making the moves locks every corner.
+fn checked_moves(puzzle: &Puzzle, lengths: &RangeInclusive<usize>) -> Result<Vec<Move>, String> {
+    let solution = puzzle
+        .solve()
+        .ok_or("generated a puzzle with no solution")?;
+    if !lengths.contains(&solution.presses.len()) {
+        return Err(format!(
+            "generated a puzzle solved in {} presses, outside {}..={}",
+            solution.presses.len(),
+            lengths.start(),
+            lengths.end()
+        ));
+    }
+    let moves = puzzle.moves_for(&solution.presses);
+    let mut played = puzzle.clone();
+    for &action in &moves {
+        played.apply(action);
+    }
+    if !played.is_solved() {
+        return Err(String::from("the solver's moves left corners unlocked"));
+    }
+    Ok(moves)
+}
+
+/// Generates puzzles with optimal solutions of `lengths` presses and animates each
+/// solution, waiting `delay` between moves and `pause` between puzzles, until `count`
+/// puzzles are shown or forever if there is no count. Stops with an error naming the
+/// puzzle if the generator or solver gets one wrong.
+pub fn demo(
+    rules: &RuleSet,
+    lengths: RangeInclusive<usize>,
+    count: Option<usize>,
+    delay: Duration,
+    pause: Duration,
+    output: &Output,
+) -> Result<(), Box<dyn Error>> {
+    let mut stdout = io::stdout();
+    let clear = stdout.is_terminal();
+    for shown in 1.. {
+        if count.is_some_and(|count| shown > count) {
+            break;
+        }
+        let puzzle = Puzzle::new_random_with_solution_length(rules.clone(), lengths.clone());
+        let text = notation::to_notation_with_labels(&puzzle, output.color_labels());
+        let moves = checked_moves(&puzzle, &lengths).map_err(|e| format!("{text}: {e}"))?;
+
+        if clear {
+            execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+        }
+        println!("Puzzle {shown}: {text}");
+        animate::animate(&puzzle, &moves, delay, output)?;
+        thread::sleep(pause);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Color;
+
+    #[test]
+    fn generated_puzzles_check_out() {
+        // A few colors keep generation quick in debug builds
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White | Color::Black));
+        for _ in 0..3 {
+            let puzzle = Puzzle::new_random_with_solution_length(rules.clone(), 1..=4);
+            let moves = checked_moves(&puzzle, &(1..=4)).unwrap();
+            assert!(moves.len() >= 5, "{moves:?}");
+        }
+
+        let puzzle = notation::parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        assert_eq!(
+            checked_moves(&puzzle, &(3..=5)).unwrap_err(),
+            "generated a puzzle solved in 2 presses, outside 3..=5"
+        );
+    }
+}
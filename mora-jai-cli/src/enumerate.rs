@@ -0,0 +1,58 @@
+//! Reporting on every puzzle of a few colors, such as how many can be solved at all.
+
+use mora_jai_core::{Census, ColorLabels, Puzzle, RuleSet};
+use mora_jai_io::notation;
+
+/// Describes what was found, with a row for each optimal solution length.
+fn describe(census: &Census, rules: &RuleSet, labels: &ColorLabels) -> String {
+    let colors: Vec<&str> = rules.colors().map(|color| labels.name(color)).collect();
+    let mut out = format!("Colors: {}\n", colors.join(", "));
+    out += &format!("Puzzles: {}\n", census.puzzles);
+    out += &format!(
+        "Solvable: {} ({:.2}%)\n",
+        census.solvable(),
+        census.solvable_fraction() * 100.0
+    );
+    let (Some(depth), Some(deepest)) = (census.max_depth(), &census.deepest) else {
+        return out;
+    };
+    out += &format!(
+        "Deepest: {depth} presses, such as {}\n",
+        notation::to_notation_with_labels(deepest, labels)
+    );
+    out += "Presses  Puzzles\n";
+    for (presses, &count) in census.lengths.iter().enumerate() {
+        out += &format!("{presses:>7}  {count}\n");
+    }
+    out
+}
+
+/// Solves every puzzle of the colors left in `rules` on `jobs` threads and prints the
+/// results.
+pub fn enumerate(rules: &RuleSet, jobs: usize, labels: &ColorLabels) {
+    let census = Puzzle::enumerate(rules, jobs);
+    print!("{}", describe(&census, rules, labels));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Color;
+
+    #[test]
+    fn reports_list_every_length() {
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White));
+        let census = Puzzle::enumerate(&rules, 1);
+        let report = describe(&census, &rules, &ColorLabels::default());
+
+        assert!(
+            report.starts_with("Colors: gray, white\nPuzzles: 512\n"),
+            "{report}"
+        );
+        let rows = report
+            .lines()
+            .skip_while(|line| !line.starts_with("Presses"));
+        assert_eq!(rows.count(), census.max_depth().unwrap() + 2);
+    }
+}
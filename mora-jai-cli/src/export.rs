@@ -0,0 +1,142 @@
+//! Printing puzzles in forms meant for other programs and devices.
+
+#[cfg(any(feature = "png", feature = "qr", feature = "svg"))]
+use std::path::Path;
+#[cfg(feature = "png")]
+use std::path::PathBuf;
+
+use mora_jai_core::Puzzle;
+
+/// Error for puzzles with custom colors, which share codes cannot describe.
+const NO_SHARE_CODE: &str = "puzzles with custom colors have no share code";
+
+pub fn print_share_code(puzzle: &Puzzle) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", puzzle.to_share_code().ok_or(NO_SHARE_CODE)?);
+    Ok(())
+}
+
+/// Writes a QR code of the puzzle's share code to a PNG file, or draws it in the
+/// terminal if there is no file.
+#[cfg(feature = "qr")]
+pub fn export_qr(puzzle: &Puzzle, file: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    use mora_jai_render::qr;
+
+    match file {
+        Some(file) => qr::render_qr_image(puzzle)
+            .ok_or(NO_SHARE_CODE)?
+            .save(file)
+            .map_err(|e| format!("{}: {e}", file.display()))?,
+        None => print!("{}", qr::render_qr_terminal(puzzle).ok_or(NO_SHARE_CODE)?),
+    }
+    Ok(())
+}
+
+/// How many steps are drawn in each row of a contact sheet.
+#[cfg(feature = "png")]
+const SHEET_COLUMNS: u32 = 4;
+
+/// Draws a puzzle to a file whose extension picks the format, or draws each step of its
+/// solution as separate images or as one contact sheet.
+#[cfg(any(feature = "png", feature = "svg"))]
+pub fn render(
+    puzzle: &Puzzle,
+    out: &Path,
+    steps: bool,
+    sheet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = out
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if steps || sheet {
+        #[cfg(feature = "png")]
+        if extension.as_deref() == Some("png") {
+            return write_steps(puzzle, out, sheet);
+        }
+        return Err("solution steps can only be drawn as .png".into());
+    }
+    match extension.as_deref() {
+        #[cfg(feature = "svg")]
+        Some("svg") => write_svg(puzzle, out),
+        #[cfg(feature = "png")]
+        Some("png") => save_png(&mora_jai_render::raster::render_image(puzzle, None), out),
+        _ => Err(format!("{}: unsupported format", out.display()).into()),
+    }
+}
+
+#[cfg(feature = "svg")]
+fn write_svg(puzzle: &Puzzle, file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let svg = mora_jai_render::svg::render_svg(puzzle);
+    std::fs::write(file, svg).map_err(|e| format!("{}: {e}", file.display()).into())
+}
+
+#[cfg(feature = "png")]
+fn save_png(
+    image: &mora_jai_render::raster::RgbImage,
+    file: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    image
+        .save(file)
+        .map_err(|e| format!("{}: {e}", file.display()).into())
+}
+
+/// The file for one step, numbered after `out` with enough digits for every step.
+#[cfg(feature = "png")]
+fn step_path(out: &Path, step: usize, count: usize) -> PathBuf {
+    let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+    let width = (count - 1).to_string().len();
+    out.with_file_name(format!("{stem}-{step:0width$}.png"))
+}
+
+#[cfg(feature = "png")]
+fn write_steps(puzzle: &Puzzle, out: &Path, sheet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use mora_jai_render::raster;
+
+    let moves = puzzle
+        .remaining_moves()
+        .ok_or("the puzzle has no solution")?;
+    let images = raster::render_steps(puzzle, &moves);
+    if sheet {
+        return save_png(&raster::contact_sheet(&images, SHEET_COLUMNS), out);
+    }
+    for (step, image) in images.iter().enumerate() {
+        save_png(image, &step_path(out, step, images.len()))?;
+    }
+    Ok(())
+}
+
+/// Animates the puzzle's solution as a GIF.
+#[cfg(feature = "gif")]
+pub fn write_gif(
+    puzzle: &Puzzle,
+    out: &Path,
+    delay: std::time::Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    use mora_jai_render::raster;
+
+    let moves = puzzle
+        .remaining_moves()
+        .ok_or("the puzzle has no solution")?;
+    let file = File::create(out).map_err(|e| format!("{}: {e}", out.display()))?;
+    raster::encode_gif(
+        raster::render_steps(puzzle, &moves),
+        delay,
+        BufWriter::new(file),
+    )
+    .map_err(|e| format!("{}: {e}", out.display()).into())
+}
+
+#[cfg(all(test, feature = "png"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_numbered_after_out() {
+        let out = Path::new("guide/box.png");
+        assert_eq!(step_path(out, 3, 8), Path::new("guide/box-3.png"));
+        assert_eq!(step_path(out, 3, 11), Path::new("guide/box-03.png"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Gauntlets: chains of puzzles of rising difficulty to be solved one after another, like
+//! the boxes guarding the sanctum keys.
+
+use std::rc::Rc;
+
+use mora_jai_core::{Difficulty, Puzzle, RuleSet, Tablebase};
+
+use crate::PlayArgs;
+use crate::output::Output;
+use crate::play::{self, Game, Outcome};
+use crate::stats::Stats;
+
+/// The difficulty of box `index` of a gauntlet, rising from easy to hard along it.
+fn difficulty(index: usize, length: usize) -> Difficulty {
+    Difficulty::ALL[index * Difficulty::ALL.len() / length]
+}
+
+/// A bar of the boxes solved so far, such as `[###-------]`.
+fn progress(solved: usize, length: usize) -> String {
+    format!("[{}{}]", "#".repeat(solved), "-".repeat(length - solved))
+}
+
+/// Plays a gauntlet of `length` boxes, looking hints up in `tablebase` if given.
+/// Resetting a box or giving up on it sends the player back to the box before.
+pub fn gauntlet(
+    rules: &RuleSet,
+    output: &Output,
+    args: &PlayArgs,
+    tablebase: Option<Rc<Tablebase>>,
+    length: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Boxes are generated as they are reached, and kept for when the player is sent back
+    let mut boxes: Vec<Puzzle> = vec![];
+    let mut current = 0;
+
+    while current < length {
+        let difficulty = difficulty(current, length);
+        if current == boxes.len() {
+            println!("Generating box {}...", current + 1);
+            boxes.push(Puzzle::new_random_with_difficulty(
+                rules.clone(),
+                difficulty,
+            ));
+        }
+        println!("{}", progress(current, length));
+        println!("Box {} of {length} ({difficulty})", current + 1);
+
+        let mut game = Game::new(boxes[current].clone(), Some(args.hints));
+        game.end_on_reset = true;
+        game.practice = args.practice;
+        game.tablebase = tablebase.clone();
+        let outcome = play::play(&mut game, output)?;
+        Stats::record_to_file(outcome.score());
+        match outcome {
+            Outcome::Solved(score) => {
+                println!("{score}");
+                current += 1;
+            }
+            Outcome::Reset | Outcome::GaveUp => {
+                current = current.saturating_sub(1);
+                println!("Back to box {}", current + 1);
+            }
+            Outcome::Quit => return Ok(()),
+        }
+    }
+
+    println!("{}", progress(length, length));
+    println!("Gauntlet complete!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_rises_along_the_gauntlet() {
+        let difficulties: Vec<_> = (0..10).map(|i| difficulty(i, 10)).collect();
+        assert_eq!(difficulties[0], Difficulty::Easy);
+        assert_eq!(difficulties[4], Difficulty::Medium);
+        assert_eq!(difficulties[9], Difficulty::Hard);
+        assert!(difficulties.is_sorted());
+        assert_eq!(difficulty(0, 1), Difficulty::Easy);
+        assert_eq!(progress(3, 5), "[###--]");
+    }
+}
@@ -0,0 +1,100 @@
+//! Showing which tiles the optimal solutions of a puzzle, or of many puzzles, press most.
+
+use std::io::{self, BufRead};
+#[cfg(feature = "svg")]
+use std::path::Path;
+
+use mora_jai_core::{ColorLabels, RuleSet};
+use mora_jai_render::heatmap::render_heatmap;
+
+use crate::batch;
+
+/// How many times each tile is pressed per optimal solution, averaged over the puzzles,
+/// with the number of puzzles solved. Puzzles which fail to parse or have no solution
+/// are warned about and left out.
+fn average_presses(
+    puzzles: &[String],
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> ([[f64; 3]; 3], usize) {
+    let mut total = [[0.0; 3]; 3];
+    let mut solved = 0;
+    for text in puzzles {
+        let puzzle = match batch::parse_puzzle(text, rules, labels) {
+            Ok(puzzle) => puzzle,
+            Err((_, e)) => {
+                eprintln!("warning: {text}: {e}");
+                continue;
+            }
+        };
+        let Some(counts) = puzzle.press_counts() else {
+            eprintln!("warning: {text}: puzzle has no solution");
+            continue;
+        };
+        for (total, presses) in total.iter_mut().zip(counts.per_solution()) {
+            for (total, presses) in total.iter_mut().zip(presses) {
+                *total += presses;
+            }
+        }
+        solved += 1;
+    }
+    if solved > 0 {
+        total = total.map(|row| row.map(|presses| presses / solved as f64));
+    }
+    (total, solved)
+}
+
+/// Prints how often each tile is pressed per optimal solution of the puzzles, or of the
+/// puzzles on stdin, one per line, if none are given. With `svg`, the heatmap is written
+/// there as a diagram instead.
+pub fn heatmap(
+    puzzles: &[String],
+    #[cfg(feature = "svg")] svg: Option<&Path>,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin;
+    let puzzles = if puzzles.is_empty() {
+        stdin = io::stdin()
+            .lock()
+            .lines()
+            .filter(|line| line.as_ref().is_ok_and(|line| !line.trim().is_empty()))
+            .map(|line| line.map(|line| line.trim().to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        &stdin
+    } else {
+        puzzles
+    };
+
+    let (presses, solved) = average_presses(puzzles, rules, labels);
+    if solved == 0 {
+        return Err("no puzzles with solutions to count presses in".into());
+    }
+
+    #[cfg(feature = "svg")]
+    if let Some(file) = svg {
+        let diagram = mora_jai_render::heatmap::render_heatmap_svg(&presses);
+        return std::fs::write(file, diagram)
+            .map_err(|e| format!("{}: {e}", file.display()).into());
+    }
+    let puzzles = if solved == 1 { "puzzle" } else { "puzzles" };
+    println!("Presses of each tile per optimal solution, over {solved} {puzzles}:");
+    print!("{}", render_heatmap(&presses));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_are_averaged_over_solvable_puzzles() {
+        let rules = RuleSet::standard();
+        let labels = ColorLabels::default();
+        // Pressed 3 2 or 7 4, then a puzzle with no solution which is left out
+        let puzzles = [String::from("wwwwwww-w---w"), String::from("kkkkwww-w---w")];
+        let (presses, solved) = average_presses(&puzzles, &rules, &labels);
+        assert_eq!(solved, 1);
+        assert_eq!(presses, [[0.0, 0.5, 0.5], [0.5, 0.0, 0.0], [0.5, 0.0, 0.0]]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Which keys do what during play, in both the line-based play mode and the TUI.
+//!
+//! The `[keys]` table of the settings file rebinds actions by name, to one key or a
+//! list of keys. Keys are single characters or one of `up`, `down`, `left`, `right`,
+//! `enter`, `space`, `tab` and `backspace`. Rebinding an action replaces its default
+//! keys, and a key taken by a rebound action is removed from the action it was bound to
+//! by default. For a WASD layout:
+//!
+//! ```toml
+//! [keys]
+//! up = "w"
+//! left = "a"
+//! down = "s"
+//! right = "d"
+//! press = ["space", "enter"]
+//! nw = "q"
+//! ne = "e"
+//! sw = "z"
+//! se = "c"
+//! ```
+//!
+//! Saved games and replays always record moves with the default keys, so they can be
+//! shared between players with different bindings.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use mora_jai_core::{Corner, Move};
+use serde::Deserialize;
+
+/// A key which can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+}
+
+const NAMED_KEYS: [(&str, Key); 8] = [
+    ("up", Key::Up),
+    ("down", Key::Down),
+    ("left", Key::Left),
+    ("right", Key::Right),
+    ("enter", Key::Enter),
+    ("space", Key::Char(' ')),
+    ("tab", Key::Tab),
+    ("backspace", Key::Backspace),
+];
+
+impl Key {
+    /// The key pressed in a terminal key event, if it is one which can be bound.
+    pub fn from_event(event: &KeyEvent) -> Option<Self> {
+        match event.code {
+            KeyCode::Char(c) => Some(Key::Char(c)),
+            KeyCode::Up => Some(Key::Up),
+            KeyCode::Down => Some(Key::Down),
+            KeyCode::Left => Some(Key::Left),
+            KeyCode::Right => Some(Key::Right),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Tab => Some(Key::Tab),
+            KeyCode::Backspace => Some(Key::Backspace),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(&(_, key)) = NAMED_KEYS.iter().find(|(name, _)| *name == s) {
+            return Ok(key);
+        }
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Key::Char(c)),
+            _ => Err(format!("unknown key {s:?}")),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match NAMED_KEYS.iter().find(|(_, key)| key == self) {
+            Some((name, _)) => f.write_str(name),
+            None => match self {
+                Key::Char(c) => write!(f, "{c}"),
+                _ => unreachable!("every other key has a name"),
+            },
+        }
+    }
+}
+
+/// Something a key can do during play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Move(Move),
+    Undo,
+    Hint,
+    GiveUp,
+    NewPuzzle,
+    /// Move the selected tile up, down, left or right
+    Cursor(Direction),
+    /// Press the selected tile
+    Press,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The tile next to `(row, col)` in this direction, staying on the 3x3 grid.
+    pub fn step(self, (row, col): (usize, usize)) -> (usize, usize) {
+        match self {
+            Direction::Up => ((row + 1).min(2), col),
+            Direction::Down => (row.saturating_sub(1), col),
+            Direction::Left => (row, col.saturating_sub(1)),
+            Direction::Right => (row, (col + 1).min(2)),
+        }
+    }
+}
+
+impl Action {
+    /// The action's name in the settings file.
+    fn from_name(name: &str) -> Option<Self> {
+        let action = match name {
+            "nw" => Action::Move(Move::Corner(Corner::NW)),
+            "ne" => Action::Move(Move::Corner(Corner::NE)),
+            "sw" => Action::Move(Move::Corner(Corner::SW)),
+            "se" => Action::Move(Move::Corner(Corner::SE)),
+            "undo" => Action::Undo,
+            "hint" => Action::Hint,
+            "give-up" => Action::GiveUp,
+            "new" => Action::NewPuzzle,
+            "up" => Action::Cursor(Direction::Up),
+            "down" => Action::Cursor(Direction::Down),
+            "left" => Action::Cursor(Direction::Left),
+            "right" => Action::Cursor(Direction::Right),
+            "press" => Action::Press,
+            _ => {
+                // Tiles are named by their keypad number, `tile-1` to `tile-9`
+                let n: usize = name.strip_prefix("tile-")?.parse().ok()?;
+                if !(1..=9).contains(&n) {
+                    return None;
+                }
+                Action::Move(Move::Tile((n - 1) / 3, (n - 1) % 3))
+            }
+        };
+        Some(action)
+    }
+}
+
+/// The keys rebound for an action in the settings file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The key bound to each action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    /// Keypad numbers for tiles, `q`, `w`, `a` and `s` for corners, and the arrow keys
+    /// with Enter or Space for the selected tile.
+    fn default() -> Self {
+        let mut bindings = vec![];
+        for n in 0..9 {
+            let key = Key::Char(char::from(b'1' + n as u8));
+            bindings.push((key, Action::Move(Move::Tile(n / 3, n % 3))));
+        }
+        for (c, corner) in [
+            ('q', Corner::NW),
+            ('w', Corner::NE),
+            ('a', Corner::SW),
+            ('s', Corner::SE),
+        ] {
+            bindings.push((Key::Char(c), Action::Move(Move::Corner(corner))));
+        }
+        bindings.extend([
+            (Key::Char('u'), Action::Undo),
+            (Key::Char('h'), Action::Hint),
+            (Key::Char('g'), Action::GiveUp),
+            (Key::Char('n'), Action::NewPuzzle),
+            (Key::Up, Action::Cursor(Direction::Up)),
+            (Key::Down, Action::Cursor(Direction::Down)),
+            (Key::Left, Action::Cursor(Direction::Left)),
+            (Key::Right, Action::Cursor(Direction::Right)),
+            (Key::Enter, Action::Press),
+            (Key::Char(' '), Action::Press),
+        ]);
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// The default bindings with those from the settings file applied on top.
+    pub fn from_config(keys: &BTreeMap<String, KeySpec>) -> Result<Self, String> {
+        let mut rebound: Vec<(Key, Action)> = vec![];
+        let mut bound_by: Vec<(Key, &str)> = vec![];
+        for (name, spec) in keys {
+            let action =
+                Action::from_name(name).ok_or_else(|| format!("unknown action {name:?}"))?;
+            let keys = match spec {
+                KeySpec::One(key) => std::slice::from_ref(key),
+                KeySpec::Many(keys) => keys.as_slice(),
+            };
+            for key in keys {
+                let key: Key = key.parse()?;
+                if let Some((_, other)) = bound_by.iter().find(|&&(k, _)| k == key) {
+                    return Err(format!("{key} is bound to both {other} and {name}"));
+                }
+                bound_by.push((key, name));
+                rebound.push((key, action));
+            }
+        }
+
+        let mut keymap = Self::default();
+        keymap
+            .bindings
+            .retain(|&(key, action)| !rebound.iter().any(|&(k, a)| k == key || a == action));
+        keymap.bindings.extend(rebound);
+        Ok(keymap)
+    }
+
+    /// What a key does, if anything.
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, action)| action)
+    }
+
+    /// The first key bound to an action, if any.
+    pub fn key(&self, action: Action) -> Option<Key> {
+        self.bindings
+            .iter()
+            .find(|&&(_, a)| a == action)
+            .map(|&(key, _)| key)
+    }
+
+    /// The first key bound to an action, for showing to the player.
+    pub fn label(&self, action: Action) -> String {
+        self.key(action)
+            .map_or_else(|| String::from("(unbound)"), |key| key.to_string())
+    }
+
+    /// The keys which press each tile, such as `1-9` for the defaults.
+    pub fn tile_labels(&self) -> String {
+        let labels: Vec<String> = (0..9)
+            .map(|n| self.label(Action::Move(Move::Tile(n / 3, n % 3))))
+            .collect();
+        if labels.concat() == "123456789" {
+            String::from("1-9")
+        } else {
+            labels.join(" ")
+        }
+    }
+
+    /// The keys which move the selected tile, such as `up/down/left/right`.
+    pub fn cursor_labels(&self) -> String {
+        let labels: Vec<String> = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .map(|direction| self.label(Action::Cursor(direction)))
+        .collect();
+        labels.join("/")
+    }
+
+    /// The keys which press each corner, in the order NW, NE, SW, SE.
+    pub fn corner_labels(&self) -> String {
+        let labels: Vec<String> = [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+            .into_iter()
+            .map(|corner| self.label(Action::Move(Move::Corner(corner))))
+            .collect();
+        labels.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_takes_keys_from_defaults() {
+        let config: BTreeMap<String, KeySpec> = toml::from_str(
+            r#"
+            up = "w"
+            left = "a"
+            press = ["space", "e"]
+            tile-5 = "0"
+            "#,
+        )
+        .unwrap();
+        let keymap = Keymap::from_config(&config).unwrap();
+
+        assert_eq!(
+            keymap.action(Key::Char('w')),
+            Some(Action::Cursor(Direction::Up))
+        );
+        assert_eq!(keymap.key(Action::Move(Move::Corner(Corner::NE))), None);
+        assert_eq!(keymap.action(Key::Up), None);
+        assert_eq!(keymap.action(Key::Enter), None);
+        assert_eq!(keymap.action(Key::Char('e')), Some(Action::Press));
+        assert_eq!(keymap.action(Key::Char('5')), None);
+        assert_eq!(
+            keymap.action(Key::Char('0')),
+            Some(Action::Move(Move::Tile(1, 1)))
+        );
+        assert_eq!(keymap.tile_labels(), "1 2 3 4 0 6 7 8 9");
+        assert_eq!(keymap.corner_labels(), "q/(unbound)/(unbound)/s");
+        assert_eq!(keymap.cursor_labels(), "w/down/a/right");
+    }
+
+    #[test]
+    fn invalid_bindings_are_rejected() {
+        let parse = |text| {
+            let config: BTreeMap<String, KeySpec> = toml::from_str(text).unwrap();
+            Keymap::from_config(&config)
+        };
+        assert!(parse("jump = \"j\"").is_err());
+        assert!(parse("tile-10 = \"j\"").is_err());
+        assert!(parse("undo = \"pgup\"").is_err());
+        assert!(parse("undo = \"z\"\nhint = \"z\"").is_err());
+        assert_eq!(Keymap::default().tile_labels(), "1-9");
+        assert_eq!("space".parse(), Ok(Key::Char(' ')));
+        assert_eq!(Key::Char(' ').to_string(), "space");
+    }
+}
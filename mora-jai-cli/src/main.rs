@@ -1,160 +1,895 @@
-use std::io;
-use std::io::{BufRead, Write};
-
-use colored::{ColoredString, control};
-use puzzle::{Color, Corner, Grid, Puzzle};
-
-fn print_puzzle(puzzle: &Puzzle) {
-    print!(
-        concat!(
-            "Goals: {} {} {} {}\n",
-            "{}|{}{}{}|{}\n",
-            " |{}{}{}| \n",
-            "{}|{}{}{}|{}\n"
-        ),
-        colorize(puzzle.goal(Corner::NW).name(), puzzle.goal(Corner::NW)),
-        colorize(puzzle.goal(Corner::NE).name(), puzzle.goal(Corner::NE)),
-        colorize(puzzle.goal(Corner::SW).name(), puzzle.goal(Corner::SW)),
-        colorize(puzzle.goal(Corner::SE).name(), puzzle.goal(Corner::SE)),
-        colorize("q", puzzle.get_corner(Corner::NW)),
-        colorize("7", puzzle.get_tile(2, 0)),
-        colorize("8", puzzle.get_tile(2, 1)),
-        colorize("9", puzzle.get_tile(2, 2)),
-        colorize("w", puzzle.get_corner(Corner::NE)),
-        colorize("4", puzzle.get_tile(1, 0)),
-        colorize("5", puzzle.get_tile(1, 1)),
-        colorize("6", puzzle.get_tile(1, 2)),
-        colorize("a", puzzle.get_corner(Corner::SW)),
-        colorize("1", puzzle.get_tile(0, 0)),
-        colorize("2", puzzle.get_tile(0, 1)),
-        colorize("3", puzzle.get_tile(0, 2)),
-        colorize("s", puzzle.get_corner(Corner::SE)),
-    );
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::Duration;
+
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+#[cfg(windows)]
+use colored::control;
+use mora_jai_core::{ChainLink, Color, ColorLabels, Puzzle, RuleSet, state_bytes};
+#[cfg(feature = "image")]
+use mora_jai_io::image_import;
+use mora_jai_io::notation;
+use mora_jai_io::rule_config::RuleConfig;
+
+mod analyze;
+mod animate;
+mod assist;
+mod batch;
+mod bench;
+#[cfg(feature = "image")]
+mod capture;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod collection;
+mod config;
+mod daily;
+mod demo;
+mod enumerate;
+mod export;
+mod gauntlet;
+mod heatmap;
+mod keys;
+mod output;
+mod play;
+mod quiz;
+mod race;
+mod reference;
+mod repl;
+mod replay;
+mod save;
+mod stats;
+mod tablebase;
+
+use analyze::ReportFormat;
+use batch::{ErrorFormat, OutputProfile, Report, TieBreakChoice};
+use collection::CollectionCommand;
+use config::UserConfig;
+use output::{Output, OutputArgs};
+use play::DifficultyChoice;
+use tablebase::TablebaseCommand;
+#[cfg(feature = "server")]
+mod serve;
+#[cfg(feature = "tui")]
+mod tui;
+mod watch;
+
+#[derive(Parser)]
+#[command(version, about = "Solve and play Mora Jai puzzles")]
+struct Cli {
+    /// Rule file (TOML or JSON) describing a box variant to use instead of the standard rules
+    #[arg(long, global = true)]
+    rules: Option<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Settings file to use instead of ~/.config/mora-jai/config.toml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn print_solution(solution: &[(usize, usize)]) {
-    print!("Solution: ");
-    for (row, col) in solution {
-        let num = 1 + 3 * row + col;
-        print!("{} ", num);
-    }
-    println!();
+/// Options shared by the play modes.
+#[derive(Args)]
+struct PlayArgs {
+    /// How many hints may be asked for on each puzzle
+    #[arg(long, default_value_t = 3)]
+    hints: usize,
+    /// Time each puzzle, showing when the first corner was locked and the total
+    #[arg(long)]
+    timer: bool,
+    /// Practice without resets: pressing a corner whose tile is not its goal color
+    /// warns instead of resetting the box
+    #[arg(long)]
+    practice: bool,
+    #[command(flatten)]
+    generate: GenerateArgs,
 }
 
-fn colorize(s: &str, color: Color) -> ColoredString {
-    // Import here to avoid adding .blue(), .red(), etc. methods to all strings
-    use colored::Colorize;
-
-    match color {
-        Color::Gray => s.truecolor(128, 128, 128),
-        Color::White => s.white(),
-        Color::Black => s.truecolor(0, 0, 0).on_truecolor(64, 64, 64),
-        Color::Red => s.truecolor(255, 0, 0),
-        Color::Pink => s.truecolor(255, 192, 203),
-        Color::Green => s.truecolor(0, 255, 0),
-        Color::Orange => s.truecolor(255, 165, 0),
-        Color::Yellow => s.truecolor(255, 255, 0),
-        Color::Violet => s.truecolor(127, 0, 255),
-        Color::Blue => s.truecolor(0, 0, 255),
+/// Options for generating random puzzles.
+#[derive(Args)]
+struct GenerateArgs {
+    /// How hard puzzles should be: easy, medium, hard, or the number of presses in
+    /// their optimal solution
+    #[arg(long)]
+    difficulty: Option<DifficultyChoice>,
+    /// Only use tiles of these colors, by code or name. For example `p,-` drills pink
+    /// rotations among gray tiles
+    #[arg(long, value_delimiter = ',')]
+    colors: Vec<String>,
+}
+
+impl GenerateArgs {
+    /// Removes the rules for colors not chosen with `--colors`, so that random puzzles
+    /// are drawn from only the chosen colors. Colors are looked up by their `labels`.
+    fn restrict(&self, rules: &mut RuleSet, labels: &ColorLabels) -> Result<(), String> {
+        if self.colors.is_empty() {
+            return Ok(());
+        }
+        restrict_colors(&self.colors, rules, labels)
     }
 }
 
-fn char_to_color(c: char) -> Option<Color> {
-    let color = match c {
-        '-' => Color::Gray,
-        'w' => Color::White,
-        'k' => Color::Black,
-        'r' => Color::Red,
-        'o' => Color::Orange,
-        'g' => Color::Green,
-        'y' => Color::Yellow,
-        'v' => Color::Violet,
-        'p' => Color::Pink,
-        'b' => Color::Blue,
-        _ => return None,
+/// Looks up a color by its code or name in `labels`.
+fn parse_color(name: &str, labels: &ColorLabels) -> Result<Color, String> {
+    let mut chars = name.chars();
+    let color = match (chars.next(), chars.next()) {
+        (Some(code), None) => labels.from_code(code),
+        _ => labels.from_name(name),
     };
-    Some(color)
+    color.ok_or_else(|| format!("unknown color {name:?}"))
 }
 
-fn parse_puzzle(s: &str) -> Option<Puzzle> {
-    let mut colors = s.chars().map(|c| char_to_color(c));
-    let goals = [
-        colors.next()??,
-        colors.next()??,
-        colors.next()??,
-        colors.next()??,
-    ];
+/// Removes the rules for colors other than those named, by code or name, with at least
+/// one other than gray to draw goals from. Colors are looked up by their `labels`.
+fn restrict_colors(
+    names: &[String],
+    rules: &mut RuleSet,
+    labels: &ColorLabels,
+) -> Result<(), String> {
+    let mut colors = vec![];
+    for name in names {
+        let color = parse_color(name, labels)?;
+        if rules.get(color).is_none() {
+            return Err(format!("{} tiles have no rule", labels.name(color)));
+        }
+        colors.push(color);
+    }
+    // Goals are never gray, so there must be another color to draw them from
+    if colors.iter().all(|&color| color == Color::Gray) {
+        return Err(String::from("choose at least one color other than gray"));
+    }
 
-    let r2 = [colors.next()??, colors.next()??, colors.next()??];
-    let r1 = [colors.next()??, colors.next()??, colors.next()??];
-    let r0 = [colors.next()??, colors.next()??, colors.next()??];
+    rules.retain_colors(|color| colors.contains(&color));
+    Ok(())
+}
 
-    let grid = Grid::from_rows(r2, r1, r0);
+/// Which tiles of a solved box in a campaign give the next box's goals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum LinkChoice {
+    /// The corners, so every box has the same goals
+    #[default]
+    Corners,
+    /// The middle of each edge, read top, left, right, bottom as the NW, NE, SW and SE
+    /// goals
+    Edges,
+}
 
-    Some(Puzzle::new(goals, grid))
+impl From<LinkChoice> for ChainLink {
+    fn from(choice: LinkChoice) -> Self {
+        match choice {
+            LinkChoice::Corners => ChainLink::Corners,
+            LinkChoice::Edges => ChainLink::Edges,
+        }
+    }
 }
 
-fn solve_puzzle(puzzle_str: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let puzzle = parse_puzzle(&puzzle_str).ok_or("failed to parse puzzle")?;
-    print_puzzle(&puzzle);
-    let solution = puzzle
-        .solve()
-        .ok_or("puzzle should always have a solution")?;
-    print_solution(&solution);
-    Ok(())
+#[derive(Subcommand)]
+enum Command {
+    /// Solve puzzles, one per line in notation or as share codes (the default)
+    Solve {
+        /// Files to read puzzles from, or `-` for stdin. Reads stdin if none are given
+        files: Vec<PathBuf>,
+        /// Number of puzzles to solve at once. Defaults to the number of CPUs; with 1,
+        /// each line is solved as soon as it is read
+        #[arg(long, short, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+        /// How to report puzzles which fail to parse or solve
+        #[arg(long, value_enum, default_value_t)]
+        errors: ErrorFormat,
+        /// Play each solution out on the puzzle instead of only listing its presses
+        #[arg(long)]
+        animate: bool,
+        /// Milliseconds between moves with `--animate`
+        #[arg(long, default_value_t = 600, requires = "animate")]
+        delay: u64,
+        /// Explain what each press of a solution does
+        #[arg(long)]
+        explain: bool,
+        /// Include corner presses in solutions, each pressed as soon as no later press
+        /// would unlock it
+        #[arg(long)]
+        corners: bool,
+        /// List up to this many distinct solutions, shortest first
+        #[arg(long, value_name = "COUNT")]
+        alternatives: Option<usize>,
+        /// How many presses longer than optimal the solutions listed by `--alternatives`
+        /// may be
+        #[arg(long, default_value_t = 0, requires = "alternatives")]
+        slack: usize,
+        /// Count the distinct solutions of each length up to this many presses
+        #[arg(long, value_name = "PRESSES")]
+        lengths_up_to: Option<usize>,
+        /// Give up on a puzzle after searching for this long, such as `5s` or `500ms`,
+        /// and report it as timed out
+        #[arg(long, value_parser = batch::parse_timeout)]
+        timeout: Option<Duration>,
+        /// Keep the searches solving at once within about this much memory, such as
+        /// `512M` or `2G`, by dropping states. Solutions may then be longer than optimal,
+        /// and some puzzles may go unsolved
+        #[arg(long, value_parser = batch::parse_size, conflicts_with_all = ["alternatives", "lengths_up_to"])]
+        max_memory: Option<usize>,
+        /// Which optimal solution to show when there are several
+        #[arg(long, value_enum, default_value_t)]
+        tie_break: TieBreakChoice,
+        /// How much to print for each puzzle
+        #[arg(long = "output", value_enum, default_value_t)]
+        profile: OutputProfile,
+        /// Solve the files again whenever they change, or each line as it is typed if
+        /// reading stdin, showing problems under the line they are on
+        #[arg(long, conflicts_with = "animate")]
+        watch: bool,
+        /// Read puzzles laid out like the printed board, a goals line then the rows of
+        /// tiles top first, with a blank line between puzzles
+        #[arg(long, conflicts_with = "watch")]
+        board: bool,
+        /// Copy the solutions to the clipboard as keypad numbers, one line per puzzle
+        #[cfg(feature = "clipboard")]
+        #[arg(long)]
+        copy: bool,
+        /// Solve the puzzles on the clipboard instead of reading files
+        #[cfg(feature = "clipboard")]
+        #[arg(long, conflicts_with = "files")]
+        paste: bool,
+    },
+    /// Play a randomly generated puzzle. Type `:` during play to save the game
+    Play {
+        #[command(flatten)]
+        args: PlayArgs,
+        /// Resume a game saved during play instead of generating a puzzle
+        #[arg(long)]
+        resume: Option<PathBuf>,
+        /// Record every press to this file when the game ends, for `replay`
+        #[arg(long)]
+        record: Option<PathBuf>,
+        /// Look hints up in this file, written by `tablebase build`, when it holds the
+        /// puzzle, instead of searching for them
+        #[arg(long)]
+        tablebase: Option<PathBuf>,
+        /// Play a chain of this many puzzles of rising difficulty. Resetting a box or
+        /// giving up on it sends you back one box
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u16).range(1..),
+            conflicts_with_all = ["resume", "record", "difficulty"]
+        )]
+        gauntlet: Option<u16>,
+    },
+    /// Play back a game recorded with `play --record`
+    Replay {
+        file: PathBuf,
+        /// How many times faster than the recording to play back
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+    /// Print randomly generated puzzles in notation, one per line
+    Generate {
+        #[command(flatten)]
+        args: GenerateArgs,
+        /// Number of puzzles to generate
+        #[arg(long, short, default_value_t = 1)]
+        count: usize,
+        /// Number of threads to generate puzzles on. Defaults to the number of CPUs.
+        /// Puzzles are printed in the order they are found
+        #[arg(long, short, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+        /// Print share codes instead of notation. Puzzles with custom colors, which codes
+        /// cannot describe, are still printed in notation
+        #[arg(long)]
+        share: bool,
+        /// Chain the puzzles into a campaign, where solving each box reveals the goals of
+        /// the next. Campaigns are generated on one thread, in order
+        #[arg(long)]
+        campaign: bool,
+        /// Which tiles of a solved box reveal the next box's goals
+        #[arg(long, value_enum, default_value_t, requires = "campaign")]
+        link: LinkChoice,
+    },
+    /// Print a puzzle, given in notation or as a share code, in another form
+    Export {
+        puzzle: String,
+        /// Write a QR code of the puzzle's share code to this PNG file, or draw it in the
+        /// terminal if no file is given. Without this the share code itself is printed
+        #[cfg(feature = "qr")]
+        #[arg(long, num_args = 0..=1, value_name = "FILE")]
+        qr: Option<Option<PathBuf>>,
+    },
+    /// Draw a puzzle, given in notation or as a share code, as an SVG or PNG diagram
+    #[cfg(any(feature = "png", feature = "svg"))]
+    Render {
+        puzzle: String,
+        /// Where to write the diagram. Its extension, `.svg` or `.png`, picks the format
+        out: PathBuf,
+        /// Draw the puzzle before each step of its solution and once solved, each as a
+        /// PNG numbered after `out`, such as `out-01.png`
+        #[arg(long, conflicts_with = "sheet")]
+        steps: bool,
+        /// Draw every step of the solution side by side in one PNG
+        #[arg(long)]
+        sheet: bool,
+        /// Animate the solution as a looping GIF
+        #[cfg(feature = "gif")]
+        #[arg(long, conflicts_with_all = ["steps", "sheet"])]
+        gif: bool,
+        /// Milliseconds each step is shown for with `--gif`
+        #[cfg(feature = "gif")]
+        #[arg(long, default_value_t = 800, requires = "gif")]
+        delay: u64,
+    },
+    /// Describe each color's rule, showing a press of a tile of that color on a sample
+    /// grid
+    Rules {
+        /// Only describe this color's rule, by code or name
+        #[arg(value_name = "COLOR")]
+        only: Option<String>,
+    },
+    /// Learn the rules by predicting what pressing a tile of a random grid does, scored
+    /// for each rule
+    Quiz {
+        /// Number of questions to ask
+        #[arg(long, short, default_value_t = 10)]
+        rounds: usize,
+        /// Only ask about tiles of these colors, by code or name
+        #[arg(long, value_delimiter = ',')]
+        colors: Vec<String>,
+    },
+    /// Explore puzzles at a prompt, mixing presses with hints and solutions. Type `help`
+    /// for a list of commands
+    Repl {
+        /// A puzzle in notation or as a share code to start with
+        puzzle: Option<String>,
+    },
+    /// Advise on a puzzle part way through: whether to carry on or reset, and the
+    /// quickest way to finish including corner presses
+    Assist {
+        /// The puzzle as it started, in notation or as a share code
+        puzzle: String,
+        /// The moves made so far, such as `3 2 nw`: keypad numbers for tiles, 1 at the
+        /// bottom left, and nw, ne, sw or se for corners
+        #[arg(long, num_args = 1.., conflicts_with = "grid")]
+        moves: Vec<String>,
+        /// The grid as it is now instead of the moves made: nine color codes, top row
+        /// first
+        #[arg(long)]
+        grid: Option<String>,
+        /// Corners locked so far, with `--grid`
+        #[arg(long, value_delimiter = ',', requires = "grid")]
+        locked: Vec<String>,
+    },
+    /// Play today's puzzle, which is the same for everyone on a given UTC date
+    Daily,
+    /// Race another player over the network to solve the same generated puzzle. One
+    /// player hosts with `--host` and the other joins with `--join`
+    Race {
+        /// Host a race on this port, waiting for the other player to join
+        #[arg(
+            long,
+            value_name = "PORT",
+            required_unless_present = "join",
+            conflicts_with = "join"
+        )]
+        host: Option<u16>,
+        /// Join a race hosted at this address, such as `192.168.1.5:7878`
+        #[arg(long, value_name = "ADDRESS")]
+        join: Option<String>,
+        #[command(flatten)]
+        args: PlayArgs,
+    },
+    /// Show statistics from past games
+    Stats,
+    /// Play randomly generated puzzles full-screen
+    #[cfg(feature = "tui")]
+    Tui(PlayArgs),
+    /// Read puzzles from screenshots of the in-game box
+    #[cfg(feature = "image")]
+    ImportImage {
+        /// A screenshot cropped to the face of the box, whose puzzle is printed in
+        /// notation
+        #[arg(required_unless_present = "dir", conflicts_with = "dir")]
+        file: Option<PathBuf>,
+        /// Directory of screenshots to import
+        #[arg(long, requires = "out")]
+        dir: Option<PathBuf>,
+        /// Where to write the resulting puzzle pack as JSON
+        #[arg(long, requires = "dir")]
+        out: Option<PathBuf>,
+        /// Parses less certain than this are listed for review instead of packed, or
+        /// warned about for a single screenshot
+        #[arg(long, default_value_t = 0.5)]
+        min_confidence: f32,
+    },
+    /// Capture a region of the screen every so often, solving the box it shows whenever
+    /// it changes, while playing the game
+    #[cfg(feature = "image")]
+    Capture {
+        /// The region cropped to the face of the box, as X,Y,W,H in pixels from the top
+        /// left of the screen
+        #[arg(long)]
+        region: capture::Region,
+        /// How long to wait between captures, such as `2s` or `500ms`
+        #[arg(long, default_value = "2s", value_parser = batch::parse_timeout)]
+        interval: Duration,
+        /// The command capturing the region to a PNG file, with `{x}`, `{y}`, `{width}`,
+        /// `{height}`, `{geometry}` (`X,Y WxH`) and `{file}` filled in. Defaults to
+        /// `grim`, ImageMagick's `import` or `screencapture`
+        #[arg(long)]
+        command: Option<String>,
+        /// Captures read less certainly than this are taken to show no box
+        #[arg(long, default_value_t = 0.5)]
+        min_confidence: f32,
+    },
+    /// Serve a JSON API for solving puzzles over HTTP
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Give up on a puzzle after searching for this long, such as `10s`, and answer
+        /// that the server is too busy
+        #[arg(long, default_value = "10s", value_parser = batch::parse_timeout)]
+        timeout: Duration,
+        /// Give up on a puzzle once its search holds this many states
+        #[arg(long, default_value_t = 5_000_000)]
+        max_states: usize,
+    },
+    /// List, add to or validate collections of puzzles kept in TOML or JSON files
+    Collection {
+        #[command(subcommand)]
+        command: CollectionCommand,
+    },
+    /// Report on each puzzle of a pack: its optimal length, how many optimal solutions
+    /// it has, the rules they use and its difficulty
+    Analyze {
+        /// A JSON pack of puzzles, such as one written by `import-image --out`, whose
+        /// `puzzles` are in notation or share codes, or objects with a `notation` field
+        #[arg(long)]
+        input: PathBuf,
+        /// How to write the report
+        #[arg(long, value_enum, default_value_t)]
+        format: ReportFormat,
+        /// Give up on a puzzle after searching for this long, such as `5s`, and report
+        /// it as timed out
+        #[arg(long, value_parser = batch::parse_timeout)]
+        timeout: Option<Duration>,
+    },
+    /// Solve every puzzle of a few colors, reporting how many have a solution and how
+    /// long their optimal solutions are
+    Enumerate {
+        /// The colors of the puzzles' tiles, by code or name, such as `w,k,-`. Goals are
+        /// every color but gray. Each color added multiplies the work many times over
+        #[arg(long, value_delimiter = ',', required = true)]
+        colors: Vec<String>,
+        /// Number of threads to solve on. Defaults to the number of CPUs
+        #[arg(long, short, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+    },
+    /// Build tablebases of the fewest presses solving every puzzle of a few colors, and
+    /// look puzzles up in them
+    Tablebase {
+        #[command(subcommand)]
+        command: TablebaseCommand,
+    },
+    /// Show how often each tile is pressed across the optimal solutions of puzzles
+    Heatmap {
+        /// Puzzles in notation or as share codes. Reads them from stdin, one per line, if
+        /// none are given, averaging over them all
+        puzzles: Vec<String>,
+        /// Write the heatmap as an SVG diagram to this file instead of printing it
+        #[cfg(feature = "svg")]
+        #[arg(long)]
+        svg: Option<PathBuf>,
+    },
+    /// Generate random puzzles and play out their solutions on a loop, stopping with an
+    /// error if any puzzle is not solved as expected
+    Demo {
+        #[command(flatten)]
+        args: GenerateArgs,
+        /// Number of puzzles to show. Loops until interrupted if not given
+        #[arg(long, short)]
+        count: Option<usize>,
+        /// Milliseconds between moves
+        #[arg(long, default_value_t = 600)]
+        delay: u64,
+        /// Milliseconds to show each solved puzzle for before the next
+        #[arg(long, default_value_t = 2000)]
+        pause: u64,
+    },
+    /// Time the solver on a fixed suite of puzzles, for comparing machines and versions
+    Bench {
+        /// How many times to solve the suite
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u16).range(1..))]
+        rounds: u16,
+    },
+    /// Print a completion script for a shell, such as
+    /// `mora-jai-cli completions bash > /etc/bash_completion.d/mora-jai-cli`
+    Completions { shell: clap_complete::Shell },
 }
 
-fn solve_puzzles() -> Result<(), Box<dyn std::error::Error>> {
-    let stdin = io::stdin();
+/// Prints the puzzle in a screenshot in notation, warning about cells which may have been
+/// misread.
+#[cfg(feature = "image")]
+fn import_image(
+    file: &std::path::Path,
+    min_confidence: f32,
+    labels: &ColorLabels,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let parse =
+        image_import::parse_image_file(file).map_err(|e| format!("{}: {e}", file.display()))?;
+    println!("{}", parse.notation_with_labels(labels));
 
-    for line in stdin.lock().lines() {
-        if let Err(e) = solve_puzzle(&line.unwrap()) {
-            eprintln!("{}", e);
-        }
+    let uncertain = parse.uncertain_cells(min_confidence);
+    if !uncertain.is_empty() {
+        eprintln!(
+            "check {} against the screenshot, they may be misread",
+            uncertain.join(", ")
+        );
     }
-
     Ok(())
 }
 
-fn random_challenge() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Generating puzzle...");
-    let mut puzzle = Puzzle::new_random();
-    print_puzzle(&puzzle);
-    // let solution = puzzle.solve().expect("puzzle should always have a solution");
-    // print_solution(&solution);
-
-    while !puzzle.is_solved() {
-        print!("Input: ");
-        std::io::stdout().flush()?;
-        let mut line = String::new();
-        std::io::stdin().read_line(&mut line)?;
-
-        match line.trim() {
-            "1" => puzzle.press_tile(0, 0),
-            "2" => puzzle.press_tile(0, 1),
-            "3" => puzzle.press_tile(0, 2),
-            "4" => puzzle.press_tile(1, 0),
-            "5" => puzzle.press_tile(1, 1),
-            "6" => puzzle.press_tile(1, 2),
-            "7" => puzzle.press_tile(2, 0),
-            "8" => puzzle.press_tile(2, 1),
-            "9" => puzzle.press_tile(2, 2),
-            "q" => puzzle.press_corner(Corner::NW),
-            "w" => puzzle.press_corner(Corner::NE),
-            "a" => puzzle.press_corner(Corner::SW),
-            "s" => puzzle.press_corner(Corner::SE),
-            _ => println!("invalid input"),
-        }
-
-        print_puzzle(&puzzle);
-    }
+#[cfg(feature = "image")]
+fn import_images(
+    dir: &std::path::Path,
+    out: &std::path::Path,
+    min_confidence: f32,
+    labels: &ColorLabels,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pack = image_import::import_dir(dir, min_confidence, labels)?;
+    std::fs::write(out, serde_json::to_string_pretty(&pack)?)?;
 
+    println!(
+        "Imported {} distinct puzzles, {} images need review",
+        pack.puzzles.len(),
+        pack.review.len()
+    );
+    for entry in &pack.review {
+        println!("  {}: {}", entry.file.display(), entry.reason);
+    }
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Exit status when some puzzles could not be parsed or solved. Fatal errors such as an
+/// unreadable file exit with 1, and invalid arguments with 2.
+const EXIT_PUZZLES_FAILED: u8 = 3;
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     #[cfg(windows)]
     control::set_virtual_terminal(true).unwrap();
 
-    solve_puzzles()
+    let cli = Cli::parse();
+
+    let config = UserConfig::load(cli.config.as_deref())?;
+    let output = Output::new(&cli.output, &config)?;
+
+    let mut rules = match &cli.rules {
+        Some(path) => RuleConfig::load(path)?.build(),
+        None => RuleSet::standard(),
+    };
+
+    let command = cli.command.unwrap_or(Command::Solve {
+        files: Vec::new(),
+        jobs: None,
+        errors: ErrorFormat::default(),
+        animate: false,
+        delay: 0,
+        explain: false,
+        corners: false,
+        alternatives: None,
+        slack: 0,
+        lengths_up_to: None,
+        timeout: None,
+        max_memory: None,
+        tie_break: TieBreakChoice::default(),
+        profile: OutputProfile::default(),
+        watch: false,
+        board: false,
+        #[cfg(feature = "clipboard")]
+        copy: false,
+        #[cfg(feature = "clipboard")]
+        paste: false,
+    });
+    match command {
+        Command::Solve {
+            files,
+            jobs,
+            errors,
+            animate,
+            delay,
+            explain,
+            corners,
+            alternatives,
+            slack,
+            lengths_up_to,
+            timeout,
+            max_memory,
+            tie_break,
+            profile,
+            watch,
+            board,
+            #[cfg(feature = "clipboard")]
+            copy,
+            #[cfg(feature = "clipboard")]
+            paste,
+        } => {
+            #[cfg(not(feature = "clipboard"))]
+            let (copy, paste) = (false, false);
+            let output = match profile {
+                OutputProfile::Plain => output.into_plain(),
+                _ => output,
+            };
+            // Each search solving at once gets an equal share of the memory
+            let jobs = jobs.map(usize::from);
+            let searches = jobs.unwrap_or_else(rayon::current_num_threads);
+            let memory_bound =
+                max_memory.map(|bytes| (bytes / searches / state_bytes::<3, 3>()).max(1));
+            let mut report = Report {
+                animate: animate.then(|| Duration::from_millis(delay)),
+                explain,
+                corners,
+                alternatives: alternatives.map(|count| (count, slack)),
+                lengths_up_to,
+                timeout,
+                memory_bound,
+                tie_break: tie_break.into(),
+                profile,
+                solutions: copy.then(Vec::new),
+                ..Report::new(errors)
+            };
+
+            if paste {
+                #[cfg(feature = "clipboard")]
+                batch::solve_text(
+                    "<clipboard>",
+                    &clipboard::paste()?,
+                    board,
+                    &mut report,
+                    &output,
+                    &rules,
+                )?;
+            } else if watch
+                && (files.is_empty() || files.iter().any(|path| path.as_os_str() == "-"))
+            {
+                watch::watch_stdin(&mut report, &output, &rules)?;
+            } else if watch {
+                watch::watch_files(&files, &mut report, &output, &rules)?;
+            } else {
+                batch::solve_files(&files, jobs, board, &mut report, &output, &rules)?;
+            }
+
+            #[cfg(feature = "clipboard")]
+            if let Some(solutions) = &report.solutions {
+                clipboard::copy(&solutions.join("\n"))?;
+            }
+            if report.timeouts > 0 {
+                eprintln!("puzzles timed out: {}", report.timeouts);
+            }
+            if let Some(bound) = report.memory_bound {
+                eprintln!(
+                    "peak estimated memory: {} of {} per search",
+                    batch::format_size(report.peak_states * state_bytes::<3, 3>()),
+                    batch::format_size(bound * state_bytes::<3, 3>())
+                );
+                eprintln!("puzzles with states dropped for memory: {}", report.capped);
+            }
+            if report.failures > 0 {
+                return Ok(ExitCode::from(EXIT_PUZZLES_FAILED));
+            }
+        }
+        Command::Play {
+            args,
+            resume,
+            record,
+            tablebase,
+            gauntlet,
+        } => {
+            args.generate.restrict(&mut rules, output.color_labels())?;
+            let tablebase = tablebase
+                .map(|path| tablebase::load(&path, &rules))
+                .transpose()?
+                .map(Rc::new);
+            match gauntlet {
+                Some(length) => {
+                    gauntlet::gauntlet(&rules, &output, &args, tablebase, length.into())?
+                }
+                None => play::random_challenge(
+                    &rules,
+                    &output,
+                    &args,
+                    tablebase,
+                    resume.as_deref(),
+                    record.as_deref(),
+                )?,
+            }
+        }
+        Command::Generate {
+            args,
+            count,
+            jobs,
+            share,
+            campaign,
+            link,
+        } => {
+            args.restrict(&mut rules, output.color_labels())?;
+            let jobs = match jobs {
+                Some(jobs) => jobs.into(),
+                None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            // Threads beyond one per puzzle would only generate puzzles to throw away
+            let jobs = jobs.min(count).max(1);
+            let lengths = DifficultyChoice::lengths(args.difficulty);
+            let puzzles: Box<dyn Iterator<Item = Puzzle>> = if campaign {
+                Box::new(Puzzle::new_campaign(rules, lengths, count, link.into()).into_iter())
+            } else {
+                Box::new(Puzzle::new_random_parallel(rules, lengths, jobs).take(count))
+            };
+            for puzzle in puzzles {
+                match puzzle.to_share_code() {
+                    Some(code) if share => println!("{code}"),
+                    _ => println!(
+                        "{}",
+                        notation::to_notation_with_labels(&puzzle, output.color_labels())
+                    ),
+                }
+            }
+        }
+        Command::Export {
+            puzzle,
+            #[cfg(feature = "qr")]
+            qr,
+        } => {
+            let puzzle =
+                batch::parse_puzzle(&puzzle, &rules, output.color_labels()).map_err(|(_, e)| e)?;
+            #[cfg(feature = "qr")]
+            if let Some(file) = qr {
+                export::export_qr(&puzzle, file.as_deref())?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            export::print_share_code(&puzzle)?;
+        }
+        #[cfg(any(feature = "png", feature = "svg"))]
+        Command::Render {
+            puzzle,
+            out,
+            steps,
+            sheet,
+            #[cfg(feature = "gif")]
+            gif,
+            #[cfg(feature = "gif")]
+            delay,
+        } => {
+            let puzzle =
+                batch::parse_puzzle(&puzzle, &rules, output.color_labels()).map_err(|(_, e)| e)?;
+            #[cfg(feature = "gif")]
+            if gif {
+                export::write_gif(&puzzle, &out, Duration::from_millis(delay))?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            export::render(&puzzle, &out, steps, sheet)?;
+        }
+        Command::Rules { only } => {
+            let color = only
+                .map(|name| parse_color(&name, output.color_labels()))
+                .transpose()?;
+            reference::rules(&rules, color, &output)?;
+        }
+        Command::Quiz { rounds, colors } => {
+            if !colors.is_empty() {
+                restrict_colors(&colors, &mut rules, output.color_labels())?;
+            }
+            quiz::quiz(&rules, rounds, &output)?;
+        }
+        Command::Repl { puzzle } => repl::repl(puzzle.as_deref(), &rules, &output)?,
+        Command::Assist {
+            puzzle,
+            moves,
+            grid,
+            locked,
+        } => assist::assist(&puzzle, &moves, grid.as_deref(), &locked, &rules, &output)?,
+        Command::Daily => daily::daily(&rules, &output)?,
+        Command::Race { host, join, args } => {
+            args.generate.restrict(&mut rules, output.color_labels())?;
+            match (host, join) {
+                (Some(port), _) => race::host(port, &rules, &output, &args)?,
+                (None, Some(address)) => race::join(&address, &rules, &output, &args)?,
+                (None, None) => unreachable!("clap requires --host or --join"),
+            }
+        }
+        Command::Stats => stats::show()?,
+        Command::Replay { file, speed } => replay::replay(&file, speed, &rules, &output)?,
+        #[cfg(feature = "tui")]
+        Command::Tui(args) => {
+            args.generate.restrict(&mut rules, output.color_labels())?;
+            tui::run(&rules, &output, &args)?;
+        }
+        #[cfg(feature = "image")]
+        Command::ImportImage {
+            file,
+            dir,
+            out,
+            min_confidence,
+        } => match (file, dir, out) {
+            (Some(file), _, _) => import_image(&file, min_confidence, output.color_labels())?,
+            (None, Some(dir), Some(out)) => {
+                import_images(&dir, &out, min_confidence, output.color_labels())?
+            }
+            _ => unreachable!("clap requires a file or both --dir and --out"),
+        },
+        #[cfg(feature = "image")]
+        Command::Capture {
+            region,
+            interval,
+            command,
+            min_confidence,
+        } => capture::capture(
+            region,
+            interval,
+            command.as_deref(),
+            min_confidence,
+            &rules,
+            &output,
+        )?,
+        #[cfg(feature = "server")]
+        Command::Serve {
+            port,
+            timeout,
+            max_states,
+        } => serve::serve(port, &rules, timeout, max_states)?,
+        Command::Collection { command } => {
+            if !collection::run(command, &rules, output.color_labels())? {
+                return Ok(ExitCode::from(EXIT_PUZZLES_FAILED));
+            }
+        }
+        Command::Analyze {
+            input,
+            format,
+            timeout,
+        } => analyze::analyze_pack(&input, format, timeout, &rules, output.color_labels())?,
+        Command::Tablebase { command } => tablebase::run(command, &rules, &output)?,
+        Command::Enumerate { colors, jobs } => {
+            restrict_colors(&colors, &mut rules, output.color_labels())?;
+            let jobs = match jobs {
+                Some(jobs) => jobs.into(),
+                None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            enumerate::enumerate(&rules, jobs, output.color_labels());
+        }
+        Command::Heatmap {
+            puzzles,
+            #[cfg(feature = "svg")]
+            svg,
+        } => heatmap::heatmap(
+            &puzzles,
+            #[cfg(feature = "svg")]
+            svg.as_deref(),
+            &rules,
+            output.color_labels(),
+        )?,
+        Command::Demo {
+            args,
+            count,
+            delay,
+            pause,
+        } => {
+            args.restrict(&mut rules, output.color_labels())?;
+            demo::demo(
+                &rules,
+                DifficultyChoice::lengths(args.difficulty),
+                count,
+                Duration::from_millis(delay),
+                Duration::from_millis(pause),
+                &output,
+            )?;
+        }
+        Command::Bench { rounds } => bench::bench(rounds.into()),
+        Command::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
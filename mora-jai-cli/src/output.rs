@@ -0,0 +1,320 @@
+//! How puzzles and solutions are printed.
+
+use std::io::{self, IsTerminal};
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+use mora_jai_core::{Color, ColorLabels, Move, Puzzle};
+#[cfg(feature = "graphics")]
+use mora_jai_render::graphics::GraphicsProtocol;
+use mora_jai_render::palette::Palette;
+use mora_jai_render::terminal::{ColorDepth, Style};
+use serde::Deserialize;
+
+use crate::config::UserConfig;
+use crate::keys::Keymap;
+use mora_jai_render::{SolutionFormatter, plain, render_solution_with, terminal};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn should_color(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // https://no-color.org: any non-empty value disables color
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteChoice {
+    /// Colors close to those in the game
+    #[default]
+    Standard,
+    /// Solid, colorblind-friendly backgrounds with contrasting text
+    HighContrast,
+}
+
+impl PaletteChoice {
+    fn palette(self) -> Palette {
+        match self {
+            PaletteChoice::Standard => Palette::standard(),
+            PaletteChoice::HighContrast => Palette::high_contrast(),
+        }
+    }
+}
+
+/// How the tiles pressed in solutions are labelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LabelChoice {
+    /// Keypad numbers, from 1 at the bottom left to 9 at the top right
+    #[default]
+    Keypad,
+    /// Chess-style squares, from a1 at the bottom left to c3 at the top right
+    Chess,
+    /// (row, column) pairs, from (0, 0) at the bottom left
+    RowCol,
+}
+
+impl From<LabelChoice> for SolutionFormatter {
+    fn from(labels: LabelChoice) -> Self {
+        match labels {
+            LabelChoice::Keypad => SolutionFormatter::Keypad,
+            LabelChoice::Chess => SolutionFormatter::Chess,
+            LabelChoice::RowCol => SolutionFormatter::RowCol,
+        }
+    }
+}
+
+/// Whether puzzles are drawn as images in terminals supporting inline graphics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphicsChoice {
+    /// Always draw puzzles as colored text
+    #[default]
+    Off,
+    /// Draw images if the terminal is known to support them
+    Auto,
+    /// DEC sixel graphics
+    Sixel,
+    /// The Kitty graphics protocol
+    Kitty,
+}
+
+impl GraphicsChoice {
+    #[cfg(feature = "graphics")]
+    fn protocol(self) -> Option<GraphicsProtocol> {
+        match self {
+            GraphicsChoice::Off => None,
+            GraphicsChoice::Auto => GraphicsProtocol::detect(),
+            GraphicsChoice::Sixel => Some(GraphicsProtocol::Sixel),
+            GraphicsChoice::Kitty => Some(GraphicsProtocol::Kitty),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+pub enum DepthChoice {
+    /// 24-bit color
+    #[value(name = "truecolor")]
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    /// xterm's 256 colors
+    #[value(name = "256")]
+    #[serde(rename = "256")]
+    Ansi256,
+    /// The 16 basic terminal colors
+    #[value(name = "16")]
+    #[serde(rename = "16")]
+    Ansi16,
+}
+
+impl From<DepthChoice> for ColorDepth {
+    fn from(depth: DepthChoice) -> Self {
+        match depth {
+            DepthChoice::TrueColor => ColorDepth::TrueColor,
+            DepthChoice::Ansi256 => ColorDepth::Ansi256,
+            DepthChoice::Ansi16 => ColorDepth::Ansi16,
+        }
+    }
+}
+
+/// Options for how output looks, shared by every command.
+#[derive(Debug, Default, clap::Args)]
+pub struct OutputArgs {
+    /// When to color output. `auto` colors only terminals and honors NO_COLOR
+    #[arg(long, global = true, value_enum, default_value_t)]
+    color: ColorChoice,
+
+    /// Never color output, the same as `--color never`
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Show each tile's notation code next to it, so tiles can be told apart without
+    /// relying on color
+    #[arg(long, global = true)]
+    symbols: bool,
+
+    /// Colors used for tiles in colored output [default: standard]
+    #[arg(long, global = true, value_enum)]
+    palette: Option<PaletteChoice>,
+
+    /// How many colors the terminal supports [default: detected from COLORTERM and TERM]
+    #[arg(long, global = true, value_enum)]
+    color_depth: Option<DepthChoice>,
+
+    /// How tiles are labelled in solutions [default: keypad]
+    #[arg(long, global = true, value_enum)]
+    labels: Option<LabelChoice>,
+
+    /// Draw puzzles as images in terminals supporting sixel or the Kitty graphics
+    /// protocol (`graphics` feature) [default: off]
+    #[arg(long, global = true, value_enum)]
+    graphics: Option<GraphicsChoice>,
+
+    /// Describe puzzles in sentences for screen readers instead of drawing them, and
+    /// announce what each press changed
+    #[arg(long, global = true)]
+    accessible: bool,
+}
+
+/// Settings for printing puzzles and playing them, chosen once from the command line
+/// and settings file.
+pub struct Output {
+    color: bool,
+    style: Style,
+    /// How puzzles are drawn as images, if they are
+    #[cfg(feature = "graphics")]
+    graphics: Option<GraphicsProtocol>,
+    labels: SolutionFormatter,
+    keys: Keymap,
+    /// Describe puzzles in sentences rather than drawing them
+    accessible: bool,
+    /// The puzzle last printed, so accessible output can announce what changed since
+    shown: Mutex<Option<Puzzle>>,
+}
+
+impl Output {
+    /// Combines the command line options with the user's settings file, with options
+    /// given on the command line taking precedence.
+    pub fn new(args: &OutputArgs, config: &UserConfig) -> Result<Self, String> {
+        let accessible = args.accessible || config.accessible;
+        let color = match args.color {
+            _ if args.no_color || accessible => false,
+            choice => choice.should_color(),
+        };
+        let graphics = args.graphics.or(config.graphics).unwrap_or_default();
+        #[cfg(not(feature = "graphics"))]
+        if graphics != GraphicsChoice::Off {
+            return Err(String::from(
+                "drawing puzzles as images needs the graphics feature",
+            ));
+        }
+        let palette = args
+            .palette
+            .or(config.palette)
+            .unwrap_or_default()
+            .palette();
+        let depth = args
+            .color_depth
+            .or(config.color_depth)
+            .map_or_else(ColorDepth::detect, ColorDepth::from);
+        Ok(Self {
+            color,
+            style: Style {
+                palette: config.apply_colors(palette)?,
+                depth,
+                symbols: args.symbols || config.symbols,
+                colors: config.color_labels()?,
+            },
+            #[cfg(feature = "graphics")]
+            graphics: graphics.protocol().filter(|_| color),
+            labels: args.labels.or(config.labels).unwrap_or_default().into(),
+            keys: config.keymap()?,
+            accessible,
+            shown: Mutex::new(None),
+        })
+    }
+
+    /// These settings with puzzles drawn as uncolored text, whatever was configured.
+    pub fn into_plain(self) -> Self {
+        Self {
+            color: false,
+            #[cfg(feature = "graphics")]
+            graphics: None,
+            ..self
+        }
+    }
+
+    /// How colored output is drawn.
+    #[cfg_attr(not(feature = "tui"), expect(dead_code))]
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// The names and notation codes of colors, for reading and writing puzzles.
+    pub fn color_labels(&self) -> &ColorLabels {
+        &self.style.colors
+    }
+
+    /// Colors text like a tile of `color`, or leaves it be if output is not colored.
+    pub fn paint(&self, text: &str, color: Color) -> String {
+        if self.color {
+            terminal::paint(text, color, &self.style)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn print_puzzle(&self, puzzle: &Puzzle) {
+        self.print_puzzle_highlighted(puzzle, None);
+    }
+
+    /// Prints a puzzle with the tile or corner pressed by `highlight` picked out. Plain
+    /// output has no room for highlighting, so callers should describe the move too.
+    /// Accessible output first announces what changed since the last puzzle printed.
+    pub fn print_puzzle_highlighted(&self, puzzle: &Puzzle, highlight: Option<Move>) {
+        if self.accessible {
+            let mut shown = self.shown.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(shown) = shown.replace(puzzle.clone()) {
+                let colors = &self.style.colors;
+                print!(
+                    "{}",
+                    plain::describe_changes(&shown, puzzle, self.labels, colors)
+                );
+            }
+        }
+        print!("{}", self.render_puzzle_highlighted(puzzle, highlight));
+    }
+
+    /// Draws a puzzle as [`print_puzzle_highlighted`](Self::print_puzzle_highlighted)
+    /// would print it.
+    pub fn render_puzzle_highlighted(&self, puzzle: &Puzzle, highlight: Option<Move>) -> String {
+        if self.accessible {
+            let colors = &self.style.colors;
+            return plain::describe_puzzle(puzzle, highlight, self.labels, colors);
+        }
+        #[cfg(feature = "graphics")]
+        if let Some(protocol) = self.graphics {
+            let image = mora_jai_render::raster::render_image(puzzle, highlight);
+            return protocol.encode(&image) + "\n";
+        }
+        if self.color {
+            terminal::render_puzzle_highlighted(puzzle, &self.style, highlight)
+        } else {
+            plain::render_puzzle_with_labels(puzzle, &self.style.colors)
+        }
+    }
+
+    /// How the tiles pressed in solutions are labelled.
+    pub fn labels(&self) -> SolutionFormatter {
+        self.labels
+    }
+
+    /// The keys used during play.
+    pub fn keys(&self) -> &Keymap {
+        &self.keys
+    }
+
+    pub fn print_solution(&self, solution: &[(usize, usize)]) {
+        println!("{}", render_solution_with(solution, self.labels));
+    }
+
+    /// Prints a solution's tile and corner presses in the order they are made.
+    pub fn print_moves(&self, moves: &[Move]) {
+        println!("Solution: {}", self.labels.format_moves(moves));
+    }
+}
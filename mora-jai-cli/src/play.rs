@@ -0,0 +1,711 @@
+//! The line-based play mode, which reprints the puzzle after every press.
+
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use mora_jai_core::{ColorLabels, Corner, Difficulty, Move, Puzzle, RuleSet, Tablebase};
+use mora_jai_io::notation;
+
+use crate::PlayArgs;
+use crate::keys::{Action, Key, Keymap};
+use crate::output::Output;
+use crate::replay::{Replay, ReplayEvent};
+use crate::save::SavedGame;
+use crate::stats::Stats;
+
+/// How hard generated puzzles should be: a difficulty level, or an exact number of
+/// presses in the optimal solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyChoice {
+    Level(Difficulty),
+    Presses(usize),
+}
+
+impl DifficultyChoice {
+    /// The optimal solution lengths a choice allows, any length for no choice.
+    pub fn lengths(choice: Option<Self>) -> RangeInclusive<usize> {
+        match choice {
+            None => 0..=usize::MAX,
+            Some(DifficultyChoice::Level(difficulty)) => {
+                let (min, max) = difficulty.solution_lengths();
+                min..=max.unwrap_or(usize::MAX)
+            }
+            Some(DifficultyChoice::Presses(n)) => n..=n,
+        }
+    }
+
+    pub fn new_puzzle(choice: Option<Self>, rules: &RuleSet) -> Puzzle {
+        Puzzle::new_random_with_solution_length(rules.clone(), Self::lengths(choice))
+    }
+}
+
+impl FromStr for DifficultyChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<usize>() {
+            Ok(0) => Err(String::from("puzzles need at least 1 press")),
+            Ok(n) => Ok(DifficultyChoice::Presses(n)),
+            Err(_) => s.parse().map(DifficultyChoice::Level).map_err(|_| {
+                format!("expected easy, medium, hard or a number of presses, not {s:?}")
+            }),
+        }
+    }
+}
+
+/// How a puzzle was solved, compared to its optimal solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    /// Tile presses made, not counting undone presses
+    pub presses: usize,
+    /// Presses in the optimal solution
+    pub optimal: usize,
+    /// Times a mismatched corner was pressed, resetting the puzzle
+    pub resets: usize,
+    pub splits: Splits,
+}
+
+/// Wall-clock times through a puzzle, measured from when it was first shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Splits {
+    /// When a corner was first locked, even if a later press unlocked it
+    pub first_corner: Option<Duration>,
+    pub total: Duration,
+}
+
+impl fmt::Display for Splits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Time: {}", format_time(self.total))?;
+        if let Some(first_corner) = self.first_corner {
+            write!(f, " (first corner at {})", format_time(first_corner))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a time as minutes and seconds to a tenth, such as `1:05.3`.
+pub fn format_time(time: Duration) -> String {
+    let tenths = time.as_millis() / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+impl Score {
+    /// A letter grade for how close the presses came to optimal, from S for an optimal
+    /// solve down to D.
+    pub fn grade(&self) -> char {
+        // Puzzles needing no presses are only solved optimally
+        let efficiency = self.optimal as f64 / self.presses.max(1) as f64;
+        match efficiency {
+            _ if self.presses <= self.optimal => 'S',
+            0.8.. => 'A',
+            0.6.. => 'B',
+            0.4.. => 'C',
+            _ => 'D',
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Solved in {} presses (optimal: {})",
+            self.presses, self.optimal
+        )?;
+        match self.resets {
+            0 => {}
+            1 => write!(f, " with 1 reset")?,
+            n => write!(f, " with {n} resets")?,
+        }
+        write!(f, ", grade {}", self.grade())
+    }
+}
+
+/// What the player typed.
+enum Input {
+    Key(Key),
+    /// An action asked for by name rather than by key
+    Action(Action),
+    /// Save the game to a file
+    Save(PathBuf),
+}
+
+/// Reads a single keypress without waiting for Enter. Returns `None` for Esc or Ctrl-C.
+fn read_key() -> io::Result<Option<Input>> {
+    terminal::enable_raw_mode()?;
+    let key = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match key.code {
+            KeyCode::Esc => break Ok(None),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Ok(None);
+            }
+            _ => {
+                if let Some(key) = Key::from_event(&key) {
+                    break Ok(Some(key));
+                }
+            }
+        }
+    };
+    terminal::disable_raw_mode()?;
+
+    match key? {
+        Some(Key::Char(':')) => read_save_path(),
+        key => Ok(key.map(Input::Key)),
+    }
+}
+
+/// How long each move is shown for when stepping through a solution.
+const STEP_DELAY: Duration = Duration::from_millis(600);
+
+/// Reads a line for input which isn't a terminal, such as a script piped to stdin.
+/// Each line is a key, such as `5` or `enter`. `!solve` gives up, and `:save <file>`
+/// saves the game. Returns `None` at the end of input.
+fn read_line_key() -> io::Result<Option<Input>> {
+    loop {
+        print!("Input: ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim();
+        if line == "!solve" {
+            return Ok(Some(Input::Action(Action::GiveUp)));
+        }
+        if let Some(path) = line.strip_prefix(":save ") {
+            return Ok(Some(Input::Save(PathBuf::from(path.trim()))));
+        }
+        match line.parse() {
+            Ok(Key::Char(':')) => return read_save_path(),
+            Ok(key) => return Ok(Some(Input::Key(key))),
+            Err(_) => println!("invalid input"),
+        }
+    }
+}
+
+/// Asks for the file to save to after `:` is typed.
+fn read_save_path() -> io::Result<Option<Input>> {
+    print!("Save to: ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Input::Save(PathBuf::from(line.trim()))))
+}
+
+/// The key which records a move in saved games and replays, whatever keys the player
+/// has bound.
+pub fn key_for(action: Move) -> char {
+    match action {
+        Move::Tile(row, col) => char::from(b'1' + (3 * row + col) as u8),
+        Move::Corner(Corner::NW) => 'q',
+        Move::Corner(Corner::NE) => 'w',
+        Move::Corner(Corner::SW) => 'a',
+        Move::Corner(Corner::SE) => 's',
+    }
+}
+
+/// The key which takes back the last move.
+pub const UNDO_KEY: char = 'u';
+
+/// The move recorded by a key in saved games and replays, if any.
+pub fn move_for(key: char) -> Option<Move> {
+    match key {
+        '1'..='9' => {
+            let n = key as usize - '1' as usize;
+            Some(Move::Tile(n / 3, n % 3))
+        }
+        'q' => Some(Move::Corner(Corner::NW)),
+        'w' => Some(Move::Corner(Corner::NE)),
+        'a' => Some(Move::Corner(Corner::SW)),
+        's' => Some(Move::Corner(Corner::SE)),
+        _ => None,
+    }
+}
+
+/// A puzzle being played, with the progress through it which the puzzle does not track.
+pub struct Game {
+    pub puzzle: Puzzle,
+    /// Hints which may still be asked for, or `None` to keep the solution hidden
+    /// entirely, with no hints or giving up
+    hints_left: Option<usize>,
+    resets: usize,
+    first_corner: Option<Duration>,
+    /// Time spent before the game was last resumed
+    elapsed: Duration,
+    /// When the game was started or resumed
+    resumed: Instant,
+    /// Whether pressing a mismatched corner ends the game
+    pub end_on_reset: bool,
+    /// Whether pressing a mismatched corner warns instead of resetting the box
+    pub practice: bool,
+    /// Where hints are looked up before searching for them, if anywhere
+    pub tablebase: Option<Rc<Tablebase>>,
+    /// Every move and undo made, for replays. Moves made before the game was saved are
+    /// logged at the start, as their times are not saved
+    log: Vec<ReplayEvent>,
+}
+
+impl Game {
+    pub fn new(puzzle: Puzzle, hints: Option<usize>) -> Self {
+        Self {
+            puzzle,
+            hints_left: hints,
+            resets: 0,
+            end_on_reset: false,
+            practice: false,
+            tablebase: None,
+            first_corner: None,
+            elapsed: Duration::ZERO,
+            resumed: Instant::now(),
+            log: vec![],
+        }
+    }
+
+    /// Makes a move, counting resets and timing the first locked corner. Resets stay
+    /// counted even if the press is undone.
+    fn apply(&mut self, action: Move) {
+        self.record(key_for(action));
+        let resets = self.puzzle.reset_count();
+        self.puzzle.apply(action);
+        if self.puzzle.reset_count() > resets {
+            self.resets += 1;
+        } else if matches!(action, Move::Corner(_)) && self.first_corner.is_none() {
+            self.first_corner = Some(self.elapsed());
+        }
+    }
+
+    fn undo(&mut self) -> Option<Move> {
+        let undone = self.puzzle.undo()?;
+        self.record(UNDO_KEY);
+        Some(undone)
+    }
+
+    fn record(&mut self, key: char) {
+        let ms = self.elapsed().as_millis() as u64;
+        self.log.push(ReplayEvent { ms, key });
+    }
+
+    /// A replay of every move and undo made in the game.
+    pub fn to_replay(&self) -> Replay {
+        Replay {
+            puzzle: notation::to_notation(&self.puzzle),
+            events: self.log.clone(),
+        }
+    }
+
+    /// Time spent on the game so far, across every time it was played.
+    fn elapsed(&self) -> Duration {
+        self.elapsed + self.resumed.elapsed()
+    }
+
+    pub fn to_saved(&self) -> SavedGame {
+        SavedGame {
+            puzzle: notation::to_notation(&self.puzzle),
+            moves: self.puzzle.moves().map(key_for).collect(),
+            hints_left: self.hints_left,
+            resets: self.resets,
+            elapsed_ms: self.elapsed().as_millis() as u64,
+            first_corner_ms: self.first_corner.map(|time| time.as_millis() as u64),
+        }
+    }
+
+    /// Restores a saved game by replaying its moves, which restores the grid, locked
+    /// corners and history for undo.
+    pub fn from_saved(saved: &SavedGame, rules: &RuleSet) -> Result<Self, String> {
+        let mut puzzle =
+            notation::try_parse_puzzle(&saved.puzzle, rules).map_err(|e| e.to_string())?;
+        let mut log = vec![];
+        for key in saved.moves.chars() {
+            let action = move_for(key).ok_or_else(|| format!("unknown move {key:?}"))?;
+            puzzle.apply(action);
+            log.push(ReplayEvent { ms: 0, key });
+        }
+
+        Ok(Self {
+            puzzle,
+            hints_left: saved.hints_left,
+            resets: saved.resets,
+            end_on_reset: false,
+            practice: false,
+            tablebase: None,
+            first_corner: saved.first_corner_ms.map(Duration::from_millis),
+            elapsed: Duration::from_millis(saved.elapsed_ms),
+            resumed: Instant::now(),
+            log,
+        })
+    }
+}
+
+/// Why a corner press was not made in practice, where it would have reset the box.
+/// `key` is the corner's key.
+pub fn practice_warning(
+    puzzle: &Puzzle,
+    corner: Corner,
+    key: &str,
+    labels: &ColorLabels,
+) -> String {
+    format!(
+        "Pressing {key} would reset the box: its tile is {}, not {}",
+        labels.name(puzzle.corner_tile(corner)),
+        labels.name(puzzle.goal(corner))
+    )
+}
+
+/// Prints the keys of an optimal solution from the current state, then plays it out
+/// move by move if asked to.
+fn give_up(game: &mut Game, output: &Output, interactive: bool) -> io::Result<()> {
+    let Some(moves) = game.puzzle.remaining_moves() else {
+        println!("no solution from here, press a mismatched corner to reset");
+        return Ok(());
+    };
+    let keys = output.keys();
+    let labels: Vec<String> = moves
+        .iter()
+        .map(|&action| keys.label(Action::Move(action)))
+        .collect();
+    println!("Solution: {}", labels.join(" "));
+
+    println!("Step through it? (y/n)");
+    let key = if interactive {
+        read_key()?
+    } else {
+        read_line_key()?
+    };
+    if !matches!(key, Some(Input::Key(Key::Char('y')))) {
+        return Ok(());
+    }
+
+    for action in moves {
+        output.print_puzzle_highlighted(&game.puzzle, Some(action));
+        println!("Pressing {}", keys.label(Action::Move(action)));
+        if interactive {
+            thread::sleep(STEP_DELAY);
+        }
+        game.apply(action);
+    }
+    output.print_puzzle(&game.puzzle);
+    Ok(())
+}
+
+/// Plays a random puzzle, or resumes a saved game from `resume`, looking hints up in
+/// `tablebase` if given. The game is recorded to `record` when it ends, if given.
+pub fn random_challenge(
+    rules: &RuleSet,
+    output: &Output,
+    args: &PlayArgs,
+    tablebase: Option<Rc<Tablebase>>,
+    resume: Option<&Path>,
+    record: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut game = match resume {
+        Some(path) => {
+            let saved = SavedGame::load(path)?;
+            Game::from_saved(&saved, rules).map_err(|e| format!("{}: {e}", path.display()))?
+        }
+        None => {
+            println!("Generating puzzle...");
+            let puzzle = DifficultyChoice::new_puzzle(args.generate.difficulty, rules);
+            Game::new(puzzle, Some(args.hints))
+        }
+    };
+    game.practice = args.practice;
+    game.tablebase = tablebase;
+
+    let outcome = play(&mut game, output)?;
+    Stats::record_to_file(outcome.score());
+    if let Some(path) = record {
+        game.to_replay().save(path)?;
+    }
+    if let Outcome::Solved(score) = outcome {
+        println!("{score}");
+        if args.timer {
+            println!("{}", score.splits);
+        }
+    }
+    Ok(())
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Solved(Score),
+    /// The player gave up and stepped through the solution
+    GaveUp,
+    /// A mismatched corner was pressed in a game which ends on resets
+    Reset,
+    Quit,
+}
+
+impl Outcome {
+    /// The score, if the player solved the puzzle.
+    pub fn score(&self) -> Option<&Score> {
+        match self {
+            Outcome::Solved(score) => Some(score),
+            _ => None,
+        }
+    }
+}
+
+/// How to play, with the keys the player has bound.
+fn controls(keys: &Keymap, hints: bool) -> String {
+    let mut controls = format!(
+        "Press {} to press a tile, {} for a corner, {} to select a tile and {} to press it, {} to undo, ",
+        keys.tile_labels(),
+        keys.corner_labels(),
+        keys.cursor_labels(),
+        keys.label(Action::Press),
+        keys.label(Action::Undo),
+    );
+    if hints {
+        controls += &format!(
+            "{} for a hint, {} to give up, ",
+            keys.label(Action::Hint),
+            keys.label(Action::GiveUp)
+        );
+    }
+    controls + ": to save, or Esc to quit"
+}
+
+/// Plays a game until its puzzle is solved or the player quits.
+pub fn play(game: &mut Game, output: &Output) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let interactive = io::stdin().is_terminal();
+    let optimal = game
+        .puzzle
+        .solve()
+        .map_or(0, |solution| solution.presses.len());
+    let keys = output.keys();
+    // The tile pressed by `Action::Press`, moved with `Action::Cursor`
+    let mut cursor = (1, 1);
+    game.resumed = Instant::now();
+
+    output.print_puzzle(&game.puzzle);
+    if interactive {
+        println!("{}", controls(keys, game.hints_left.is_some()));
+    }
+
+    while !game.puzzle.is_solved() {
+        let input = if interactive {
+            read_key()?
+        } else {
+            read_line_key()?
+        };
+        let action = match input {
+            None => return Ok(Outcome::Quit),
+            Some(Input::Save(path)) => {
+                match game.to_saved().save(&path) {
+                    Ok(()) => println!("saved to {}", path.display()),
+                    Err(e) => println!("could not save: {e}"),
+                }
+                continue;
+            }
+            Some(Input::Action(action)) => action,
+            Some(Input::Key(key)) => match keys.action(key) {
+                // Pressing the selected tile is the same as pressing its own key
+                Some(Action::Press) => Action::Move(Move::Tile(cursor.0, cursor.1)),
+                Some(action) => action,
+                None => {
+                    println!("invalid input");
+                    continue;
+                }
+            },
+        };
+
+        match (action, game.hints_left) {
+            (Action::Move(Move::Corner(corner)), _)
+                if game.practice && !game.puzzle.would_lock(corner) =>
+            {
+                let key = keys.label(Action::Move(Move::Corner(corner)));
+                let labels = output.color_labels();
+                println!("{}", practice_warning(&game.puzzle, corner, &key, labels));
+            }
+            (Action::Move(action), _) => {
+                if let Move::Tile(row, col) = action {
+                    cursor = (row, col);
+                }
+                let resets = game.resets;
+                game.apply(action);
+                output.print_puzzle(&game.puzzle);
+                if game.end_on_reset && game.resets > resets {
+                    return Ok(Outcome::Reset);
+                }
+            }
+            (Action::Cursor(direction), _) => {
+                cursor = direction.step(cursor);
+                let selected = Move::Tile(cursor.0, cursor.1);
+                output.print_puzzle_highlighted(&game.puzzle, Some(selected));
+                println!("Selected {}", keys.label(Action::Move(selected)));
+            }
+            (Action::Undo, _) => match game.undo() {
+                Some(_) => output.print_puzzle(&game.puzzle),
+                None => println!("nothing to undo"),
+            },
+            (Action::Hint | Action::GiveUp, None) => {
+                println!("the solution stays hidden until this puzzle is solved");
+            }
+            (Action::Hint, Some(0)) => println!("no hints left"),
+            (Action::Hint, Some(hints_left)) => {
+                let hint = match &game.tablebase {
+                    Some(tablebase) => game.puzzle.hint_with(tablebase),
+                    None => game.puzzle.hint(),
+                };
+                let Some(hint) = hint else {
+                    println!("no solution from here, press a mismatched corner to reset");
+                    continue;
+                };
+                let hints_left = hints_left - 1;
+                game.hints_left = Some(hints_left);
+                output.print_puzzle_highlighted(&game.puzzle, Some(hint));
+                let key = keys.label(Action::Move(hint));
+                println!("Hint: press {key} ({hints_left} hints left)");
+            }
+            (Action::GiveUp, Some(_)) => {
+                give_up(game, output, interactive)?;
+                if game.puzzle.is_solved() {
+                    return Ok(Outcome::GaveUp);
+                }
+            }
+            (Action::NewPuzzle | Action::Press, _) => println!("invalid input"),
+        }
+    }
+
+    let presses = game
+        .puzzle
+        .moves()
+        .filter(|action| matches!(action, Move::Tile(..)))
+        .count();
+    Ok(Outcome::Solved(Score {
+        presses,
+        optimal,
+        resets: game.resets,
+        splits: Splits {
+            first_corner: game.first_corner,
+            total: game.elapsed(),
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_difficulty() {
+        assert_eq!(
+            "hard".parse(),
+            Ok(DifficultyChoice::Level(Difficulty::Hard))
+        );
+        assert_eq!("7".parse(), Ok(DifficultyChoice::Presses(7)));
+        assert!("0".parse::<DifficultyChoice>().is_err());
+        assert!("tricky".parse::<DifficultyChoice>().is_err());
+    }
+
+    #[test]
+    fn grades_compare_presses_to_optimal() {
+        let score = |presses, optimal| Score {
+            presses,
+            optimal,
+            resets: 0,
+            splits: Splits {
+                first_corner: None,
+                total: Duration::ZERO,
+            },
+        };
+        assert_eq!(score(9, 9).grade(), 'S');
+        assert_eq!(score(10, 9).grade(), 'A');
+        assert_eq!(score(14, 9).grade(), 'B');
+        assert_eq!(score(30, 9).grade(), 'D');
+        assert_eq!(score(0, 0).grade(), 'S');
+        assert_eq!(
+            Score {
+                resets: 2,
+                ..score(14, 9)
+            }
+            .to_string(),
+            "Solved in 14 presses (optimal: 9) with 2 resets, grade B"
+        );
+    }
+
+    #[test]
+    fn splits_show_minutes_and_tenths() {
+        let splits = Splits {
+            first_corner: Some(Duration::from_millis(4_250)),
+            total: Duration::from_millis(65_380),
+        };
+        assert_eq!(splits.to_string(), "Time: 1:05.3 (first corner at 0:04.2)");
+    }
+
+    #[test]
+    fn saved_games_resume_where_they_left_off() {
+        let rules = RuleSet::standard();
+        let puzzle = notation::parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let mut game = Game::new(puzzle, Some(2));
+        game.apply(Move::Corner(Corner::SW));
+        game.apply(Move::Tile(0, 1));
+        game.apply(Move::Tile(0, 2));
+
+        let saved = game.to_saved();
+        assert_eq!(saved.puzzle, "wwwwwww-w---w");
+        assert_eq!(saved.moves, "a23");
+        assert_eq!(saved.resets, 1);
+
+        let resumed = Game::from_saved(&saved, &rules).unwrap();
+        assert_eq!(resumed.puzzle, game.puzzle);
+        assert_eq!(resumed.hints_left, Some(2));
+    }
+
+    #[test]
+    fn resets_stay_counted_after_undo() {
+        let rules = RuleSet::standard();
+        let puzzle = notation::parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let mut game = Game::new(puzzle, None);
+        game.apply(Move::Corner(Corner::SW));
+        game.undo();
+        assert_eq!((game.resets, game.puzzle.reset_count()), (1, 0));
+
+        assert_eq!(
+            practice_warning(&game.puzzle, Corner::SW, "a", &ColorLabels::default()),
+            "Pressing a would reset the box: its tile is gray, not white"
+        );
+    }
+
+    #[test]
+    fn replays_log_moves_and_undos() {
+        let rules = RuleSet::standard();
+        let puzzle = notation::parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let mut game = Game::new(puzzle, None);
+        game.apply(Move::Tile(0, 1));
+        game.undo();
+        game.apply(Move::Tile(0, 2));
+
+        let replay = game.to_replay();
+        assert_eq!(replay.puzzle, "wwwwwww-w---w");
+        let keys: String = replay.events.iter().map(|event| event.key).collect();
+        assert_eq!(keys, "2u3");
+    }
+
+    #[test]
+    fn keys_match_moves() {
+        assert_eq!(key_for(Move::Tile(0, 0)), '1');
+        assert_eq!(key_for(Move::Tile(2, 1)), '8');
+        assert_eq!(key_for(Move::Corner(Corner::SE)), 's');
+        assert_eq!(move_for('8'), Some(Move::Tile(2, 1)));
+        assert_eq!(move_for('q'), Some(Move::Corner(Corner::NW)));
+        assert_eq!(move_for('u'), None);
+    }
+}
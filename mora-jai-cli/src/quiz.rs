@@ -0,0 +1,220 @@
+//! A quiz on the rules: a random grid with one tile picked out, and a choice of what
+//! pressing it does, for learning each color's rule by heart.
+
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Write};
+
+use mora_jai_core::{Color, ColorLabels, Grid, Move, Puzzle, RuleSet};
+use mora_jai_render::SolutionFormatter;
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+
+use crate::output::Output;
+
+/// The most answers offered for each question, the right one among them.
+const CHOICES: usize = 4;
+
+/// A grid, a tile to press, and the grids pressing it might lead to.
+struct Question {
+    grid: Grid,
+    tile: (usize, usize),
+    choices: Vec<Grid>,
+    /// The index of the grid pressing the tile does lead to
+    answer: usize,
+}
+
+impl Question {
+    /// A random grid of the colors in `rules`, with a tile whose press changes it. The
+    /// wrong answers are what pressing the tile would do if it followed another color's
+    /// rule, or nothing changing at all.
+    fn random(rules: &RuleSet, rng: &mut impl Rng) -> Self {
+        let palette: Vec<Color> = rules.colors().collect();
+        loop {
+            let grid = Grid::from_fn(|_, _| *palette.choose(rng).unwrap());
+            let mask = grid.press_mask_with(rules);
+            let tiles: Vec<(usize, usize)> = (0..3)
+                .flat_map(|row| (0..3).map(move |col| (row, col)))
+                .filter(|&(row, col)| mask[row][col])
+                .collect();
+            let Some(&(row, col)) = tiles.choose(rng) else {
+                continue;
+            };
+
+            let result = grid.press_with(rules, row, col);
+            let mut choices = vec![];
+            // Rules such as blue's act on the pressed tile's color, so the tile is
+            // recolored to follow another rule, and its color put back if left alone
+            let others = palette.iter().map(|&color| {
+                let mut recolored = grid.clone();
+                recolored.set(row, col, color);
+                let mut other = recolored.press_with(rules, row, col);
+                if *other.get(row, col) == color {
+                    other.set(row, col, *grid.get(row, col));
+                }
+                other
+            });
+            for other in others.chain([grid.clone()]) {
+                if other != result && !choices.contains(&other) {
+                    choices.push(other);
+                }
+            }
+            choices.shuffle(rng);
+            choices.truncate(CHOICES - 1);
+            let answer = rng.random_range(0..=choices.len());
+            choices.insert(answer, result);
+            return Self {
+                grid,
+                tile: (row, col),
+                choices,
+                answer,
+            };
+        }
+    }
+
+    fn color(&self) -> Color {
+        *self.grid.get(self.tile.0, self.tile.1)
+    }
+}
+
+/// Describes how one grid differs from another by the tiles changed, such as
+/// `4 white → gray, 5 gray → white`.
+pub(crate) fn describe_changes(
+    from: &Grid,
+    to: &Grid,
+    labels: SolutionFormatter,
+    colors: &ColorLabels,
+) -> String {
+    let changes: Vec<String> = from
+        .diff(to)
+        .iter()
+        .map(|change| {
+            format!(
+                "{} {} → {}",
+                labels.label(change.row, change.col),
+                colors.name(change.from),
+                colors.name(change.to)
+            )
+        })
+        .collect();
+    if changes.is_empty() {
+        String::from("nothing changes")
+    } else {
+        changes.join(", ")
+    }
+}
+
+/// Right answers and questions asked for each color's rule.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Scores(BTreeMap<Color, (usize, usize)>);
+
+impl Scores {
+    fn record(&mut self, color: Color, right: bool) {
+        let (correct, asked) = self.0.entry(color).or_default();
+        *correct += usize::from(right);
+        *asked += 1;
+    }
+
+    /// A line per rule asked about, with the fraction answered right.
+    fn describe(&self, colors: &ColorLabels) -> String {
+        let mut out = String::new();
+        for (&color, &(correct, asked)) in &self.0 {
+            out += &format!(
+                "{:<8} {correct}/{asked} ({:.0}%)\n",
+                colors.name(color),
+                correct as f64 * 100.0 / asked as f64
+            );
+        }
+        out
+    }
+}
+
+/// Asks `rounds` questions on the rules, reading each answer as a letter, then prints
+/// how often each rule was predicted right. Typing `quit` or ending input stops early.
+pub fn quiz(rules: &RuleSet, rounds: usize, output: &Output) -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+    let colors = output.color_labels();
+    let mut rng = rand::rng();
+    let mut scores = Scores::default();
+
+    'rounds: for round in 1..=rounds {
+        let question = Question::random(rules, &mut rng);
+        let (row, col) = question.tile;
+        // Goals play no part, so show the corner tiles as they are
+        let grid = &question.grid;
+        let goals = [(2, 0), (2, 2), (0, 0), (0, 2)].map(|(row, col)| *grid.get(row, col));
+        let puzzle = Puzzle::with_rules(goals, question.grid.clone(), rules.clone());
+        println!("Question {round} of {rounds}");
+        output.print_puzzle_highlighted(&puzzle, Some(Move::Tile(row, col)));
+        println!(
+            "What does pressing the {} tile at {} do?",
+            colors.name(question.color()),
+            output.labels().label(row, col)
+        );
+        for (i, choice) in question.choices.iter().enumerate() {
+            let text = describe_changes(&question.grid, choice, output.labels(), colors);
+            println!("  {}) {text}", char::from(b'a' + i as u8));
+        }
+
+        let choice = loop {
+            if interactive {
+                print!("> ");
+                io::stdout().flush()?;
+            }
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line)? == 0 {
+                break 'rounds;
+            }
+            match line.trim() {
+                "quit" => break 'rounds,
+                answer => match answer.as_bytes() {
+                    &[letter] if (b'a'..b'a' + question.choices.len() as u8).contains(&letter) => {
+                        break usize::from(letter - b'a');
+                    }
+                    _ => println!("Answer with a letter from the list, or quit"),
+                },
+            }
+        };
+        let right = choice == question.answer;
+        scores.record(question.color(), right);
+        if right {
+            println!("Right\n");
+        } else {
+            println!(
+                "Wrong, it was {})\n",
+                char::from(b'a' + question.answer as u8)
+            );
+        }
+    }
+
+    print!("{}", scores.describe(colors));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questions_offer_the_press_among_other_rules() {
+        let rules = RuleSet::standard();
+        let mut rng = rand::rng();
+        for _ in 0..20 {
+            let question = Question::random(&rules, &mut rng);
+            let (row, col) = question.tile;
+            let result = question.grid.press_with(&rules, row, col);
+            assert_ne!(result, question.grid);
+            assert_eq!(question.choices[question.answer], result);
+            assert!((2..=CHOICES).contains(&question.choices.len()));
+            assert_eq!(question.choices.iter().filter(|&c| *c == result).count(), 1);
+        }
+
+        let mut scores = Scores::default();
+        scores.record(Color::White, true);
+        scores.record(Color::White, false);
+        scores.record(Color::Pink, true);
+        assert_eq!(
+            scores.describe(&ColorLabels::default()),
+            "white    1/2 (50%)\npink     1/1 (100%)\n"
+        );
+    }
+}
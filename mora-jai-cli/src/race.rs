@@ -0,0 +1,204 @@
+//! Races between two players solving the same puzzle, over TCP.
+//!
+//! The host generates a puzzle and waits for the other player to join, then sends it in
+//! notation. Each player solves it on their own terminal and sends back how they
+//! finished, so that both see the two results side by side. Messages are JSON objects,
+//! one per line. Both players should use the same rules, as the puzzle is read with the
+//! joining player's own.
+
+use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use mora_jai_core::{Move, Puzzle, RuleSet};
+use mora_jai_io::notation;
+use serde::{Deserialize, Serialize};
+
+use crate::PlayArgs;
+use crate::output::Output;
+use crate::play::{self, DifficultyChoice, Game, Outcome};
+use crate::stats::Stats;
+
+/// How a player finished the race.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Finish {
+    /// Whether the player solved the puzzle, rather than giving up or quitting
+    solved: bool,
+    /// Tile presses made, not counting undone presses
+    presses: usize,
+    resets: usize,
+    /// Time from the puzzle being shown until the player finished
+    ms: u64,
+}
+
+impl Finish {
+    fn new(puzzle: &Puzzle, outcome: Outcome, time: Duration) -> Self {
+        Self {
+            solved: matches!(outcome, Outcome::Solved(_)),
+            presses: puzzle
+                .moves()
+                .filter(|action| matches!(action, Move::Tile(..)))
+                .count(),
+            resets: puzzle.reset_count(),
+            ms: time.as_millis() as u64,
+        }
+    }
+
+    /// Solving beats not solving, then the quicker time wins, then the fewer presses.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let rank = |finish: &Self| (!finish.solved, finish.ms, finish.presses);
+        rank(self).cmp(&rank(other))
+    }
+
+    /// The finish as a line of the results, such as
+    /// `solved in 12 presses with 1 reset, 0:34.2`.
+    fn describe(&self) -> String {
+        let time = play::format_time(Duration::from_millis(self.ms));
+        if !self.solved {
+            return format!("did not finish, {time}");
+        }
+        let resets = match self.resets {
+            0 => String::new(),
+            1 => String::from(" with 1 reset"),
+            n => format!(" with {n} resets"),
+        };
+        format!("solved in {} presses{resets}, {time}", self.presses)
+    }
+}
+
+/// The messages players send each other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Message {
+    /// The puzzle to race on in notation, sent by the host
+    Puzzle { puzzle: String },
+    /// How the sender finished
+    Finished(Finish),
+}
+
+fn send(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    let json = serde_json::to_string(message).expect("messages always serialize");
+    writeln!(stream, "{json}")
+}
+
+fn receive(reader: &mut impl BufRead) -> Result<Message, Box<dyn std::error::Error>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err("the other player left the race".into());
+    }
+    serde_json::from_str(&line).map_err(|e| format!("unexpected message: {e}").into())
+}
+
+/// Hosts a race on `port`: waits for the other player to join, then races them on a
+/// puzzle generated as `args` asks.
+pub fn host(
+    port: u16,
+    rules: &RuleSet,
+    output: &Output,
+    args: &PlayArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Generating puzzle...");
+    let puzzle = DifficultyChoice::new_puzzle(args.generate.difficulty, rules);
+
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!("Waiting for the other player to join on port {port}...");
+    let (mut stream, address) = listener.accept()?;
+    println!("{address} joined");
+
+    let notation = notation::to_notation_with_labels(&puzzle, output.color_labels());
+    send(&mut stream, &Message::Puzzle { puzzle: notation })?;
+    race(stream, puzzle, output, args)
+}
+
+/// Joins a race hosted at `address`, such as `192.168.1.5:7878`.
+pub fn join(
+    address: &str,
+    rules: &RuleSet,
+    output: &Output,
+    args: &PlayArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stream = TcpStream::connect(address).map_err(|e| format!("{address}: {e}"))?;
+    println!("Joined, waiting for the puzzle...");
+    let Message::Puzzle { puzzle } = receive(&mut BufReader::new(stream.try_clone()?))? else {
+        return Err("expected a puzzle from the host".into());
+    };
+    let puzzle = notation::try_parse_puzzle_with_labels(&puzzle, rules, output.color_labels())
+        .map_err(|e| format!("the host's puzzle: {e}"))?;
+    race(stream, puzzle, output, args)
+}
+
+/// Plays the puzzle, then swaps finishes with the other player and prints both.
+fn race(
+    mut stream: TcpStream,
+    puzzle: Puzzle,
+    output: &Output,
+    args: &PlayArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    println!("Go!");
+
+    let mut game = Game::new(puzzle, Some(args.hints));
+    game.practice = args.practice;
+    let start = Instant::now();
+    let outcome = play::play(&mut game, output)?;
+    let mine = Finish::new(&game.puzzle, outcome, start.elapsed());
+    Stats::record_to_file(outcome.score());
+
+    send(&mut stream, &Message::Finished(mine))?;
+    println!("Waiting for the other player to finish...");
+    let Message::Finished(theirs) = receive(&mut reader)? else {
+        return Err("expected the other player's result".into());
+    };
+
+    println!("You:      {}", mine.describe());
+    println!("Opponent: {}", theirs.describe());
+    match (mine.solved || theirs.solved, mine.cmp(&theirs)) {
+        (false, _) => println!("Neither of you solved it"),
+        (true, Ordering::Less) => println!("You win!"),
+        (true, Ordering::Greater) => println!("Your opponent wins"),
+        (true, Ordering::Equal) => println!("A tie!"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solving_first_wins() {
+        let finish = |solved, presses, ms| Finish {
+            solved,
+            presses,
+            resets: 0,
+            ms,
+        };
+        let quick = finish(true, 8, 30_000);
+        let slow = finish(true, 4, 45_000);
+        let quit = finish(false, 2, 10_000);
+
+        assert_eq!(quick.cmp(&slow), Ordering::Less);
+        assert_eq!(quit.cmp(&slow), Ordering::Greater);
+        assert_eq!(quick.describe(), "solved in 8 presses, 0:30.0");
+        assert_eq!(quit.describe(), "did not finish, 0:10.0");
+    }
+
+    #[test]
+    fn messages_cross_the_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(server);
+
+        let puzzle = Message::Puzzle {
+            puzzle: String::from("wwwwwww-w---w"),
+        };
+        send(&mut client, &puzzle).unwrap();
+        assert_eq!(receive(&mut reader).unwrap(), puzzle);
+
+        drop(client);
+        let err = receive(&mut reader).unwrap_err();
+        assert_eq!(err.to_string(), "the other player left the race");
+    }
+}
@@ -0,0 +1,104 @@
+//! A reference to the rules, showing each with a press worked out by the rules
+//! themselves, so the examples always match what pressing does.
+
+use mora_jai_core::{Color, Grid, RuleSet};
+
+use crate::output::Output;
+use crate::quiz;
+
+/// The tiles tried for showing a rule, the middle first and then an edge and a corner.
+const PRESSES: [(usize, usize); 3] = [(1, 1), (0, 1), (0, 0)];
+
+/// Grids of the colors the rules know to show presses on: one repeating them in keypad
+/// order, and one with a color per row for rules which need like neighbours.
+fn samples(rules: &RuleSet) -> [Grid; 2] {
+    let palette: Vec<Color> = rules.colors().collect();
+    [
+        Grid::from_fn(|row, col| palette[(3 * row + col) % palette.len()]),
+        Grid::from_fn(|row, _| palette[(row + 1) % palette.len()]),
+    ]
+}
+
+/// A sample grid with a tile of `color`, the tile, and the grid pressing it gives, or
+/// `None` if no tried press of the color changes a sample.
+fn demonstrate(rules: &RuleSet, color: Color) -> Option<(Grid, (usize, usize), Grid)> {
+    let samples = samples(rules);
+    samples.iter().find_map(|sample| {
+        PRESSES.iter().find_map(|&(row, col)| {
+            let mut before = sample.clone();
+            before.set(row, col, color);
+            let after = before.press_with(rules, row, col);
+            (after != before).then_some((before, (row, col), after))
+        })
+    })
+}
+
+/// The rows of a grid, top first, with each tile its code.
+fn grid_rows(grid: &Grid, output: &Output) -> [String; 3] {
+    let labels = output.color_labels();
+    [2, 1, 0].map(|row| {
+        (0..3)
+            .map(|col| {
+                let color = *grid.get(row, col);
+                output.paint(&labels.code(color).to_string(), color)
+            })
+            .collect()
+    })
+}
+
+/// Prints the rule for `color`, or for every color if none is given, each with a press
+/// on a sample grid before and after.
+pub fn rules(rules: &RuleSet, color: Option<Color>, output: &Output) -> Result<(), String> {
+    let labels = output.color_labels();
+    let colors: Vec<Color> = match color {
+        Some(color) if rules.get(color).is_none() => {
+            return Err(format!("{} tiles have no rule", labels.name(color)));
+        }
+        Some(color) => vec![color],
+        None => rules.colors().collect(),
+    };
+
+    for (i, &color) in colors.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let rule = rules.get(color).expect("only colors with rules are listed");
+        let name = output.paint(labels.name(color), color);
+        println!("{name} ({}): {}", labels.code(color), rule.describe());
+        let Some((before, (row, col), after)) = demonstrate(rules, color) else {
+            println!("  No press of this color changes the sample grids");
+            continue;
+        };
+        let (before_rows, after_rows) = (grid_rows(&before, output), grid_rows(&after, output));
+        for (line, (before, after)) in before_rows.iter().zip(&after_rows).enumerate() {
+            let arrow = if line == 1 { "→" } else { " " };
+            println!("  {before} {arrow} {after}");
+        }
+        println!(
+            "  Pressing {}: {}",
+            output.labels().label(row, col),
+            quiz::describe_changes(&before, &after, output.labels(), labels)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_standard_rule_but_gray_changes_a_sample() {
+        let rules = RuleSet::standard();
+        for color in rules.colors() {
+            let shown = demonstrate(&rules, color);
+            if color == Color::Gray {
+                assert_eq!(shown, None);
+                continue;
+            }
+            let (before, (row, col), after) = shown.unwrap_or_else(|| panic!("{color:?}"));
+            assert_eq!(*before.get(row, col), color);
+            assert_eq!(before.press_with(&rules, row, col), after);
+        }
+    }
+}
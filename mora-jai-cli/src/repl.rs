@@ -0,0 +1,264 @@
+//! An interactive prompt for exploring puzzles, mixing manual presses with the solver.
+
+use std::io::{self, IsTerminal, Write};
+
+use mora_jai_core::{ColorLabels, Corner, Move, Puzzle, RuleSet};
+
+use crate::batch;
+use crate::output::Output;
+
+const HELP: &str = "\
+load <puzzle>      load a puzzle in notation or as a share code
+press <1-9>...     press tiles by keypad number, 1 at the bottom left
+corner <nw|ne|sw|se>
+                   press a corner
+undo               take back the last press
+reset              go back to the puzzle as it was loaded
+show               print the puzzle
+hint               show the next press of an optimal solution
+solve              list the presses of an optimal solution from here
+help               show this list
+quit               leave";
+
+/// What to print after a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reply {
+    Nothing,
+    Puzzle,
+    /// The puzzle with a move picked out, and a description of the move
+    Highlight(Move, String),
+    Text(String),
+    Quit,
+}
+
+struct Repl<'a> {
+    rules: &'a RuleSet,
+    labels: &'a ColorLabels,
+    puzzle: Option<Puzzle>,
+}
+
+/// How a move is typed at the prompt, such as `5` or `nw`.
+pub(crate) fn move_label(action: Move) -> String {
+    match action {
+        Move::Tile(row, col) => (1 + 3 * row + col).to_string(),
+        Move::Corner(corner) => format!("{corner:?}").to_lowercase(),
+    }
+}
+
+pub(crate) fn parse_tile(word: &str) -> Result<(usize, usize), String> {
+    match word.parse::<usize>() {
+        Ok(n @ 1..=9) => Ok(((n - 1) / 3, (n - 1) % 3)),
+        _ => Err(format!("expected a tile from 1 to 9, not {word:?}")),
+    }
+}
+
+pub(crate) fn parse_corner(word: &str) -> Result<Corner, String> {
+    match word.to_lowercase().as_str() {
+        "nw" => Ok(Corner::NW),
+        "ne" => Ok(Corner::NE),
+        "sw" => Ok(Corner::SW),
+        "se" => Ok(Corner::SE),
+        _ => Err(format!("expected a corner nw, ne, sw or se, not {word:?}")),
+    }
+}
+
+impl<'a> Repl<'a> {
+    fn new(rules: &'a RuleSet, labels: &'a ColorLabels) -> Self {
+        Self {
+            rules,
+            labels,
+            puzzle: None,
+        }
+    }
+
+    fn puzzle(&mut self) -> Result<&mut Puzzle, String> {
+        self.puzzle
+            .as_mut()
+            .ok_or_else(|| String::from("no puzzle loaded, try load <puzzle>"))
+    }
+
+    fn load(&mut self, text: &str) -> Result<Reply, String> {
+        let puzzle = batch::parse_puzzle(text, self.rules, self.labels).map_err(|(_, e)| e)?;
+        self.puzzle = Some(puzzle);
+        Ok(Reply::Puzzle)
+    }
+
+    /// Runs one line typed at the prompt.
+    fn execute(&mut self, line: &str) -> Result<Reply, String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Reply::Nothing);
+        };
+        let args: Vec<&str> = words.collect();
+
+        match command {
+            "load" => match args.as_slice() {
+                [text] => self.load(text),
+                _ => Err(String::from("expected load <puzzle>")),
+            },
+            "press" => {
+                if args.is_empty() {
+                    return Err(String::from("expected press <1-9>..."));
+                }
+                // Check every tile before pressing any, so a typo presses nothing
+                let tiles = args
+                    .iter()
+                    .map(|word| parse_tile(word))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let puzzle = self.puzzle()?;
+                for (row, col) in tiles {
+                    puzzle.press_tile(row, col);
+                }
+                Ok(Reply::Puzzle)
+            }
+            "corner" => match args.as_slice() {
+                [word] => {
+                    let corner = parse_corner(word)?;
+                    self.puzzle()?.press_corner(corner);
+                    Ok(Reply::Puzzle)
+                }
+                _ => Err(String::from("expected corner <nw|ne|sw|se>")),
+            },
+            "undo" => match self.puzzle()?.undo() {
+                Some(_) => Ok(Reply::Puzzle),
+                None => Ok(Reply::Text(String::from("nothing to undo"))),
+            },
+            "reset" => {
+                let puzzle = self.puzzle()?;
+                *puzzle = Puzzle::with_rules(
+                    *puzzle.goals(),
+                    puzzle.original().clone(),
+                    puzzle.rules().clone(),
+                );
+                Ok(Reply::Puzzle)
+            }
+            "show" => self.puzzle().map(|_| Reply::Puzzle),
+            "hint" => match self.puzzle()?.hint() {
+                Some(hint) => {
+                    let text = match hint {
+                        Move::Tile(..) => format!("Hint: press {}", move_label(hint)),
+                        Move::Corner(_) => format!("Hint: corner {}", move_label(hint)),
+                    };
+                    Ok(Reply::Highlight(hint, text))
+                }
+                None => Ok(Reply::Text(String::from(
+                    "no solution from here, press a mismatched corner to reset",
+                ))),
+            },
+            "solve" => match self.puzzle()?.remaining_moves() {
+                Some(moves) => {
+                    let labels: Vec<String> = moves.into_iter().map(move_label).collect();
+                    Ok(Reply::Text(format!("Solution: {}", labels.join(" "))))
+                }
+                None => Ok(Reply::Text(String::from("no solution from here"))),
+            },
+            "help" | "?" => Ok(Reply::Text(String::from(HELP))),
+            "quit" | "exit" => Ok(Reply::Quit),
+            _ => Err(format!("unknown command {command:?}, try help")),
+        }
+    }
+}
+
+/// Reads commands until `quit` or the end of input, starting from `puzzle` if given.
+pub fn repl(
+    puzzle: Option<&str>,
+    rules: &RuleSet,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let interactive = io::stdin().is_terminal();
+    let mut repl = Repl::new(rules, output.color_labels());
+    if let Some(puzzle) = puzzle {
+        repl.load(puzzle)?;
+    }
+    match &repl.puzzle {
+        Some(puzzle) => output.print_puzzle(puzzle),
+        None if interactive => println!("Type help for a list of commands"),
+        None => {}
+    }
+
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let reply = match repl.execute(&line) {
+            Ok(reply) => reply,
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        let puzzle = repl.puzzle.as_ref();
+        match (reply, puzzle) {
+            (Reply::Quit, _) => return Ok(()),
+            (Reply::Nothing, _) => {}
+            (Reply::Text(text), _) => println!("{text}"),
+            (Reply::Puzzle, Some(puzzle)) => {
+                output.print_puzzle(puzzle);
+                if puzzle.is_solved() {
+                    println!("Solved");
+                }
+            }
+            (Reply::Highlight(action, text), Some(puzzle)) => {
+                output.print_puzzle_highlighted(puzzle, Some(action));
+                println!("{text}");
+            }
+            (Reply::Puzzle | Reply::Highlight(..), None) => {
+                unreachable!("commands showing the puzzle need one loaded")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_share_the_current_puzzle() {
+        let rules = RuleSet::standard();
+        let labels = ColorLabels::default();
+        let mut repl = Repl::new(&rules, &labels);
+
+        assert!(repl.execute("press 5").is_err());
+        assert_eq!(repl.execute("load wwwwwww-w---w"), Ok(Reply::Puzzle));
+        assert_eq!(
+            repl.execute("solve"),
+            Ok(Reply::Text(String::from("Solution: 3 2 nw ne sw se")))
+        );
+
+        assert_eq!(repl.execute("press 3"), Ok(Reply::Puzzle));
+        assert_eq!(
+            repl.execute("hint"),
+            Ok(Reply::Highlight(
+                Move::Tile(0, 1),
+                String::from("Hint: press 2")
+            ))
+        );
+        assert_eq!(repl.execute("undo"), Ok(Reply::Puzzle));
+        assert_eq!(repl.puzzle().unwrap().moves().count(), 0);
+
+        // A bad tile stops the whole press
+        assert!(repl.execute("press 3 10").is_err());
+        assert_eq!(repl.puzzle().unwrap().moves().count(), 0);
+
+        repl.execute("press 3 2").unwrap();
+        repl.execute("corner nw").unwrap();
+        assert_eq!(
+            repl.execute("solve"),
+            Ok(Reply::Text(String::from("Solution: ne sw se")))
+        );
+        repl.execute("reset").unwrap();
+        assert_eq!(repl.puzzle().unwrap().moves().count(), 0);
+
+        assert!(repl.execute("corner north").is_err());
+        assert!(repl.execute("jump").is_err());
+        assert_eq!(repl.execute("  "), Ok(Reply::Nothing));
+        assert_eq!(repl.execute("quit"), Ok(Reply::Quit));
+    }
+}
@@ -0,0 +1,76 @@
+//! Recordings of games, which log every press with when it was made.
+
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use mora_jai_core::RuleSet;
+use mora_jai_io::notation;
+use serde::{Deserialize, Serialize};
+
+use crate::output::Output;
+use crate::play;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    /// The puzzle's goals and original grid in notation
+    pub puzzle: String,
+    pub events: Vec<ReplayEvent>,
+}
+
+/// A key pressed during play: a move, or `u` to undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Milliseconds since the game started
+    pub ms: u64,
+    pub key: char,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Plays a recording back, waiting between presses as long as the player did divided by
+/// `speed`.
+pub fn replay(
+    path: &Path,
+    speed: f64,
+    rules: &RuleSet,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(format!("speed must be positive, not {speed}").into());
+    }
+    let replay = Replay::load(path)?;
+    let mut puzzle = notation::try_parse_puzzle(&replay.puzzle, rules)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    output.print_puzzle(&puzzle);
+
+    let mut last = 0;
+    for event in replay.events {
+        let wait = event.ms.saturating_sub(last) as f64 / speed;
+        thread::sleep(Duration::from_secs_f64(wait / 1000.0));
+        last = event.ms;
+
+        if event.key == play::UNDO_KEY {
+            puzzle.undo();
+            println!("Undo");
+            output.print_puzzle(&puzzle);
+            continue;
+        }
+        let action = play::move_for(event.key)
+            .ok_or_else(|| format!("{}: unknown key {:?}", path.display(), event.key))?;
+        println!("Pressing {}", event.key);
+        puzzle.apply(action);
+        output.print_puzzle_highlighted(&puzzle, Some(action));
+    }
+    Ok(())
+}
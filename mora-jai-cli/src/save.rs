@@ -0,0 +1,32 @@
+//! Save files for games in progress, as JSON.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A game in progress. The puzzle is stored as its original notation along with the
+/// moves made since, which are replayed on loading to restore it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub puzzle: String,
+    /// The keys of each move made, not counting undone moves
+    pub moves: String,
+    /// Hints which may still be asked for, or `None` if the solution is hidden
+    pub hints_left: Option<usize>,
+    pub resets: usize,
+    /// Time spent on the game so far, in milliseconds
+    pub elapsed_ms: u64,
+    pub first_corner_ms: Option<u64>,
+}
+
+impl SavedGame {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
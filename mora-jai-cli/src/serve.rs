@@ -0,0 +1,242 @@
+//! A small JSON API over HTTP for solving puzzles.
+//!
+//! `POST /solve` takes `{"puzzle": "<notation>"}` and responds with the solution, its
+//! difficulty, how many optimal solutions there are, and a trace of the grid after each
+//! press. Errors are reported as `{"error": "..."}` with a 4xx status, or 503 for
+//! puzzles the server gives up on, as each search is limited in time and states.
+//!
+//! `/live` takes WebSocket connections for playing a puzzle while watching the solver,
+//! described in [`live`].
+
+mod live;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use mora_jai_core::{Color, Difficulty, Puzzle, RuleSet, SolveOptions};
+use mora_jai_io::notation::{grid_to_notation, parse_puzzle};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tungstenite::handshake::derive_accept_key;
+
+/// How many requests are handled at once. Solving is CPU bound, so more workers than
+/// this rarely helps.
+const WORKERS: usize = 4;
+
+#[derive(Deserialize)]
+struct SolveRequest {
+    puzzle: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SolveResponse {
+    puzzle: String,
+    /// Presses as row, column pairs with row 0 at the bottom
+    solution: Vec<(usize, usize)>,
+    /// Presses as keypad numbers, 1 at the bottom left
+    keypad: Vec<usize>,
+    difficulty: Difficulty,
+    /// How many different optimal solutions the puzzle has
+    optimal_solutions: u64,
+    trace: Vec<TraceStep>,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceStep {
+    row: usize,
+    col: usize,
+    color: Color,
+    rule: Option<String>,
+    /// The tiles after the press, in notation form
+    grid: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn solve(
+    body: &str,
+    rules: &RuleSet,
+    options: &SolveOptions,
+) -> Result<SolveResponse, (u16, String)> {
+    let request: SolveRequest =
+        serde_json::from_str(body).map_err(|e| (400, format!("invalid request: {e}")))?;
+    let puzzle: Puzzle = parse_puzzle(request.puzzle.trim(), rules)
+        .ok_or_else(|| (400, String::from("failed to parse puzzle")))?;
+    let (solution, optimal_solutions) = puzzle
+        .solve_counting_with(options)
+        .map_err(|e| (503, format!("gave up on the puzzle: {e}")))?
+        .ok_or_else(|| (422, String::from("puzzle has no solution")))?;
+
+    let trace = puzzle
+        .trace(&solution)
+        .into_iter()
+        .map(|step| TraceStep {
+            row: step.row,
+            col: step.col,
+            color: step.color,
+            rule: step.rule,
+            grid: grid_to_notation(&step.result),
+        })
+        .collect();
+
+    Ok(SolveResponse {
+        puzzle: request.puzzle.trim().to_string(),
+        keypad: solution
+            .iter()
+            .map(|(row, col)| 1 + 3 * row + col)
+            .collect(),
+        difficulty: Difficulty::from_solution_length(solution.len()),
+        optimal_solutions,
+        solution,
+        trace,
+    })
+}
+
+fn respond<T: Serialize>(request: Request, status: u16, body: &T) {
+    let json = serde_json::to_string(body).expect("responses always serialize");
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(json)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        eprintln!("failed to send response: {e}");
+    }
+}
+
+/// Upgrades a request for `/live` to a WebSocket served on its own thread, as the
+/// connection lasts as long as the client stays.
+fn upgrade(request: Request, rules: &RuleSet, options: &SolveOptions) {
+    let key = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+        .map(|header| derive_accept_key(header.value.as_bytes()));
+    let Some(key) = key else {
+        let error = ErrorResponse {
+            error: String::from("expected a WebSocket upgrade"),
+        };
+        return respond(request, 400, &error);
+    };
+
+    let header = Header::from_bytes("Sec-WebSocket-Accept", key).unwrap();
+    let stream = request.upgrade("websocket", Response::empty(101).with_header(header));
+    let (rules, options) = (rules.clone(), *options);
+    thread::spawn(move || live::serve_socket(stream, rules, options));
+}
+
+fn handle(mut request: Request, rules: &RuleSet, options: &SolveOptions) {
+    if request.url() == "/live" {
+        return upgrade(request, rules, options);
+    }
+    if request.url() != "/solve" {
+        let error = ErrorResponse {
+            error: String::from("not found"),
+        };
+        return respond(request, 404, &error);
+    }
+    if *request.method() != Method::Post {
+        let error = ErrorResponse {
+            error: String::from("use POST"),
+        };
+        return respond(request, 405, &error);
+    }
+
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        let error = ErrorResponse {
+            error: format!("failed to read request: {e}"),
+        };
+        return respond(request, 400, &error);
+    }
+
+    match solve(&body, rules, options) {
+        Ok(response) => respond(request, 200, &response),
+        Err((status, error)) => respond(request, status, &ErrorResponse { error }),
+    }
+}
+
+/// Serves the API on the given port until the process is killed, giving up on each
+/// puzzle after `time_limit` or once its search holds `max_states` states.
+pub fn serve(
+    port: u16,
+    rules: &RuleSet,
+    time_limit: Duration,
+    max_states: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let options = SolveOptions {
+        time_limit: Some(time_limit),
+        max_states: Some(max_states),
+        ..SolveOptions::default()
+    };
+    let server = Arc::new(Server::http(("0.0.0.0", port)).map_err(|e| e.to_string())?);
+    println!("Listening on port {port}");
+
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let rules = rules.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request, &rules, &options);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_returns_solution_and_trace() {
+        let options = SolveOptions::default();
+        let response = solve(
+            r#"{"puzzle": "wwwwwww-w---w"}"#,
+            &RuleSet::standard(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(response.solution, [(0, 2), (0, 1)]);
+        assert_eq!(response.keypad, [3, 2]);
+        assert_eq!(response.difficulty, Difficulty::Easy);
+        assert_eq!(response.optimal_solutions, 2);
+        assert_eq!(response.trace.len(), 2);
+        assert_eq!(response.trace[1].grid, "www--ww-w");
+    }
+
+    #[test]
+    fn bad_requests_are_rejected() {
+        let rules = RuleSet::standard();
+        let options = SolveOptions::default();
+        assert_eq!(solve("not json", &rules, &options).unwrap_err().0, 400);
+        assert_eq!(
+            solve(r#"{"puzzle": "xyz"}"#, &rules, &options)
+                .unwrap_err()
+                .0,
+            400
+        );
+
+        let limited = SolveOptions {
+            max_states: Some(2),
+            ..SolveOptions::default()
+        };
+        let (status, error) =
+            solve(r#"{"puzzle": "wwwwwww-w---w"}"#, &rules, &limited).unwrap_err();
+        assert_eq!(status, 503);
+        assert_eq!(
+            error,
+            "gave up on the puzzle: search exceeded its state limit"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Live solving over a WebSocket at `/live`, so a browser can show the solver at work as
+//! the player presses tiles, without loading the solver as WebAssembly.
+//!
+//! Clients send JSON messages tagged by `type`:
+//!
+//! - `{"type": "load", "puzzle": "<notation>"}` starts a puzzle
+//! - `{"type": "press", "row": 0, "col": 2}` presses a tile, with row 0 at the bottom
+//! - `{"type": "corner", "corner": "nw"}` presses a corner
+//! - `{"type": "undo"}` takes back the last press
+//!
+//! Each is answered with a `state` event describing the grid and locked corners, then a
+//! `progress` event for each layer the solver searches from there, and finally either a
+//! `solution` with the moves which finish the puzzle or `unsolvable`. Problems with a
+//! message are answered with an `error` event and leave the puzzle as it was, as are
+//! searches which run past the server's limits. A search stops early if the client
+//! leaves.
+
+use std::io::{Read, Write};
+use std::ops::ControlFlow;
+
+use mora_jai_core::{Corner, Puzzle, RuleSet, SearchProgress, SolveError, SolveOptions};
+use mora_jai_io::notation::{grid_to_notation, try_parse_puzzle};
+use serde::{Deserialize, Serialize};
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::repl::{move_label, parse_corner};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    Load { puzzle: String },
+    Press { row: usize, col: usize },
+    Corner { corner: String },
+    Undo,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event {
+    State {
+        /// The tiles in notation form
+        grid: String,
+        /// Which corners are locked, in goal order (NW, NE, SW, SE)
+        locked: [bool; 4],
+        solved: bool,
+    },
+    Progress {
+        /// The presses made to reach the states the solver is now searching
+        depth: usize,
+        visited: usize,
+        queued: usize,
+        /// The first state of the layer, in notation form
+        grid: String,
+    },
+    Solution {
+        /// The moves which finish the puzzle, as keypad numbers and corner names
+        moves: Vec<String>,
+        /// The states the search visited
+        visited: usize,
+    },
+    Unsolvable,
+    Error {
+        error: String,
+    },
+}
+
+const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+/// The puzzle one client is playing.
+struct Session {
+    rules: RuleSet,
+    /// The limits of each search
+    options: SolveOptions,
+    puzzle: Option<Puzzle>,
+}
+
+impl Session {
+    fn new(rules: RuleSet, options: SolveOptions) -> Self {
+        Self {
+            rules,
+            options,
+            puzzle: None,
+        }
+    }
+
+    /// Applies one client message to the puzzle.
+    fn update(&mut self, text: &str) -> Result<&Puzzle, String> {
+        let message: ClientMessage =
+            serde_json::from_str(text).map_err(|e| format!("invalid message: {e}"))?;
+        if let ClientMessage::Load { puzzle } = &message {
+            let puzzle = try_parse_puzzle(puzzle.trim(), &self.rules).map_err(|e| e.to_string())?;
+            return Ok(self.puzzle.insert(puzzle));
+        }
+
+        let puzzle = self
+            .puzzle
+            .as_mut()
+            .ok_or_else(|| String::from("no puzzle loaded"))?;
+        match message {
+            ClientMessage::Load { .. } => unreachable!("loads are handled above"),
+            ClientMessage::Press { row, col } if row < 3 && col < 3 => puzzle.press_tile(row, col),
+            ClientMessage::Press { row, col } => {
+                return Err(format!("there is no tile at row {row}, column {col}"));
+            }
+            ClientMessage::Corner { corner } => puzzle.press_corner(parse_corner(&corner)?),
+            ClientMessage::Undo => {
+                puzzle
+                    .undo()
+                    .ok_or_else(|| String::from("nothing to undo"))?;
+            }
+        }
+        Ok(puzzle)
+    }
+
+    /// Handles one client message, passing each event in reply to `send` as it happens.
+    /// `send` breaks once events can no longer be sent, which stops the search.
+    fn handle(&mut self, text: &str, send: &mut dyn FnMut(Event) -> ControlFlow<()>) {
+        let options = self.options;
+        let puzzle = match self.update(text) {
+            Ok(puzzle) => puzzle,
+            Err(error) => {
+                let _ = send(Event::Error { error });
+                return;
+            }
+        };
+
+        let state = Event::State {
+            grid: grid_to_notation(puzzle.current_state()),
+            locked: CORNERS.map(|corner| puzzle.get_corner(corner) == puzzle.goal(corner)),
+            solved: puzzle.is_solved(),
+        };
+        if send(state).is_break() {
+            return;
+        }
+        let solution = puzzle.solve_current_reporting(&options, |progress: SearchProgress| {
+            send(Event::Progress {
+                depth: progress.depth,
+                visited: progress.visited,
+                queued: progress.queued,
+                grid: grid_to_notation(progress.grid),
+            })
+        });
+        let _ = send(match solution {
+            Ok(Some(solution)) => Event::Solution {
+                moves: solution.moves().into_iter().map(move_label).collect(),
+                visited: solution.stats.visited,
+            },
+            Ok(None) => Event::Unsolvable,
+            Err(SolveError::Cancelled) => return,
+            Err(e) => Event::Error {
+                error: format!("gave up on the puzzle: {e}"),
+            },
+        });
+    }
+}
+
+/// Plays puzzles with one client over an upgraded connection until it closes, searching
+/// within the limits of `options`.
+pub fn serve_socket<S: Read + Write>(stream: S, rules: RuleSet, options: SolveOptions) {
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+    let mut session = Session::new(rules, options);
+    loop {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            // Pings are answered and closes acknowledged by the next read
+            Ok(_) => continue,
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return;
+            }
+            Err(e) => return eprintln!("live connection failed: {e}"),
+        };
+
+        let mut failed = None;
+        session.handle(&text, &mut |event| {
+            let json = serde_json::to_string(&event).expect("events always serialize");
+            match socket.send(Message::text(json)) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(e) => {
+                    failed = Some(e);
+                    ControlFlow::Break(())
+                }
+            }
+        });
+        if let Some(e) = failed {
+            return eprintln!("live connection failed: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(session: &mut Session, text: &str) -> Vec<Event> {
+        let mut events = vec![];
+        session.handle(text, &mut |event| {
+            events.push(event);
+            ControlFlow::Continue(())
+        });
+        events
+    }
+
+    #[test]
+    fn presses_are_followed_by_a_fresh_solve() {
+        let mut session = Session::new(RuleSet::standard(), SolveOptions::default());
+
+        let loaded = events(
+            &mut session,
+            r#"{"type": "load", "puzzle": "wwwwwww-w---w"}"#,
+        );
+        assert!(matches!(loaded[0], Event::State { solved: false, .. }));
+        assert!(matches!(loaded[1], Event::Progress { depth: 0, .. }));
+        assert!(matches!(
+            loaded.last(),
+            Some(Event::Solution { moves, .. }) if moves == &["3", "2", "nw", "ne", "sw", "se"]
+        ));
+
+        let pressed = events(&mut session, r#"{"type": "press", "row": 0, "col": 2}"#);
+        assert!(matches!(
+            pressed.last(),
+            Some(Event::Solution { moves, .. }) if moves[0] == "2"
+        ));
+    }
+
+    #[test]
+    fn searches_stop_at_the_limits_or_when_the_client_leaves() {
+        let load = r#"{"type": "load", "puzzle": "wwwwwww-w---w"}"#;
+        let limited = SolveOptions {
+            max_states: Some(2),
+            ..SolveOptions::default()
+        };
+        let mut session = Session::new(RuleSet::standard(), limited);
+        assert!(matches!(
+            events(&mut session, load).last(),
+            Some(Event::Error { error }) if error.contains("state limit")
+        ));
+
+        let mut session = Session::new(RuleSet::standard(), SolveOptions::default());
+        let mut sent = vec![];
+        session.handle(load, &mut |event| {
+            let progress = matches!(event, Event::Progress { .. });
+            sent.push(event);
+            if progress {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(matches!(
+            sent[..],
+            [Event::State { .. }, Event::Progress { .. }]
+        ));
+    }
+
+    #[test]
+    fn bad_messages_leave_the_puzzle_alone() {
+        let mut session = Session::new(RuleSet::standard(), SolveOptions::default());
+        let error = |events: Vec<Event>| matches!(events[..], [Event::Error { .. }]);
+
+        assert!(error(events(&mut session, r#"{"type": "undo"}"#)));
+        events(
+            &mut session,
+            r#"{"type": "load", "puzzle": "wwwwwww-w---w"}"#,
+        );
+        assert!(error(events(
+            &mut session,
+            r#"{"type": "press", "row": 3, "col": 0}"#
+        )));
+        assert!(error(events(
+            &mut session,
+            r#"{"type": "corner", "corner": "n"}"#
+        )));
+        assert!(error(events(&mut session, "press 5")));
+        assert_eq!(session.puzzle.unwrap().moves().count(), 0);
+    }
+}
@@ -0,0 +1,151 @@
+//! Results of past games, kept in `$XDG_DATA_HOME/mora-jai/stats.json` or
+//! `~/.local/share/mora-jai/stats.json`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::play::Score;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// Puzzles shown, whether or not they were solved
+    pub attempted: u64,
+    /// Puzzles solved without giving up
+    pub solved: u64,
+    /// Presses beyond the optimal solution, summed over solved puzzles
+    pub extra_presses: u64,
+    /// Puzzles solved in a row, up to the last one played
+    pub streak: u64,
+    pub best_streak: u64,
+}
+
+/// Where the statistics file is kept.
+pub fn default_path() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("mora-jai").join("stats.json"))
+}
+
+impl Stats {
+    /// Reads the statistics file, giving empty statistics if there is none yet.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {e}", path.display()).into()),
+        };
+        serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Counts a puzzle, with its score if it was solved.
+    pub fn record(&mut self, score: Option<&Score>) {
+        self.attempted += 1;
+        match score {
+            Some(score) => {
+                self.solved += 1;
+                self.extra_presses += score.presses.saturating_sub(score.optimal) as u64;
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+            }
+            None => self.streak = 0,
+        }
+    }
+
+    /// Adds a result to the statistics file. Failing to update it is reported but does
+    /// not stop play.
+    pub fn record_to_file(score: Option<&Score>) {
+        let Some(path) = default_path() else {
+            return;
+        };
+        let result = Self::load(&path).and_then(|mut stats| {
+            stats.record(score);
+            stats.save(&path)
+        });
+        if let Err(e) = result {
+            eprintln!("could not update statistics: {e}");
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Puzzles attempted: {}", self.attempted)?;
+        write!(f, "Puzzles solved: {}", self.solved)?;
+        if let Some(percent) = (self.solved * 100).checked_div(self.attempted) {
+            write!(f, " ({percent}%)")?;
+        }
+        writeln!(f)?;
+        if self.solved > 0 {
+            let average = self.extra_presses as f64 / self.solved as f64;
+            writeln!(f, "Average presses over optimal: {average:.1}")?;
+        }
+        writeln!(f, "Streak: {} (best {})", self.streak, self.best_streak)
+    }
+}
+
+/// Prints the statistics from the statistics file.
+pub fn show() -> Result<(), Box<dyn std::error::Error>> {
+    let stats = match default_path() {
+        Some(path) => Stats::load(&path)?,
+        None => Stats::default(),
+    };
+    print!("{stats}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::play::Splits;
+
+    #[test]
+    fn record_tracks_streaks() {
+        let score = Score {
+            presses: 12,
+            optimal: 9,
+            resets: 0,
+            splits: Splits {
+                first_corner: None,
+                total: Duration::ZERO,
+            },
+        };
+        let mut stats = Stats::default();
+        stats.record(Some(&score));
+        stats.record(Some(&score));
+        stats.record(None);
+        stats.record(Some(&score));
+
+        assert_eq!(
+            stats,
+            Stats {
+                attempted: 4,
+                solved: 3,
+                extra_presses: 9,
+                streak: 1,
+                best_streak: 2,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "Puzzles attempted: 4\nPuzzles solved: 3 (75%)\n\
+             Average presses over optimal: 3.0\nStreak: 1 (best 2)\n"
+        );
+    }
+}
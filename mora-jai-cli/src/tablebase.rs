@@ -0,0 +1,115 @@
+//! Building tablebases of every puzzle of a few colors, and looking puzzles up in them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Subcommand;
+use mora_jai_core::{ColorLabels, RuleSet, Tablebase, TieBreak};
+use mora_jai_render::SolutionFormatter;
+
+use crate::batch;
+use crate::output::Output;
+
+#[derive(Subcommand)]
+pub enum TablebaseCommand {
+    /// Solve every puzzle of a few colors and save the fewest presses for each to a file
+    Build {
+        /// The colors of the puzzles' tiles, by code or name, such as `w,k,-`. Goals are
+        /// every color but gray. Each color added multiplies the size many times over
+        #[arg(long, value_delimiter = ',', required = true)]
+        colors: Vec<String>,
+        /// Where to write the tablebase
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Look puzzles up in a tablebase, printing how many presses each takes and a
+    /// solution
+    Query {
+        /// A tablebase written by `tablebase build`
+        table: PathBuf,
+        /// Puzzles in notation or as share codes
+        #[arg(required = true)]
+        puzzles: Vec<String>,
+    },
+}
+
+/// Reads a tablebase built with `rules`, or with the rules for some of their colors.
+pub fn load(path: &Path, rules: &RuleSet) -> Result<Tablebase, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    Tablebase::from_bytes(&bytes, rules.clone()).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// A puzzle's line of a query, such as `wwwwwww-w---w: 2 presses, 3 2`.
+fn describe(
+    text: &str,
+    tablebase: &Tablebase,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+    formatter: SolutionFormatter,
+) -> Result<String, String> {
+    let puzzle = batch::parse_puzzle(text, rules, labels).map_err(|(_, e)| e)?;
+    let answer = match tablebase.solution(puzzle.goals(), puzzle.original(), TieBreak::default()) {
+        None => String::from("not in the tablebase"),
+        Some(None) => String::from("no solution"),
+        Some(Some(presses)) => {
+            format!("{} presses, {}", presses.len(), formatter.format(&presses))
+        }
+    };
+    Ok(format!("{text}: {answer}"))
+}
+
+pub fn run(
+    command: TablebaseCommand,
+    rules: &RuleSet,
+    output: &Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        TablebaseCommand::Build { colors, out } => {
+            let mut rules = rules.clone();
+            crate::restrict_colors(&colors, &mut rules, output.color_labels())?;
+            let tablebase = Tablebase::build(&rules);
+            let bytes = tablebase.to_bytes();
+            fs::write(&out, &bytes).map_err(|e| format!("{}: {e}", out.display()))?;
+            println!(
+                "Wrote {} grids with {} sets of goals to {} ({})",
+                tablebase.len(),
+                tablebase.goal_colors().len().pow(4),
+                out.display(),
+                batch::format_size(bytes.len())
+            );
+        }
+        TablebaseCommand::Query { table, puzzles } => {
+            let tablebase = load(&table, rules)?;
+            for text in puzzles {
+                let labels = output.color_labels();
+                let line = describe(&text, &tablebase, rules, labels, output.labels())?;
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Color;
+
+    #[test]
+    fn queries_say_what_the_table_knows() {
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White));
+        let tablebase = Tablebase::build(&rules);
+        let rules = RuleSet::standard();
+        let query = |text| {
+            let labels = ColorLabels::default();
+            describe(text, &tablebase, &rules, &labels, SolutionFormatter::Keypad).unwrap()
+        };
+
+        assert_eq!(query("wwwwwww-w---w"), "wwwwwww-w---w: 2 presses, 3 2");
+        assert_eq!(
+            query("kkkkwww-w---w"),
+            "kkkkwww-w---w: not in the tablebase"
+        );
+    }
+}
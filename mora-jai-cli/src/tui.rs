@@ -0,0 +1,555 @@
+//! A full-screen interface for playing random puzzles.
+
+use std::time::{Duration, Instant};
+
+use mora_jai_core::{Color, ColorLabels, Corner, Move, Puzzle, RuleSet};
+use mora_jai_render::palette::Indexed;
+use mora_jai_render::terminal::{ColorDepth, Style as RenderStyle};
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color as TermColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::PlayArgs;
+use crate::keys::{Action, Key, Keymap};
+use crate::output::Output;
+use crate::play::{self, DifficultyChoice, Splits};
+
+/// The size of each tile and corner on screen, in terminal cells.
+const TILE_WIDTH: u16 = 7;
+const TILE_HEIGHT: u16 = 3;
+/// The board is the 3x3 grid surrounded by a ring of space for the corners.
+const BOARD_WIDTH: u16 = 5 * TILE_WIDTH;
+const BOARD_HEIGHT: u16 = 5 * TILE_HEIGHT;
+
+/// How often the timer is redrawn.
+const TIMER_TICK: Duration = Duration::from_millis(100);
+
+struct App<'a> {
+    puzzle: Puzzle,
+    rules: &'a RuleSet,
+    style: &'a RenderStyle,
+    keys: &'a Keymap,
+    /// The selected tile, with row 0 at the bottom
+    cursor: (usize, usize),
+    hint: Option<String>,
+    /// The move the last hint pointed to, which is highlighted until the next move
+    hinted: Option<Move>,
+    /// The hints and difficulty for each new puzzle
+    args: &'a PlayArgs,
+    hints_left: usize,
+    /// When the puzzle was first shown
+    started: Instant,
+    first_corner: Option<Duration>,
+    /// How long the puzzle took, once it is solved
+    finished: Option<Duration>,
+    /// Where the board was last drawn, for finding what was clicked
+    board: Rect,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(
+        puzzle: Puzzle,
+        rules: &'a RuleSet,
+        style: &'a RenderStyle,
+        keys: &'a Keymap,
+        args: &'a PlayArgs,
+    ) -> Self {
+        Self {
+            puzzle,
+            rules,
+            style,
+            keys,
+            cursor: (1, 1),
+            hint: None,
+            hinted: None,
+            args,
+            hints_left: args.hints,
+            started: Instant::now(),
+            first_corner: None,
+            finished: None,
+            board: Rect::default(),
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            // Redraw the running timer while waiting for input
+            if self.args.timer && !event::poll(TIMER_TICK)? {
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+                Event::Mouse(mouse) => self.handle_mouse(mouse),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn press_tile(&mut self, row: usize, col: usize) {
+        if self.puzzle.is_solved() {
+            return;
+        }
+        self.cursor = (row, col);
+        self.puzzle.press_tile(row, col);
+        self.clear_hint();
+    }
+
+    fn press_corner(&mut self, corner: Corner) {
+        if self.puzzle.is_solved() {
+            return;
+        }
+        if self.args.practice && !self.puzzle.would_lock(corner) {
+            let key = self.keys.label(Action::Move(Move::Corner(corner)));
+            let labels = ColorLabels::default();
+            self.hint = Some(play::practice_warning(&self.puzzle, corner, &key, &labels));
+            self.hinted = None;
+            return;
+        }
+        self.puzzle.press_corner(corner);
+        self.clear_hint();
+
+        if self.puzzle.get_corner(corner) == self.puzzle.goal(corner) {
+            let elapsed = self.started.elapsed();
+            self.first_corner.get_or_insert(elapsed);
+            if self.puzzle.is_solved() {
+                self.finished = Some(elapsed);
+            }
+        }
+    }
+
+    /// Tile presses made so far, not counting undone presses.
+    fn moves(&self) -> usize {
+        self.puzzle
+            .moves()
+            .filter(|action| matches!(action, Move::Tile(..)))
+            .count()
+    }
+
+    fn undo(&mut self) {
+        if let Some(Move::Tile(row, col)) = self.puzzle.undo() {
+            self.cursor = (row, col);
+        }
+        self.clear_hint();
+    }
+
+    fn clear_hint(&mut self) {
+        self.hint = None;
+        self.hinted = None;
+    }
+
+    fn show_hint(&mut self) {
+        if self.hinted.is_some() {
+            return;
+        }
+        if self.hints_left == 0 {
+            self.hint = Some(String::from("No hints left"));
+            return;
+        }
+
+        let Some(hinted) = self.puzzle.hint() else {
+            self.hint = Some(String::from(
+                "No solution from here. Press a mismatched corner to reset",
+            ));
+            return;
+        };
+        let key = self.keys.label(Action::Move(hinted));
+        let hint = match hinted {
+            Move::Tile(..) => format!("Press {key}"),
+            Move::Corner(_) => format!("Lock the corner with {key}"),
+        };
+        self.hints_left -= 1;
+        self.hint = Some(hint);
+        self.hinted = Some(hinted);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            _ => {
+                let action = Key::from_event(&key).and_then(|key| self.keys.action(key));
+                if let Some(action) = action {
+                    self.handle_action(action);
+                }
+            }
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        let (row, col) = self.cursor;
+        match action {
+            Action::Move(Move::Tile(row, col)) => self.press_tile(row, col),
+            Action::Move(Move::Corner(corner)) => self.press_corner(corner),
+            Action::Cursor(direction) => self.cursor = direction.step(self.cursor),
+            Action::Press => self.press_tile(row, col),
+            Action::Hint => self.show_hint(),
+            Action::Undo if !self.puzzle.is_solved() => self.undo(),
+            Action::NewPuzzle => {
+                let puzzle =
+                    DifficultyChoice::new_puzzle(self.args.generate.difficulty, self.rules);
+                *self = App::new(puzzle, self.rules, self.style, self.keys, self.args);
+            }
+            // There is no giving up full-screen, as hints are always available
+            Action::Undo | Action::GiveUp => {}
+        }
+    }
+
+    /// What is drawn at the given screen position, if anything.
+    fn target_at(&self, column: u16, row: u16) -> Option<Move> {
+        let board = self.board;
+        if !board.contains((column, row).into()) {
+            return None;
+        }
+        let x = (column - board.x) / TILE_WIDTH;
+        let y = (row - board.y) / TILE_HEIGHT;
+
+        match (x, y) {
+            (0, 0) => Some(Move::Corner(Corner::NW)),
+            (4, 0) => Some(Move::Corner(Corner::NE)),
+            (0, 4) => Some(Move::Corner(Corner::SW)),
+            (4, 4) => Some(Move::Corner(Corner::SE)),
+            (1..=3, 1..=3) => Some(Move::Tile(3 - y as usize, x as usize - 1)),
+            _ => None,
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+        match self.target_at(mouse.column, mouse.row) {
+            Some(Move::Tile(row, col)) => self.press_tile(row, col),
+            Some(Move::Corner(corner)) => self.press_corner(corner),
+            None => {}
+        }
+    }
+
+    /// The text and background colors for a block showing the given color.
+    fn block_colors(&self, color: Color) -> (TermColor, TermColor) {
+        let swatch = self.style.palette.get(color);
+        let indexed = match self.style.depth {
+            ColorDepth::TrueColor => None,
+            ColorDepth::Ansi256 => Some(self.style.palette.get_ansi256(color)),
+            ColorDepth::Ansi16 => Some(self.style.palette.get_ansi16(color)),
+        };
+
+        // A swatch without a background is a colored foreground, so fill the block with
+        // it and pick black or white text for contrast
+        let contrast = |[r, g, b]: [u8; 3]| {
+            let luma = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+            if luma > 128_000 {
+                TermColor::Black
+            } else {
+                TermColor::White
+            }
+        };
+        let rgb = |[r, g, b]: [u8; 3]| TermColor::Rgb(r, g, b);
+
+        match (indexed, swatch.bg) {
+            (None, Some(bg)) => (rgb(swatch.fg), rgb(bg)),
+            (None, None) => (contrast(swatch.fg), rgb(swatch.fg)),
+            (Some(Indexed { fg, bg: Some(bg) }), _) => {
+                (TermColor::Indexed(fg), TermColor::Indexed(bg))
+            }
+            (Some(Indexed { fg, bg: None }), _) => (contrast(swatch.fg), TermColor::Indexed(fg)),
+        }
+    }
+
+    fn draw_block(&self, frame: &mut Frame, area: Rect, label: &str, color: Color, target: Move) {
+        let (fg, bg) = self.block_colors(color);
+        let mut style = Style::new().fg(fg).bg(bg);
+        if self.hinted == Some(target) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        let selected = target == Move::Tile(self.cursor.0, self.cursor.1);
+        let label = if selected {
+            style = style.add_modifier(Modifier::BOLD);
+            format!("[{label}]")
+        } else {
+            label.to_string()
+        };
+
+        // Leave a gap between neighbouring blocks
+        let inner = Rect {
+            x: area.x + 1,
+            width: area.width.saturating_sub(2),
+            ..area
+        };
+        let lines = vec![Line::default(), Line::from(label), Line::default()];
+        frame.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .style(style),
+            inner,
+        );
+    }
+
+    fn draw_board(&self, frame: &mut Frame, area: Rect) {
+        let cell = |x: u16, y: u16| {
+            Rect {
+                x: area.x + x * TILE_WIDTH,
+                y: area.y + y * TILE_HEIGHT,
+                width: TILE_WIDTH,
+                height: TILE_HEIGHT,
+            }
+            .intersection(area)
+        };
+
+        for row in 0..3 {
+            for col in 0..3 {
+                let target = Move::Tile(row, col);
+                let label = self.keys.label(Action::Move(target));
+                let color = self.puzzle.get_tile(row, col);
+                let area = cell(1 + col as u16, 3 - row as u16);
+                self.draw_block(frame, area, &label, color, target);
+            }
+        }
+
+        for corner in [Corner::NW, Corner::NE, Corner::SW, Corner::SE] {
+            let (x, y) = match corner {
+                Corner::NW => (0, 0),
+                Corner::NE => (4, 0),
+                Corner::SW => (0, 4),
+                Corner::SE => (4, 4),
+            };
+            let target = Move::Corner(corner);
+            let label = self.keys.label(Action::Move(target));
+            let color = self.puzzle.get_corner(corner);
+            let area = cell(x, y);
+            self.draw_block(frame, area, &label, color, target);
+        }
+    }
+
+    fn draw_panel(&self, frame: &mut Frame, area: Rect) {
+        let goal = |corner| {
+            let color = self.puzzle.goal(corner);
+            let (fg, bg) = self.block_colors(color);
+            Span::styled(format!(" {} ", color.name()), Style::new().fg(fg).bg(bg))
+        };
+
+        let mut lines = vec![
+            Line::from("Goals"),
+            Line::from(vec![goal(Corner::NW), Span::raw(" "), goal(Corner::NE)]),
+            Line::from(vec![goal(Corner::SW), Span::raw(" "), goal(Corner::SE)]),
+            Line::default(),
+            Line::from(format!("Moves: {}", self.moves())),
+            Line::default(),
+        ];
+        if self.args.timer {
+            let splits = Splits {
+                first_corner: self.first_corner,
+                total: self.finished.unwrap_or_else(|| self.started.elapsed()),
+            };
+            lines.insert(5, Line::from(splits.to_string()));
+        }
+
+        if self.puzzle.is_solved() {
+            lines.push(Line::styled(
+                format!("Solved in {} moves!", self.moves()),
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            lines.push(Line::from(format!(
+                "{}: new puzzle  Esc: quit",
+                self.keys.label(Action::NewPuzzle)
+            )));
+        } else {
+            let keys = self.keys;
+            let hint = match &self.hint {
+                Some(hint) => hint.clone(),
+                None => format!("press {}", keys.label(Action::Hint)),
+            };
+            lines.push(Line::from(format!("Hint: {hint}")));
+            lines.push(Line::from(format!("Hints left: {}", self.hints_left)));
+            lines.push(Line::default());
+            lines.push(Line::from(format!(
+                "{} or {} + {}: press a tile",
+                keys.tile_labels(),
+                keys.cursor_labels(),
+                keys.label(Action::Press)
+            )));
+            lines.push(Line::from(format!(
+                "{}: press a corner",
+                keys.corner_labels()
+            )));
+            lines.push(Line::from(format!(
+                "{}: hint  {}: undo  {}: new puzzle  Esc: quit",
+                keys.label(Action::Hint),
+                keys.label(Action::Undo),
+                keys.label(Action::NewPuzzle)
+            )));
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered()),
+            area,
+        );
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let outer = Block::bordered().title(" Mora Jai ");
+        let inner = outer.inner(frame.area());
+        frame.render_widget(outer, frame.area());
+
+        let [board, panel] =
+            Layout::horizontal([Constraint::Length(BOARD_WIDTH + 2), Constraint::Min(20)])
+                .areas(inner);
+        let board = Rect {
+            x: board.x + 1,
+            y: board.y + 1,
+            width: BOARD_WIDTH.min(board.width.saturating_sub(1)),
+            height: BOARD_HEIGHT.min(board.height.saturating_sub(1)),
+        };
+
+        self.board = board;
+        self.draw_board(frame, board);
+        self.draw_panel(frame, panel);
+    }
+}
+
+/// Plays random puzzles full-screen until the player quits.
+pub fn run(
+    rules: &RuleSet,
+    output: &Output,
+    args: &PlayArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let puzzle = DifficultyChoice::new_puzzle(args.generate.difficulty, rules);
+    let mut app = App::new(puzzle, rules, output.style(), output.keys(), args);
+    ratatui::run(|terminal| {
+        execute!(std::io::stdout(), EnableMouseCapture)?;
+        let result = app.run(terminal);
+        execute!(std::io::stdout(), DisableMouseCapture)?;
+        result
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenerateArgs;
+    use mora_jai_io::notation::parse_puzzle;
+
+    #[test]
+    fn keys_press_tiles_and_move_the_cursor() {
+        let rules = RuleSet::standard();
+        let style = RenderStyle::default();
+        let args = PlayArgs {
+            hints: 1,
+            timer: false,
+            practice: false,
+            generate: GenerateArgs {
+                difficulty: None,
+                colors: vec![],
+            },
+        };
+        let puzzle = parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let keys = Keymap::default();
+        let mut app = App::new(puzzle, &rules, &style, &keys, &args);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.handle_key(key(KeyCode::Left));
+        app.handle_key(key(KeyCode::Up));
+        app.handle_key(key(KeyCode::Up));
+        assert_eq!(app.cursor, (2, 0));
+
+        let expected = app.puzzle.current_state().press_with(&rules, 2, 0);
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.moves(), 1);
+        assert_eq!(*app.puzzle.current_state(), expected);
+
+        app.handle_key(key(KeyCode::Char('3')));
+        assert_eq!(app.moves(), 2);
+        assert_eq!(app.cursor, (0, 2));
+
+        app.handle_key(key(KeyCode::Char('u')));
+        assert_eq!(app.moves(), 1);
+        assert_eq!(*app.puzzle.current_state(), expected);
+
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn hints_run_out() {
+        let rules = RuleSet::standard();
+        let style = RenderStyle::default();
+        let args = PlayArgs {
+            hints: 1,
+            timer: false,
+            practice: false,
+            generate: GenerateArgs {
+                difficulty: None,
+                colors: vec![],
+            },
+        };
+        let puzzle = parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let keys = Keymap::default();
+        let mut app = App::new(puzzle, &rules, &style, &keys, &args);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.handle_key(key(KeyCode::Char('h')));
+        assert_eq!(app.hinted, Some(Move::Tile(0, 2)));
+        assert_eq!(app.hint.as_deref(), Some("Press 3"));
+
+        app.handle_key(key(KeyCode::Char('3')));
+        assert_eq!(app.hinted, None);
+        app.handle_key(key(KeyCode::Char('h')));
+        assert_eq!(app.hinted, None);
+        assert_eq!(app.hint.as_deref(), Some("No hints left"));
+    }
+
+    #[test]
+    fn clicks_find_tiles_and_corners() {
+        let rules = RuleSet::standard();
+        let style = RenderStyle::default();
+        let args = PlayArgs {
+            hints: 1,
+            timer: false,
+            practice: false,
+            generate: GenerateArgs {
+                difficulty: None,
+                colors: vec![],
+            },
+        };
+        let puzzle = parse_puzzle("wwwwwww-w---w", &rules).unwrap();
+        let keys = Keymap::default();
+        let mut app = App::new(puzzle, &rules, &style, &keys, &args);
+        app.board = Rect::new(2, 2, BOARD_WIDTH, BOARD_HEIGHT);
+
+        assert_eq!(app.target_at(3, 3), Some(Move::Corner(Corner::NW)));
+        assert_eq!(
+            app.target_at(2 + BOARD_WIDTH - 1, 2 + BOARD_HEIGHT - 1),
+            Some(Move::Corner(Corner::SE))
+        );
+        // The top left tile is one tile in from the corner
+        assert_eq!(
+            app.target_at(2 + TILE_WIDTH, 2 + TILE_HEIGHT),
+            Some(Move::Tile(2, 0))
+        );
+        assert_eq!(app.target_at(2 + 2 * TILE_WIDTH, 2), None);
+        assert_eq!(app.target_at(0, 0), None);
+
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 2 + 3 * TILE_WIDTH,
+            row: 2 + 3 * TILE_HEIGHT,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(click);
+        assert_eq!(app.moves(), 1);
+        assert_eq!(app.cursor, (0, 2));
+    }
+}
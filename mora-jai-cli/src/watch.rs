@@ -0,0 +1,67 @@
+//! Solving puzzles again each time they are edited, for transcribing puzzles from the
+//! game a few tiles at a time.
+
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::terminal::{Clear, ClearType};
+use mora_jai_core::RuleSet;
+
+use crate::batch::{self, Report};
+use crate::output::Output;
+
+/// How often watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What changes about a file when it is saved, without reading it. Missing files have
+/// none, so they are shown again once they appear.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Solves the puzzles in `files` on a cleared screen whenever one of them changes,
+/// until interrupted.
+pub fn watch_files(
+    files: &[PathBuf],
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut shown = None;
+    loop {
+        let stamps: Vec<_> = files.iter().map(|path| stamp(path)).collect();
+        if shown.as_ref() != Some(&stamps) {
+            execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+            for path in files {
+                let name = path.display().to_string();
+                match fs::read_to_string(path) {
+                    Ok(text) => batch::solve_inline(&name, 1, &text, report, output, rules)?,
+                    Err(e) => println!("{name}: {e}"),
+                }
+            }
+            println!("Watching for changes, press Ctrl-C to stop");
+            shown = Some(stamps);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Solves each line typed as soon as it is entered, showing problems under the line
+/// rather than stopping, until the input ends.
+pub fn watch_stdin(
+    report: &mut Report,
+    output: &Output,
+    rules: &RuleSet,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Type puzzles one per line, or Ctrl-D to stop");
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        batch::solve_inline("<stdin>", i + 1, &line?, report, output, rules)?;
+    }
+    Ok(())
+}
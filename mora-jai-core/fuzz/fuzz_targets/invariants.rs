@@ -0,0 +1,32 @@
+//! Checks the invariants in `mora_jai_core::check` on puzzles built from fuzzer input:
+//! four bytes of goals, then nine bytes of tiles, each picking a built-in color.
+//!
+//! Run with `cargo fuzz run invariants` from `mora-jai-core`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mora_jai_core::{Color, Grid, Puzzle, check};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((goals, tiles)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+    let Some(tiles) = tiles.first_chunk::<9>() else {
+        return;
+    };
+    let color = |byte: u8| Color::BUILTIN[usize::from(byte) % Color::BUILTIN.len()];
+
+    let grid: Grid = Grid::from_fn(|row, col| color(tiles[row * 3 + col]));
+    if let Err(e) = check::standard_rules(&grid) {
+        panic!("{e}");
+    }
+
+    let goals = goals.map(color);
+    if goals.contains(&Color::Gray) {
+        return;
+    }
+    if let Err(e) = check::solver_output_verifies(&Puzzle::new(goals, grid)) {
+        panic!("{e}");
+    }
+});
@@ -0,0 +1,507 @@
+//! Finding several solutions to a puzzle rather than one.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::puzzle::BuildGridHasher;
+use crate::solver::Limits;
+use crate::{Color, Grid, Puzzle, RuleSet, SolveError, SolveOptions};
+
+/// Marks states from which no goal can be reached within the explored graph.
+const UNREACHABLE: usize = usize::MAX;
+
+/// An optimal solution with how many optimal solutions there are in all.
+type Counted = (Vec<(usize, usize)>, u64);
+
+/// How often each tile is pressed across every optimal solution of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PressCounts<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The presses of each tile, indexed by row and then column, summed over every
+    /// optimal solution. Counts too large for a `u64` are capped at `u64::MAX`.
+    pub counts: [[u64; COLS]; ROWS],
+    /// The number of optimal solutions
+    pub solutions: u64,
+}
+
+impl<const ROWS: usize, const COLS: usize> PressCounts<ROWS, COLS> {
+    /// The times each tile is pressed in an average optimal solution.
+    pub fn per_solution(&self) -> [[f64; COLS]; ROWS] {
+        self.counts
+            .map(|row| row.map(|count| count as f64 / self.solutions.max(1) as f64))
+    }
+}
+
+/// How far from the start a [`StateGraph`] is explored.
+#[derive(Debug, Clone, Copy)]
+enum Extent {
+    /// Until the first solved grid, and then this many presses further
+    Slack(usize),
+    /// To this many presses, whether or not a grid is solved sooner
+    Depth(usize),
+}
+
+/// Every grid within some number of presses of a starting grid, with the presses
+/// between them.
+struct StateGraph<const ROWS: usize, const COLS: usize> {
+    grids: Vec<Grid<ROWS, COLS>>,
+    /// The grid each press leads to, `ROWS * COLS` entries per grid in row then column
+    /// order. Empty for grids at the edge of the graph, which were not expanded.
+    edges: Vec<Vec<usize>>,
+    /// The fewest presses from the start to each grid. Grids are in order of depth.
+    depths: Vec<usize>,
+    /// The fewest presses from each grid to a solved grid
+    to_goal: Vec<usize>,
+}
+
+impl<const ROWS: usize, const COLS: usize> StateGraph<ROWS, COLS> {
+    /// Explores breadth first from `start` as far as `extent`. Returns `None` if no grid
+    /// explored is solved.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(goals, start, rules, options),
+            err(level = "debug")
+        )
+    )]
+    fn explore(
+        goals: &[Color; 4],
+        start: &Grid<ROWS, COLS>,
+        rules: &RuleSet<ROWS, COLS>,
+        extent: Extent,
+        options: &SolveOptions,
+    ) -> Result<Option<(Self, usize)>, SolveError> {
+        let mut limits = Limits::start(options);
+        let mut grids = vec![start.clone()];
+        let mut depths = vec![0];
+        let mut index: HashMap<Grid<ROWS, COLS>, usize, BuildGridHasher> = Default::default();
+        index.insert(start.clone(), 0);
+        let mut edges = vec![];
+        let mut optimal = None;
+
+        let mut next = 0;
+        while next < grids.len() {
+            limits.visit(grids.len())?;
+            let depth = depths[next];
+            #[cfg(feature = "tracing")]
+            if next == 0 || depths[next - 1] < depth {
+                let (visited, queued) = (next, grids.len() - next);
+                tracing::trace!(depth, visited, queued, "searching next layer");
+            }
+            if optimal.is_none() && grids[next].is_solved(goals) {
+                optimal = Some(depth);
+            }
+            let explored = match extent {
+                Extent::Slack(slack) => optimal.is_some_and(|optimal| depth >= optimal + slack),
+                Extent::Depth(max) => depth >= max,
+            };
+            if explored {
+                break;
+            }
+
+            let mut targets = Vec::with_capacity(ROWS * COLS);
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    let grid = grids[next].press_with(rules, row, col);
+                    let target = *index.entry(grid).or_insert_with_key(|grid| {
+                        grids.push(grid.clone());
+                        depths.push(depth + 1);
+                        grids.len() - 1
+                    });
+                    targets.push(target);
+                }
+            }
+            edges.push(targets);
+            next += 1;
+        }
+        // Grids at the edge of a depth limit were not checked as the loop stopped
+        if optimal.is_none() && grids[next..].iter().any(|grid| grid.is_solved(goals)) {
+            optimal = Some(depths[next]);
+        }
+        let Some(optimal) = optimal else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(visited = grids.len(), "no solution");
+            return Ok(None);
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(optimal, states = grids.len(), "explored");
+        edges.resize(grids.len(), vec![]);
+
+        // Distances to the goal, breadth first backwards from every solved grid
+        let mut from = vec![vec![]; grids.len()];
+        for (source, targets) in edges.iter().enumerate() {
+            for &target in targets {
+                from[target].push(source);
+            }
+        }
+        let mut to_goal = vec![UNREACHABLE; grids.len()];
+        let mut queue = VecDeque::new();
+        for (i, grid) in grids.iter().enumerate() {
+            if grid.is_solved(goals) {
+                to_goal[i] = 0;
+                queue.push_back(i);
+            }
+        }
+        while let Some(i) = queue.pop_front() {
+            for &source in &from[i] {
+                if to_goal[source] == UNREACHABLE {
+                    to_goal[source] = to_goal[i] + 1;
+                    queue.push_back(source);
+                }
+            }
+        }
+
+        let graph = Self {
+            grids,
+            edges,
+            depths,
+            to_goal,
+        };
+        Ok(Some((graph, optimal)))
+    }
+
+    fn is_goal(&self, state: usize) -> bool {
+        self.to_goal[state] == 0
+    }
+
+    /// Collects paths of exactly `budget` more presses from `state` which end at their
+    /// first solved grid and never revisit a grid, until `out` holds `count` paths.
+    fn paths(
+        &self,
+        state: usize,
+        budget: usize,
+        path: &mut Vec<(usize, usize)>,
+        on_path: &mut [bool],
+        count: usize,
+        out: &mut Vec<Vec<(usize, usize)>>,
+    ) {
+        if self.is_goal(state) {
+            if budget == 0 {
+                out.push(path.clone());
+            }
+            return;
+        }
+        if budget == 0 {
+            return;
+        }
+        for (press, &next) in self.edges[state].iter().enumerate() {
+            if out.len() == count {
+                return;
+            }
+            if on_path[next] || self.to_goal[next] > budget - 1 {
+                continue;
+            }
+            on_path[next] = true;
+            path.push((press / COLS, press % COLS));
+            self.paths(next, budget - 1, path, on_path, count, out);
+            path.pop();
+            on_path[next] = false;
+        }
+    }
+
+    /// Counts the paths of at most `budget` more presses from `state`, `length` presses
+    /// in, which end at their first solved grid and never revisit a grid, by the length
+    /// of the whole path.
+    fn count_paths(
+        &self,
+        state: usize,
+        length: usize,
+        budget: usize,
+        on_path: &mut [bool],
+        counts: &mut [u64],
+    ) {
+        if self.is_goal(state) {
+            counts[length] = counts[length].saturating_add(1);
+            return;
+        }
+        if budget == 0 {
+            return;
+        }
+        for &next in &self.edges[state] {
+            if on_path[next] || self.to_goal[next] > budget - 1 {
+                continue;
+            }
+            on_path[next] = true;
+            self.count_paths(next, length + 1, budget - 1, on_path, counts);
+            on_path[next] = false;
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// An optimal solution, the same one [`solve`](Self::solve) finds, with how many
+    /// different optimal solutions there are in all. Counts too large for a `u64` are
+    /// capped at `u64::MAX`.
+    pub fn solve_counting(&self) -> Option<Counted> {
+        self.solve_counting_with(&SolveOptions::default())
+            .expect("an unlimited search always finishes")
+    }
+
+    /// [`solve_counting`](Self::solve_counting) within the given limits.
+    pub fn solve_counting_with(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<Option<Counted>, SolveError> {
+        let Some((graph, optimal)) = StateGraph::explore(
+            &self.goals,
+            &self.original,
+            &self.rules,
+            Extent::Slack(0),
+            options,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        // Every optimal path reaches one grid deeper with each press. Grids are in order
+        // of depth, so each grid's count is final before its presses are followed.
+        let mut paths = vec![0u64; graph.grids.len()];
+        paths[0] = 1;
+        for (state, targets) in graph.edges.iter().enumerate() {
+            for &target in targets {
+                if graph.depths[target] == graph.depths[state] + 1 {
+                    paths[target] = paths[target].saturating_add(paths[state]);
+                }
+            }
+        }
+
+        let solved = |state: &usize| graph.depths[*state] == optimal && graph.is_goal(*state);
+        let count = (0..graph.grids.len())
+            .filter(solved)
+            .fold(0u64, |count, state| count.saturating_add(paths[state]));
+
+        // The optimal solution first in the tie-break order takes the first press in that
+        // order which stays on an optimal path, one press at a time
+        let order = options.tie_break.order::<ROWS, COLS>();
+        let mut state = 0;
+        let mut solution = vec![];
+        while graph.to_goal[state] > 0 {
+            let on_path = |&&(row, col): &&(usize, usize)| {
+                let target = graph.edges[state][row * COLS + col];
+                graph.depths[target] == graph.depths[state] + 1
+                    && graph.to_goal[target] == graph.to_goal[state] - 1
+            };
+            let Some(&press) = order.iter().find(on_path) else {
+                return Ok(None);
+            };
+            state = graph.edges[state][press.0 * COLS + press.1];
+            solution.push(press);
+        }
+        Ok(Some((solution, count)))
+    }
+
+    /// How many times each tile is pressed across every optimal solution, showing which
+    /// tiles matter to the puzzle however it is solved. `None` if there is no solution.
+    pub fn press_counts(&self) -> Option<PressCounts<ROWS, COLS>> {
+        let unlimited = SolveOptions::default();
+        let (graph, optimal) = StateGraph::explore(
+            &self.goals,
+            &self.original,
+            &self.rules,
+            Extent::Slack(0),
+            &unlimited,
+        )
+        .expect("an unlimited search always finishes")?;
+        let next = |state: usize, target: usize| graph.depths[target] == graph.depths[state] + 1;
+
+        // Optimal paths from the start to each grid, then from each grid to a goal
+        let mut from_start = vec![0u64; graph.grids.len()];
+        from_start[0] = 1;
+        for (state, targets) in graph.edges.iter().enumerate() {
+            for &target in targets.iter().filter(|&&target| next(state, target)) {
+                from_start[target] = from_start[target].saturating_add(from_start[state]);
+            }
+        }
+        let mut to_goal = vec![0u64; graph.grids.len()];
+        for state in (0..graph.grids.len()).rev() {
+            to_goal[state] = if graph.depths[state] == optimal {
+                u64::from(graph.is_goal(state))
+            } else {
+                graph.edges[state]
+                    .iter()
+                    .filter(|&&target| next(state, target))
+                    .fold(0u64, |paths, &target| paths.saturating_add(to_goal[target]))
+            };
+        }
+
+        // Each press is made by every optimal path leading to it and on from it
+        let mut counts = [[0u64; COLS]; ROWS];
+        for (state, targets) in graph.edges.iter().enumerate() {
+            for (press, &target) in targets.iter().enumerate() {
+                if next(state, target) {
+                    let paths = from_start[state].saturating_mul(to_goal[target]);
+                    let count = &mut counts[press / COLS][press % COLS];
+                    *count = count.saturating_add(paths);
+                }
+            }
+        }
+        Some(PressCounts {
+            counts,
+            solutions: to_goal[0],
+        })
+    }
+
+    /// Up to `count` distinct solutions, shortest first, including those up to `slack`
+    /// presses longer than an optimal one. Solutions end as soon as the puzzle is solved
+    /// and never return to a grid they have already left, so a longer solution is not
+    /// just a shorter one with wasted presses.
+    pub fn solutions(&self, count: usize, slack: usize) -> Vec<Vec<(usize, usize)>> {
+        let unlimited = SolveOptions::default();
+        let Ok(Some((graph, optimal))) = StateGraph::explore(
+            &self.goals,
+            &self.original,
+            &self.rules,
+            Extent::Slack(slack),
+            &unlimited,
+        ) else {
+            return vec![];
+        };
+
+        let mut out = vec![];
+        let mut on_path = vec![false; graph.grids.len()];
+        on_path[0] = true;
+        for length in optimal..=optimal + slack {
+            if out.len() == count {
+                break;
+            }
+            graph.paths(0, length, &mut vec![], &mut on_path, count, &mut out);
+        }
+        out
+    }
+
+    /// How many distinct solutions there are of each length from none to `max_length`
+    /// presses, indexed by length, counting solutions as [`solutions`](Self::solutions)
+    /// lists them. Lengths below the optimal have none, as do all lengths if there is no
+    /// solution. Counts too large for a `u64` are capped at `u64::MAX`.
+    pub fn solution_counts(&self, max_length: usize) -> Vec<u64> {
+        let mut counts = vec![0; max_length + 1];
+        let unlimited = SolveOptions::default();
+        let Ok(Some((graph, optimal))) = StateGraph::explore(
+            &self.goals,
+            &self.original,
+            &self.rules,
+            Extent::Depth(max_length),
+            &unlimited,
+        ) else {
+            return counts;
+        };
+        debug_assert!(optimal <= max_length);
+
+        let mut on_path = vec![false; graph.grids.len()];
+        on_path[0] = true;
+        graph.count_paths(0, 0, max_length, &mut on_path, &mut counts);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_optimal_solutions() {
+        let solved = Puzzle::new([Color::Gray; 4], Grid::new([Color::Gray; 9]));
+        assert_eq!(solved.solve_counting(), Some((vec![], 1)));
+
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        let (solution, count) = puzzle.solve_counting().unwrap();
+        assert_eq!(Some(solution), puzzle.solve().map(|s| s.presses));
+        assert_eq!(count, puzzle.solutions(100, 0).len() as u64);
+    }
+
+    #[test]
+    fn press_counts_cover_every_optimal_solution() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        let solutions = puzzle.solutions(100, 0);
+        let mut expected = [[0u64; 3]; 3];
+        for &(row, col) in solutions.iter().flatten() {
+            expected[row][col] += 1;
+        }
+
+        let counts = puzzle.press_counts().unwrap();
+        assert_eq!(counts.solutions, solutions.len() as u64);
+        assert_eq!(counts.counts, expected);
+        assert_eq!(counts.per_solution()[0][2], 0.5);
+    }
+
+    #[test]
+    fn solutions_come_shortest_first() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+
+        let optimal = puzzle.solutions(5, 0);
+        assert!(!optimal.is_empty());
+        assert!(optimal.iter().all(|solution| solution.len() == 2));
+        assert!(optimal.contains(&puzzle.solve().unwrap().presses));
+
+        let solutions = puzzle.solutions(50, 2);
+        assert!(solutions.len() > optimal.len());
+        assert!(solutions.is_sorted_by_key(|solution| solution.len()));
+        let goals = *puzzle.goals();
+        for solution in &solutions {
+            let mut grid = puzzle.original().clone();
+            for &(row, col) in solution {
+                assert!(!grid.is_solved(&goals), "solved before the end");
+                grid = grid.press(row, col);
+            }
+            assert!(grid.is_solved(&goals));
+        }
+        let distinct: std::collections::HashSet<_> = solutions.iter().collect();
+        assert_eq!(distinct.len(), solutions.len());
+    }
+
+    #[test]
+    fn solution_counts_match_the_solutions_listed() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        let mut expected = vec![0u64; 5];
+        for solution in puzzle.solutions(usize::MAX, 2) {
+            expected[solution.len()] += 1;
+        }
+
+        let counts = puzzle.solution_counts(4);
+        assert_eq!(counts, expected);
+        assert_eq!(counts[..2], [0, 0]);
+        assert_eq!(puzzle.solution_counts(1), [0, 0]);
+    }
+
+    #[test]
+    fn solution_counts_reach_the_limit() {
+        let puzzle = Puzzle::new(
+            [Color::Gray, Color::Yellow, Color::White, Color::Gray],
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Red],
+                [Color::Gray, Color::Gray, Color::Yellow],
+                [Color::White, Color::Gray, Color::Gray],
+            ),
+        );
+        assert_eq!(puzzle.solve().map(|s| s.presses.len()), Some(1));
+        assert_eq!(puzzle.solution_counts(1), [0, 1]);
+        assert_eq!(puzzle.solution_counts(0), [0]);
+    }
+}
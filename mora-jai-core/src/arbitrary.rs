@@ -0,0 +1,95 @@
+//! [`proptest`](mod@proptest) strategies for colors, grids and puzzles, so that tools
+//! built on this crate can be property tested against puzzles like the ones they will
+//! be given.
+//!
+//! Each type's [`Arbitrary`] implementation draws from the builtin colors under the
+//! standard rules. Every builtin color in a puzzle makes the solver's search much
+//! larger, so for tests which solve the puzzles they are given, strategies drawing from
+//! a few colors, such as [`puzzle_of`], keep cases quick.
+
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::{Color, Grid, Puzzle, RuleSet};
+
+/// Grids whose tiles are drawn from `colors`.
+///
+/// # Panics
+///
+/// Panics if `colors` is empty.
+pub fn grid_of<const ROWS: usize, const COLS: usize>(
+    colors: &[Color],
+) -> impl Strategy<Value = Grid<ROWS, COLS>> + use<ROWS, COLS> {
+    proptest::collection::vec(select(colors.to_vec()), ROWS * COLS)
+        .prop_map(|tiles| Grid::from_fn(|row, col| tiles[row * COLS + col]))
+}
+
+/// Puzzles under the standard rules whose tiles are drawn from `colors` and whose goals
+/// are drawn from those other than gray, as a gray goal would start the puzzle solved.
+///
+/// # Panics
+///
+/// Panics if `colors` has no color other than gray.
+pub fn puzzle_of<const ROWS: usize, const COLS: usize>(
+    colors: &[Color],
+) -> impl Strategy<Value = Puzzle<ROWS, COLS>> + use<ROWS, COLS> {
+    let goals: Vec<Color> = colors
+        .iter()
+        .copied()
+        .filter(|&color| color != Color::Gray)
+        .collect();
+    (proptest::array::uniform4(select(goals)), grid_of(colors))
+        .prop_map(|(goals, grid)| Puzzle::with_rules(goals, grid, RuleSet::standard()))
+}
+
+/// Any builtin color.
+impl Arbitrary for Color {
+    type Parameters = ();
+    type Strategy = proptest::sample::Select<Color>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(Color::BUILTIN.to_vec())
+    }
+}
+
+/// Grids of builtin colors.
+impl<const ROWS: usize, const COLS: usize> Arbitrary for Grid<ROWS, COLS> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        grid_of(&Color::BUILTIN).boxed()
+    }
+}
+
+/// Puzzles of builtin colors under the standard rules, with goals other than gray.
+impl<const ROWS: usize, const COLS: usize> Arbitrary for Puzzle<ROWS, COLS> {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        puzzle_of(&Color::BUILTIN).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn puzzles_start_unsolved(puzzle in any::<Puzzle>()) {
+            prop_assert!(!puzzle.goals().contains(&Color::Gray));
+            prop_assert!(!puzzle.is_solved());
+        }
+
+        #[test]
+        fn grids_keep_to_their_colors(grid in grid_of::<2, 4>(&[Color::Gray, Color::White])) {
+            for row in 0..2 {
+                for col in 0..4 {
+                    prop_assert!(matches!(grid.get(row, col), Color::Gray | Color::White));
+                }
+            }
+        }
+    }
+}
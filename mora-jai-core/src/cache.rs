@@ -0,0 +1,187 @@
+//! Remembering solution lengths across searches, so that solving many puzzles which
+//! reach the same grids, such as while generating or filtering puzzles, does not search
+//! those grids again.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::puzzle::BuildGridHasher;
+use crate::{Color, Grid, RuleSet};
+
+/// The grids of one set of goals, with the fewest presses solving each, or `None` for
+/// grids which cannot be solved.
+type Distances<const ROWS: usize, const COLS: usize> =
+    HashMap<Grid<ROWS, COLS>, Option<usize>, BuildGridHasher>;
+
+/// A transposition table of the fewest presses solving grids under one rule set, kept
+/// between searches.
+///
+/// Each search remembers the length it finds for its starting grid, and a search
+/// finding no solution remembers every grid it reached as unsolvable, as none of them
+/// can be solved either. Later searches stop at remembered grids instead of searching
+/// past them. Once the cache holds `capacity` grids, nothing more is remembered.
+#[derive(Clone)]
+pub struct DistanceCache<const ROWS: usize = 3, const COLS: usize = 3> {
+    rules: RuleSet<ROWS, COLS>,
+    capacity: usize,
+    len: usize,
+    distances: HashMap<[Color; 4], Distances<ROWS, COLS>>,
+    hits: usize,
+}
+
+impl<const ROWS: usize, const COLS: usize> DistanceCache<ROWS, COLS> {
+    /// An empty cache for grids following `rules`, remembering at most `capacity`
+    /// grids.
+    pub fn new(rules: RuleSet<ROWS, COLS>, capacity: usize) -> Self {
+        Self {
+            rules,
+            capacity,
+            len: 0,
+            distances: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// The number of grids remembered.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many times a search has stopped at a remembered grid.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Forgets every grid.
+    pub fn clear(&mut self) {
+        self.distances.clear();
+        self.len = 0;
+    }
+
+    fn remember(&mut self, goals: &[Color; 4], grid: Grid<ROWS, COLS>, distance: Option<usize>) {
+        if self.len >= self.capacity {
+            return;
+        }
+        let distances = self.distances.entry(*goals).or_default();
+        if distances.insert(grid, distance).is_none() {
+            self.len += 1;
+        }
+    }
+
+    /// The number of presses in a shortest solution from `grid` to `goals`, or `None`
+    /// if it cannot be solved, searching only where the cache does not already know.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn solution_length(
+        &mut self,
+        goals: &[Color; 4],
+        grid: &Grid<ROWS, COLS>,
+    ) -> Option<usize> {
+        if let Some(&distance) = self.distances.get(goals).and_then(|known| known.get(grid)) {
+            self.hits += 1;
+            return distance;
+        }
+        if grid.is_solved(goals) {
+            self.remember(goals, grid.clone(), Some(0));
+            return Some(0);
+        }
+
+        let mut seen: HashSet<Grid<ROWS, COLS>, BuildGridHasher> = Default::default();
+        seen.insert(grid.clone());
+        let mut layer = vec![grid.clone()];
+        let mut depth = 0;
+        // The shortest solution through a remembered grid, which later layers can only
+        // beat by being solved sooner
+        let mut best: Option<usize> = None;
+        let known = self.distances.get(goals);
+
+        while !layer.is_empty() && best.is_none_or(|best| depth + 1 < best) {
+            depth += 1;
+            let mut next = vec![];
+            'layer: for grid in &layer {
+                for row in 0..ROWS {
+                    for col in 0..COLS {
+                        let new_grid = grid.press_with(&self.rules, row, col);
+                        if seen.contains(&new_grid) {
+                            continue;
+                        }
+                        if new_grid.is_solved(goals) {
+                            best = Some(depth);
+                            break 'layer;
+                        }
+                        match known.and_then(|known| known.get(&new_grid)) {
+                            // Grids which cannot be solved are not worth searching past
+                            Some(None) => self.hits += 1,
+                            Some(Some(distance)) => {
+                                self.hits += 1;
+                                let through = depth + distance;
+                                best = Some(best.map_or(through, |best| best.min(through)));
+                            }
+                            None => next.push(new_grid.clone()),
+                        }
+                        seen.insert(new_grid);
+                    }
+                }
+            }
+            layer = next;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            length = best,
+            visited = seen.len(),
+            hits = self.hits,
+            "searched"
+        );
+        if best.is_some() {
+            self.remember(goals, grid.clone(), best);
+        } else {
+            // Nothing reached can be solved, or it would have led to a solution
+            for grid in seen {
+                self.remember(goals, grid, None);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solution_length;
+
+    #[test]
+    fn remembered_lengths_agree_with_fresh_searches() {
+        let rules = RuleSet::standard();
+        let mut cache = DistanceCache::new(rules.clone(), 1_000_000);
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::Gray, Color::White, Color::Gray],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+
+        // The grid itself, then every grid a press away, which search through it
+        let mut grids = vec![grid.clone()];
+        grids.extend(
+            (0..3)
+                .flat_map(|row| (0..3).map(move |col| (row, col)))
+                .map(|(row, col)| grid.press_with(&rules, row, col)),
+        );
+        for goals in [[Color::White; 4], [Color::Black; 4]] {
+            for grid in &grids {
+                let expected = solution_length(&goals, grid, &rules);
+                assert_eq!(cache.solution_length(&goals, grid), expected, "{grid:?}");
+            }
+        }
+        assert!(cache.hits() > 0);
+
+        // Black goals cannot be reached, so every grid reached was remembered
+        let remembered = cache.len();
+        assert!(remembered > grids.len());
+        let pressed = grids[1].press_with(&rules, 1, 1);
+        assert_eq!(cache.solution_length(&[Color::Black; 4], &pressed), None);
+        assert_eq!(cache.len(), remembered);
+    }
+}
@@ -0,0 +1,156 @@
+//! Generating series of boxes where solving each one reveals the next one's goals, like
+//! the chained boxes of a story.
+
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::solver::solution_length_within;
+use crate::{Color, Grid, Puzzle, RuleSet};
+
+/// How the grid a box is solved with gives the goals of the box after it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ChainLink {
+    /// The corner colors, so every box shares the first box's goals
+    #[default]
+    Corners,
+    /// The middle tile of each edge, read top, left, right, bottom for the NW, NE, SW
+    /// and SE goals, so each box's solution decides the next one's goals
+    Edges,
+}
+
+impl ChainLink {
+    /// The goals, in goal order (NW, NE, SW, SE), revealed by a solved grid.
+    pub fn next_goals<const ROWS: usize, const COLS: usize>(
+        self,
+        solved: &Grid<ROWS, COLS>,
+    ) -> [Color; 4] {
+        let tiles = match self {
+            ChainLink::Corners => Grid::<ROWS, COLS>::corner_tiles(),
+            ChainLink::Edges => [
+                (ROWS - 1, COLS / 2),
+                (ROWS / 2, 0),
+                (ROWS / 2, COLS - 1),
+                (0, COLS / 2),
+            ],
+        };
+        tiles.map(|(row, col)| *solved.get(row, col))
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Generates `count` puzzles whose optimal solutions take a number of presses in the
+    /// given range, where each puzzle's goals are revealed by the grid its predecessor's
+    /// solution leaves, through `link`.
+    ///
+    /// The solution taken for each link is the one [`solve`](Self::solve) returns, so
+    /// solving a box another way can leave a different grid. A box is generated again
+    /// if its solution would reveal a gray goal, or goals no puzzle of the right length
+    /// is quickly found for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rules have no colors other than gray.
+    pub fn new_campaign(
+        rules: RuleSet<ROWS, COLS>,
+        lengths: RangeInclusive<usize>,
+        count: usize,
+        link: ChainLink,
+    ) -> Vec<Self> {
+        campaign(rules, lengths, count, link, &mut rand::rng())
+    }
+}
+
+/// How many grids to try for the goals one box reveals before generating that box again.
+const BOX_ATTEMPTS: usize = 10_000;
+
+/// A random puzzle with the given goals, or random ones which are not gray, whose
+/// optimal solution length is in `lengths`, if one is found within [`BOX_ATTEMPTS`].
+fn random_box<const ROWS: usize, const COLS: usize>(
+    rules: &RuleSet<ROWS, COLS>,
+    lengths: &RangeInclusive<usize>,
+    goals: Option<[Color; 4]>,
+    rng: &mut impl Rng,
+) -> Option<Puzzle<ROWS, COLS>> {
+    let palette: Vec<Color> = rules.colors().collect();
+    let goal_colors: Vec<Color> = palette
+        .iter()
+        .copied()
+        .filter(|&color| color != Color::Gray)
+        .collect();
+    assert!(
+        !goal_colors.is_empty(),
+        "rule set has no colors to generate goals from"
+    );
+
+    for _ in 0..BOX_ATTEMPTS {
+        let goals =
+            goals.unwrap_or_else(|| std::array::from_fn(|_| *goal_colors.choose(rng).unwrap()));
+        let grid = Grid::from_fn(|_, _| *palette.choose(rng).unwrap());
+        if solution_length_within(&goals, &grid, rules, *lengths.end())
+            .is_some_and(|presses| lengths.contains(&presses))
+        {
+            return Some(Puzzle::with_rules(goals, grid, rules.clone()));
+        }
+    }
+    None
+}
+
+/// Generates boxes in order, going back to generate a box again when no puzzle is found
+/// for the goals it reveals.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn campaign<const ROWS: usize, const COLS: usize>(
+    rules: RuleSet<ROWS, COLS>,
+    lengths: RangeInclusive<usize>,
+    count: usize,
+    link: ChainLink,
+    rng: &mut impl Rng,
+) -> Vec<Puzzle<ROWS, COLS>> {
+    let mut boxes: Vec<Puzzle<ROWS, COLS>> = Vec::with_capacity(count);
+    let mut revealed: Vec<[Color; 4]> = Vec::with_capacity(count);
+    while boxes.len() < count {
+        let Some(puzzle) = random_box(&rules, &lengths, revealed.last().copied(), rng) else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(boxes = boxes.len(), "no box found for the revealed goals");
+            boxes.pop();
+            revealed.pop();
+            continue;
+        };
+
+        let solution = puzzle.solve().expect("generated puzzles are solvable");
+        let next = link.next_goals(&solution.grid);
+        // A gray goal would leave the next box solved from the start
+        if boxes.len() + 1 < count && next.contains(&Color::Gray) {
+            continue;
+        }
+        boxes.push(puzzle);
+        revealed.push(next);
+    }
+    boxes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_box_reveals_the_next_goals() {
+        let mut rules: RuleSet = RuleSet::standard();
+        rules.retain_colors(|color| {
+            matches!(
+                color,
+                Color::Gray | Color::White | Color::Black | Color::Yellow
+            )
+        });
+
+        for link in [ChainLink::Corners, ChainLink::Edges] {
+            let boxes = Puzzle::new_campaign(rules.clone(), 1..=3, 4, link);
+            assert_eq!(boxes.len(), 4);
+            for pair in boxes.windows(2) {
+                let solved = pair[0].solve().unwrap().grid;
+                assert_eq!(&link.next_goals(&solved), pair[1].goals(), "{link:?}");
+            }
+        }
+    }
+}
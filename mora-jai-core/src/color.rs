@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::RwLock;
+
+/// The color of a tile.
+///
+/// Besides the colors found in Blue Prince, extra colors can be registered at runtime
+/// with [`Color::register`]. Pressing a custom color does nothing unless a rule is added
+/// for it to the [`RuleSet`](crate::RuleSet) in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Color {
+    Gray,
+    White,
+    Black,
+    Red,
+    Orange,
+    Green,
+    Yellow,
+    Violet,
+    Pink,
+    Blue,
+    /// A registered color, identified by its index in the registry
+    Custom(u8),
+}
+
+/// The details of a color registered at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CustomColor {
+    name: &'static str,
+    code: char,
+    rgb: [u8; 3],
+}
+
+static REGISTRY: RwLock<Vec<CustomColor>> = RwLock::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterColorError {
+    /// The name is already used by a different color
+    NameTaken(String),
+    /// The notation code is already used by a different color
+    CodeTaken(char),
+    /// No more colors can be registered
+    RegistryFull,
+}
+
+impl fmt::Display for RegisterColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterColorError::NameTaken(name) => write!(f, "color name {name:?} is taken"),
+            RegisterColorError::CodeTaken(code) => write!(f, "color code {code:?} is taken"),
+            RegisterColorError::RegistryFull => write!(f, "too many custom colors"),
+        }
+    }
+}
+
+impl std::error::Error for RegisterColorError {}
+
+impl Color {
+    /// The colors found in Blue Prince.
+    pub const BUILTIN: [Color; 10] = [
+        Color::Gray,
+        Color::White,
+        Color::Black,
+        Color::Red,
+        Color::Orange,
+        Color::Green,
+        Color::Yellow,
+        Color::Violet,
+        Color::Pink,
+        Color::Blue,
+    ];
+
+    /// Registers an extra color with the given name, single-character notation code and
+    /// display color.
+    ///
+    /// Registering the same color again returns the existing color, but names and codes
+    /// cannot be shared between different colors.
+    pub fn register(name: &str, code: char, rgb: [u8; 3]) -> Result<Color, RegisterColorError> {
+        let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(index) = registry
+            .iter()
+            .position(|c| c.name == name && c.code == code && c.rgb == rgb)
+        {
+            return Ok(Color::Custom(index as u8));
+        }
+
+        let builtin_name = Self::BUILTIN.iter().any(|c| c.name() == name);
+        if builtin_name || registry.iter().any(|c| c.name == name) {
+            return Err(RegisterColorError::NameTaken(name.to_string()));
+        }
+        let builtin_code = Self::BUILTIN.iter().any(|c| c.code() == code);
+        if builtin_code || registry.iter().any(|c| c.code == code) {
+            return Err(RegisterColorError::CodeTaken(code));
+        }
+
+        let index = u8::try_from(registry.len()).map_err(|_| RegisterColorError::RegistryFull)?;
+        registry.push(CustomColor {
+            // Registered colors live for the rest of the program, so leaking the name
+            // lets every color hand out a &'static str
+            name: Box::leak(name.to_string().into_boxed_str()),
+            code,
+            rgb,
+        });
+
+        Ok(Color::Custom(index))
+    }
+
+    /// All colors: the builtin colors followed by any registered colors.
+    pub fn all() -> Vec<Color> {
+        let registered = REGISTRY.read().unwrap_or_else(|e| e.into_inner()).len();
+        Self::BUILTIN
+            .into_iter()
+            .chain((0..registered).map(|i| Color::Custom(i as u8)))
+            .collect()
+    }
+
+    fn custom(index: u8) -> CustomColor {
+        let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+        *registry
+            .get(index as usize)
+            .expect("custom colors are only created by registration")
+    }
+
+    /// A number unique to each color, builtin colors first in the order of
+    /// [`Color::BUILTIN`].
+    pub(crate) fn ordinal(self) -> u64 {
+        match self {
+            Color::Gray => 0,
+            Color::White => 1,
+            Color::Black => 2,
+            Color::Red => 3,
+            Color::Orange => 4,
+            Color::Green => 5,
+            Color::Yellow => 6,
+            Color::Violet => 7,
+            Color::Pink => 8,
+            Color::Blue => 9,
+            Color::Custom(index) => 10 + u64::from(index),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Color::Gray => "gray",
+            Color::White => "white",
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Orange => "orange",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Violet => "violet",
+            Color::Pink => "pink",
+            Color::Blue => "blue",
+            Color::Custom(index) => Self::custom(*index).name,
+        }
+    }
+
+    /// Looks up a color by its name, as returned by [`Color::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|c| c.name() == name)
+    }
+
+    /// The character representing this color in puzzle notation.
+    pub fn code(&self) -> char {
+        match self {
+            Color::Gray => '-',
+            Color::White => 'w',
+            Color::Black => 'k',
+            Color::Red => 'r',
+            Color::Orange => 'o',
+            Color::Green => 'g',
+            Color::Yellow => 'y',
+            Color::Violet => 'v',
+            Color::Pink => 'p',
+            Color::Blue => 'b',
+            Color::Custom(index) => Self::custom(*index).code,
+        }
+    }
+
+    /// Looks up a color by its notation character, as returned by [`Color::code`].
+    pub fn from_code(code: char) -> Option<Self> {
+        Self::all().into_iter().find(|c| c.code() == code)
+    }
+
+    /// The color used to display tiles of this color.
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            Color::Gray => [128, 128, 128],
+            Color::White => [255, 255, 255],
+            Color::Black => [0, 0, 0],
+            Color::Red => [255, 0, 0],
+            Color::Orange => [255, 165, 0],
+            Color::Green => [0, 255, 0],
+            Color::Yellow => [255, 255, 0],
+            Color::Violet => [127, 0, 255],
+            Color::Pink => [255, 192, 203],
+            Color::Blue => [0, 0, 255],
+            Color::Custom(index) => Self::custom(*index).rgb,
+        }
+    }
+}
+
+/// The names and notation codes used for colors, such as translated names or codes
+/// that avoid a clash between two colors' initials.
+///
+/// Colors without a label of their own go by [`Color::name`] and [`Color::code`], so
+/// the default labels are the ones used everywhere else.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColorLabels {
+    labels: BTreeMap<Color, (String, char)>,
+}
+
+impl ColorLabels {
+    /// Labels each given color with a name and code, failing if any name or code would
+    /// then belong to two colors.
+    pub fn new(
+        labels: impl IntoIterator<Item = (Color, String, char)>,
+    ) -> Result<Self, RegisterColorError> {
+        let labels = Self {
+            labels: labels
+                .into_iter()
+                .map(|(color, name, code)| (color, (name, code)))
+                .collect(),
+        };
+        let colors = Color::all();
+        for (i, color) in colors.iter().enumerate() {
+            for other in &colors[..i] {
+                if labels.name(*color) == labels.name(*other) {
+                    return Err(RegisterColorError::NameTaken(
+                        labels.name(*color).to_string(),
+                    ));
+                }
+                if labels.code(*color) == labels.code(*other) {
+                    return Err(RegisterColorError::CodeTaken(labels.code(*color)));
+                }
+            }
+        }
+        Ok(labels)
+    }
+
+    pub fn name(&self, color: Color) -> &str {
+        self.labels
+            .get(&color)
+            .map_or_else(|| color.name(), |(name, _)| name)
+    }
+
+    /// The character representing `color` in puzzle notation.
+    pub fn code(&self, color: Color) -> char {
+        self.labels
+            .get(&color)
+            .map_or_else(|| color.code(), |(_, code)| *code)
+    }
+
+    /// Looks up a color by its name, as returned by [`ColorLabels::name`].
+    pub fn from_name(&self, name: &str) -> Option<Color> {
+        Color::all().into_iter().find(|c| self.name(*c) == name)
+    }
+
+    /// Looks up a color by its notation character, as returned by [`ColorLabels::code`].
+    pub fn from_code(&self, code: char) -> Option<Color> {
+        Color::all().into_iter().find(|c| self.code(*c) == code)
+    }
+}
+
+/// Colors are serialized by name.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Color::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown color {name:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_colors_are_found() {
+        let gold = Color::register("gold", 'G', [212, 175, 55]).unwrap();
+
+        assert_eq!(gold.name(), "gold");
+        assert_eq!(gold.code(), 'G');
+        assert_eq!(gold.rgb(), [212, 175, 55]);
+        assert_eq!(Color::from_name("gold"), Some(gold));
+        assert_eq!(Color::from_code('G'), Some(gold));
+        assert!(Color::all().contains(&gold));
+
+        // Registering the same color twice is harmless
+        assert_eq!(Color::register("gold", 'G', [212, 175, 55]), Ok(gold));
+    }
+
+    #[test]
+    fn conflicting_registrations_are_rejected() {
+        assert_eq!(
+            Color::register("pink", 'P', [0, 0, 0]),
+            Err(RegisterColorError::NameTaken(String::from("pink")))
+        );
+        assert_eq!(
+            Color::register("teal", 'k', [0, 128, 128]),
+            Err(RegisterColorError::CodeTaken('k'))
+        );
+    }
+
+    #[test]
+    fn labels_rename_colors_without_clashes() {
+        let labels = ColorLabels::new([
+            (Color::Pink, String::from("rosa"), 'r'),
+            (Color::Red, String::from("rot"), 'R'),
+        ])
+        .unwrap();
+        assert_eq!(labels.name(Color::Pink), "rosa");
+        assert_eq!(labels.from_code('r'), Some(Color::Pink));
+        assert_eq!(labels.from_code('R'), Some(Color::Red));
+        assert_eq!(labels.from_name("white"), Some(Color::White));
+        assert_eq!(labels.from_name("pink"), None);
+
+        assert_eq!(
+            ColorLabels::new([(Color::Pink, String::from("rosa"), 'r')]),
+            Err(RegisterColorError::CodeTaken('r'))
+        );
+    }
+}
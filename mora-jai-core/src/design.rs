@@ -0,0 +1,176 @@
+//! Designing puzzles backwards, from the solution they should have.
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::solver::solution_length_within;
+use crate::{Color, Grid, Puzzle, RuleSet};
+
+/// How many starting grids to try before settling for the closest one found.
+const DESIGN_ATTEMPTS: usize = 200_000;
+
+/// A puzzle designed around a chosen solution.
+#[derive(Debug, Clone)]
+pub struct Design<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The puzzle, which the chosen presses always solve
+    pub puzzle: Puzzle<ROWS, COLS>,
+    /// The number of presses in the puzzle's optimal solution, fewer than the chosen
+    /// presses if a shorter way around them was left open
+    pub optimal_length: usize,
+    /// Whether the chosen presses are an optimal solution
+    pub exact: bool,
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Searches for a starting grid, under the standard rules, whose optimal solution is
+    /// exactly the given presses. See [`Puzzle::design_from_solution_with_rules`].
+    pub fn design_from_solution(
+        presses: &[(usize, usize)],
+        goals: [Color; 4],
+    ) -> Option<Design<ROWS, COLS>> {
+        Self::design_from_solution_with_rules(RuleSet::standard(), presses, goals)
+    }
+
+    /// Searches for a starting grid whose optimal solution is exactly the given presses.
+    ///
+    /// Other solutions of the same length may remain, such as the same presses made in
+    /// another order where they do not affect each other, and the solver may find one of
+    /// those instead.
+    ///
+    /// If none is found, returns the closest: a grid the presses solve whose optimal
+    /// solution is as long as could be found. Returns `None` only if no grid tried was
+    /// solved by the presses at all, such as when a goal's color has no rule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a press is off the grid.
+    pub fn design_from_solution_with_rules(
+        rules: RuleSet<ROWS, COLS>,
+        presses: &[(usize, usize)],
+        goals: [Color; 4],
+    ) -> Option<Design<ROWS, COLS>> {
+        design(rules, presses, goals, &mut rand::rng())
+    }
+}
+
+/// Whether pressing `presses` in turn from `grid` solves it.
+fn solved_by<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    presses: &[(usize, usize)],
+    goals: &[Color; 4],
+) -> bool {
+    let mut grid = grid.clone();
+    for &(row, col) in presses {
+        grid = grid.press_with(rules, row, col);
+    }
+    grid.is_solved(goals)
+}
+
+/// Hill climbs from random grids towards one whose optimal solution is `presses`,
+/// keeping the grid with the longest optimal solution so far and changing a tile or two
+/// of it at a time.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn design<const ROWS: usize, const COLS: usize>(
+    rules: RuleSet<ROWS, COLS>,
+    presses: &[(usize, usize)],
+    goals: [Color; 4],
+    rng: &mut impl Rng,
+) -> Option<Design<ROWS, COLS>> {
+    if let Some((row, col)) = presses
+        .iter()
+        .find(|&&(row, col)| row >= ROWS || col >= COLS)
+    {
+        panic!("press ({row}, {col}) is off the {ROWS}x{COLS} grid");
+    }
+    let palette: Vec<Color> = rules.colors().collect();
+    if palette.is_empty() {
+        return None;
+    }
+    let random_color = |rng: &mut _| *palette.choose(rng).unwrap();
+
+    let mut best: Option<(Grid<ROWS, COLS>, usize)> = None;
+    for _ in 0..DESIGN_ATTEMPTS {
+        let grid = match &best {
+            Some((grid, _)) if rng.random_bool(0.9) => {
+                let mut grid = grid.clone();
+                for _ in 0..rng.random_range(1..=2) {
+                    let (row, col) = (rng.random_range(0..ROWS), rng.random_range(0..COLS));
+                    let color = random_color(rng);
+                    grid.set(row, col, color);
+                }
+                grid
+            }
+            _ => Grid::from_fn(|_, _| random_color(rng)),
+        };
+        if !solved_by(&grid, &rules, presses, &goals) {
+            continue;
+        }
+
+        let length = solution_length_within(&goals, &grid, &rules, presses.len())
+            .expect("the presses solve the grid");
+        if length == presses.len() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(length, "found exact design");
+            return Some(Design {
+                puzzle: Puzzle::with_rules(goals, grid, rules),
+                optimal_length: length,
+                exact: true,
+            });
+        }
+        // Equally long grids replace the best too, so the climb can cross plateaus
+        if best.as_ref().is_none_or(|&(_, best)| length >= best) {
+            best = Some((grid, length));
+        }
+    }
+
+    let (grid, length) = best?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(length, "settled for closest design");
+    Some(Design {
+        puzzle: Puzzle::with_rules(goals, grid, rules),
+        optimal_length: length,
+        exact: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn designs_are_solved_by_their_presses() {
+        let rules = RuleSet::<3, 3>::standard();
+        let mut rng = StdRng::seed_from_u64(7);
+        for presses in [&[(1, 1)][..], &[(0, 2), (0, 1)], &[(2, 0), (1, 2), (0, 0)]] {
+            let design = design(rules.clone(), presses, [Color::White; 4], &mut rng).unwrap();
+            let puzzle = &design.puzzle;
+            assert!(solved_by(
+                puzzle.original(),
+                &rules,
+                presses,
+                puzzle.goals()
+            ));
+            assert!(design.optimal_length <= presses.len());
+            if design.exact {
+                assert_eq!(puzzle.solve().map(|s| s.presses.len()), Some(presses.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn single_presses_are_designed_exactly() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let design = design(
+            RuleSet::<3, 3>::standard(),
+            &[(1, 1)],
+            [Color::Yellow; 4],
+            &mut rng,
+        )
+        .unwrap();
+        assert!(design.exact);
+    }
+}
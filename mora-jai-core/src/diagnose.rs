@@ -0,0 +1,175 @@
+//! Explaining why a puzzle has no solution, such as to find the tile mistyped while
+//! copying it from the game.
+
+use std::collections::HashMap;
+
+use crate::puzzle::BuildGridHasher;
+use crate::solver::Limits;
+use crate::{Corner, Grid, Puzzle, SolveError, SolveOptions};
+
+/// The corners in goal order.
+const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+/// What a search of every grid reachable from a puzzle's start found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The number of grids reachable from the start, all of which were searched
+    pub visited: usize,
+    /// A reachable grid with as many corner tiles showing their goals as any, reached
+    /// in the fewest presses
+    pub closest: Grid<ROWS, COLS>,
+    /// The presses reaching `closest`
+    pub presses: Vec<(usize, usize)>,
+    /// Which corners of `closest` show their goals, in goal order (NW, NE, SW, SE)
+    pub matched: [bool; 4],
+    /// Whether some reachable grid shows the goals of each set of corners at once,
+    /// indexed by a bitmask of the corners in goal order
+    together: [bool; 16],
+}
+
+impl<const ROWS: usize, const COLS: usize> Diagnosis<ROWS, COLS> {
+    /// Whether some reachable grid shows the goals of all of `corners` at once.
+    pub fn achievable(&self, corners: &[Corner]) -> bool {
+        self.together[mask(corners)]
+    }
+
+    /// The smallest sets of corners whose goals no reachable grid shows at once, such
+    /// as a single corner whose goal color never reaches it, or two corners which can
+    /// each be matched but never together. Sets containing one of these are left out.
+    pub fn never_together(&self) -> Vec<Vec<Corner>> {
+        (1..16usize)
+            .filter(|&set| {
+                !self.together[set]
+                    && (0..4)
+                        .filter(|bit| set & (1 << bit) != 0)
+                        .all(|bit| self.together[set & !(1 << bit)])
+            })
+            .map(|set| {
+                (0..4)
+                    .filter(|bit| set & (1 << bit) != 0)
+                    .map(|bit| CORNERS[bit])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A bitmask of corners in goal order.
+fn mask(corners: &[Corner]) -> usize {
+    corners.iter().fold(0, |mask, corner| {
+        mask | 1 << CORNERS.iter().position(|c| c == corner).unwrap()
+    })
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Searches every grid reachable from the start, for explaining why a puzzle has no
+    /// solution. Puzzles with a solution are diagnosed too, with every corner achievable
+    /// together.
+    pub fn diagnose(&self) -> Diagnosis<ROWS, COLS> {
+        self.diagnose_with(&SolveOptions::default())
+            .expect("an unlimited search always finishes")
+    }
+
+    /// [`diagnose`](Self::diagnose) within the given limits.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(level = "debug"))
+    )]
+    pub fn diagnose_with(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<Diagnosis<ROWS, COLS>, SolveError> {
+        let tiles = Grid::<ROWS, COLS>::corner_tiles();
+        let matched = |grid: &Grid<ROWS, COLS>| -> [bool; 4] {
+            std::array::from_fn(|i| *grid.get(tiles[i].0, tiles[i].1) == self.goals[i])
+        };
+
+        let mut limits = Limits::start(options);
+        // Each grid found, with the grid and press it was first reached from
+        let mut grids = vec![(self.original.clone(), None)];
+        let mut index: HashMap<Grid<ROWS, COLS>, usize, BuildGridHasher> = Default::default();
+        index.insert(self.original.clone(), 0);
+        let mut together = [false; 16];
+        let mut closest = (0, 0);
+
+        let mut next = 0;
+        while next < grids.len() {
+            limits.visit(grids.len())?;
+            let grid = grids[next].0.clone();
+            let matched = matched(&grid);
+            let set: usize = (0..4).filter(|&i| matched[i]).map(|i| 1 << i).sum();
+            together[set] = true;
+            if set.count_ones() > closest.1 {
+                closest = (next, set.count_ones());
+            }
+
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    let pressed = grid.press_with(&self.rules, row, col);
+                    index.entry(pressed).or_insert_with_key(|pressed| {
+                        grids.push((pressed.clone(), Some((next, (row, col)))));
+                        grids.len() - 1
+                    });
+                }
+            }
+            next += 1;
+        }
+
+        // Every subset of corners matched together is matched together too
+        for set in (0..16).rev() {
+            if together[set] {
+                for bit in 0..4 {
+                    together[set & !(1 << bit)] = true;
+                }
+            }
+        }
+
+        let mut presses = vec![];
+        let mut state = closest.0;
+        while let Some((previous, press)) = grids[state].1 {
+            presses.push(press);
+            state = previous;
+        }
+        presses.reverse();
+        let visited = grids.len();
+        let closest = grids.swap_remove(closest.0).0;
+        Ok(Diagnosis {
+            visited,
+            matched: matched(&closest),
+            closest,
+            presses,
+            together,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn unreachable_goals_are_named() {
+        // White tiles can match three corners at once, but nothing turns a tile black
+        let puzzle = Puzzle::new(
+            [Color::White, Color::White, Color::White, Color::Black],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        assert!(puzzle.solve().is_none());
+
+        let diagnosis = puzzle.diagnose();
+        assert_eq!(diagnosis.never_together(), [vec![Corner::SE]]);
+        assert_eq!(diagnosis.matched, [true, true, true, false]);
+        let mut reached = puzzle.original().clone();
+        for &(row, col) in &diagnosis.presses {
+            reached = reached.press(row, col);
+        }
+        assert_eq!(reached, diagnosis.closest);
+        assert!(diagnosis.achievable(&[Corner::NW, Corner::NE, Corner::SW]));
+        assert!(diagnosis.visited > 1);
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Grid, Puzzle};
+
+/// How hard a puzzle is, judged by the length of its optimal solution. See
+/// [`DifficultyReport`] for a closer judgement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Solvable in at most 4 presses
+    Easy,
+    /// Needs 5 to 8 presses
+    Medium,
+    /// Needs 9 or more presses
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn from_solution_length(len: usize) -> Self {
+        match len {
+            0..=4 => Difficulty::Easy,
+            5..=8 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    /// The inclusive range of optimal solution lengths for this difficulty. Hard puzzles
+    /// have no upper bound.
+    pub fn solution_lengths(&self) -> (usize, Option<usize>) {
+        match self {
+            Difficulty::Easy => (1, Some(4)),
+            Difficulty::Medium => (5, Some(8)),
+            Difficulty::Hard => (9, None),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.name() == s)
+            .ok_or_else(|| format!("unknown difficulty {s:?}"))
+    }
+}
+
+/// How much each unit of a report's measures adds to its human score, on top of one
+/// point per press.
+const OBSCURITY_WEIGHT: f64 = 1.0;
+const LOOKAHEAD_WEIGHT: f64 = 0.5;
+const DISTURBANCE_WEIGHT: f64 = 1.0;
+
+/// What makes a solution hard for people to find, beyond its length. Two puzzles of six
+/// presses can be far apart: rotating pink neighbours is much harder to plan than
+/// shuffling a black row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyReport {
+    /// The number of presses in the solution
+    pub solution_length: usize,
+    /// The average [obscurity](crate::TileRule::obscurity) of the rules pressed, from 0
+    /// to 1
+    pub rule_obscurity: f64,
+    /// How many presses must be planned before any corner tile shows its goal color,
+    /// with nothing to show for them along the way
+    pub lookahead: usize,
+    /// How many times a press moves a corner tile off its goal color, which unlocks its
+    /// corner for players who locked it as soon as it matched
+    pub corner_disturbances: usize,
+}
+
+impl DifficultyReport {
+    /// A rough estimate of how hard people find the puzzle, in the same units as the
+    /// solution's length: one point per press, raised for obscure rules, long stretches
+    /// without a matching corner and corners undone along the way.
+    pub fn human_score(&self) -> f64 {
+        self.solution_length as f64 * (1.0 + OBSCURITY_WEIGHT * self.rule_obscurity)
+            + LOOKAHEAD_WEIGHT * self.lookahead as f64
+            + DISTURBANCE_WEIGHT * self.corner_disturbances as f64
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Reports on how hard the given solution is to find, pressing it from the original
+    /// grid.
+    pub fn difficulty_report_for(&self, solution: &[(usize, usize)]) -> DifficultyReport {
+        let trace = self.trace(solution);
+        let obscurity: f64 = trace
+            .iter()
+            .map(|press| {
+                self.rules
+                    .get(press.color)
+                    .map_or(0.0, |rule| rule.obscurity())
+            })
+            .sum();
+
+        let grids: Vec<&Grid<ROWS, COLS>> = std::iter::once(&self.original)
+            .chain(trace.iter().map(|press| &press.result))
+            .collect();
+        let matching = |grid: &Grid<ROWS, COLS>| {
+            Grid::<ROWS, COLS>::corner_tiles()
+                .map(|(row, col)| *grid.get(row, col))
+                .into_iter()
+                .zip(self.goals)
+                .map(|(tile, goal)| tile == goal)
+        };
+        let lookahead = grids
+            .iter()
+            .position(|grid| matching(grid).any(|matches| matches))
+            .unwrap_or(solution.len());
+        let corner_disturbances = grids
+            .windows(2)
+            .map(|pair| {
+                matching(pair[0])
+                    .zip(matching(pair[1]))
+                    .filter(|&(before, after)| before && !after)
+                    .count()
+            })
+            .sum();
+
+        DifficultyReport {
+            solution_length: solution.len(),
+            rule_obscurity: if solution.is_empty() {
+                0.0
+            } else {
+                obscurity / solution.len() as f64
+            },
+            lookahead,
+            corner_disturbances,
+        }
+    }
+
+    /// Reports on how hard the puzzle's optimal solution is to find, or `None` if it has
+    /// no solution.
+    #[cfg(feature = "solver")]
+    pub fn difficulty_report(&self) -> Option<DifficultyReport> {
+        Some(self.difficulty_report_for(&self.solve()?.presses))
+    }
+}
+
+/// Difficulties are serialized by name.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Difficulty {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Difficulty {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn reports_weigh_rules_and_corners() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let puzzle = Puzzle::new([Color::White; 4], grid);
+        let report = puzzle.difficulty_report_for(&[(0, 2), (0, 1)]);
+        assert_eq!(report.solution_length, 2);
+        assert_eq!(report.rule_obscurity, 0.4);
+        // The top corners match from the start
+        assert_eq!(report.lookahead, 0);
+        // The first press turns the bottom right tile gray
+        assert_eq!(report.corner_disturbances, 1);
+        assert!(report.human_score() > 2.0);
+
+        let obscure = DifficultyReport {
+            rule_obscurity: 0.9,
+            ..report
+        };
+        assert!(obscure.human_score() > report.human_score());
+    }
+}
@@ -0,0 +1,332 @@
+//! Solving every puzzle drawn from a few colors, for settling what those colors can do,
+//! such as how many of their puzzles have a solution at all and how long the longest
+//! takes.
+//!
+//! Rather than searching from each puzzle, every grid reachable from any of them is
+//! found once, and then for each set of goals a single search backwards from the solved
+//! grids gives the fewest presses from every grid at once.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::puzzle::BuildGridHasher;
+use crate::{Color, Grid, Puzzle, RuleSet};
+
+/// Marks grids no press leads to a solution from.
+pub(crate) const UNSOLVED: u32 = u32::MAX;
+
+/// What solving every puzzle of some colors found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Census<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The number of puzzles, one for each grid of the colors with each set of goals
+    /// other than gray
+    pub puzzles: u64,
+    /// How many puzzles have an optimal solution of each number of presses, indexed by
+    /// the number of presses
+    pub lengths: Vec<u64>,
+    /// A puzzle whose optimal solution takes the most presses of any, if any puzzle has a
+    /// solution
+    pub deepest: Option<Puzzle<ROWS, COLS>>,
+}
+
+impl<const ROWS: usize, const COLS: usize> Census<ROWS, COLS> {
+    /// The number of puzzles with a solution.
+    pub fn solvable(&self) -> u64 {
+        self.lengths.iter().sum()
+    }
+
+    /// The fraction of puzzles with a solution, from 0 to 1.
+    pub fn solvable_fraction(&self) -> f64 {
+        if self.puzzles == 0 {
+            return 0.0;
+        }
+        self.solvable() as f64 / self.puzzles as f64
+    }
+
+    /// The most presses any puzzle's optimal solution takes, if any puzzle has a solution.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.lengths.len().checked_sub(1)
+    }
+}
+
+/// Every grid reachable from some starting grids, with the presses between them.
+pub(crate) struct PressGraph<const ROWS: usize, const COLS: usize> {
+    /// The starting grids first, in the order given, and then the grids only reachable
+    /// from them
+    pub(crate) grids: Vec<Grid<ROWS, COLS>>,
+    /// The grid each press leads to, `ROWS * COLS` entries per grid in row then column
+    /// order, for simulating play
+    #[cfg_attr(not(feature = "rand"), expect(dead_code))]
+    pub(crate) targets: Vec<u32>,
+    /// For each grid, where its entries of `sources` start
+    offsets: Vec<usize>,
+    /// The grids with a press leading to each grid
+    sources: Vec<u32>,
+}
+
+impl<const ROWS: usize, const COLS: usize> PressGraph<ROWS, COLS> {
+    /// The graph of every grid whose tiles are colors of `palette`.
+    pub(crate) fn of_colors(palette: &[Color], rules: &RuleSet<ROWS, COLS>) -> Self {
+        let tiles = (ROWS * COLS) as u32;
+        // Room is left for the grids only reachable by pressing
+        let starts = (palette.len() as u64)
+            .checked_pow(tiles)
+            .filter(|&starts| starts < u64::from(u32::MAX / 4))
+            .unwrap_or_else(|| panic!("too many grids of {} colors to enumerate", palette.len()));
+
+        let grids = (0..starts as usize).map(|mut digits| {
+            Grid::from_fn(|_, _| {
+                let color = palette[digits % palette.len()];
+                digits /= palette.len();
+                color
+            })
+        });
+        Self::build(grids, rules)
+    }
+
+    /// The graph of every grid reachable from `starts`, which must all differ.
+    pub(crate) fn build(
+        starts: impl IntoIterator<Item = Grid<ROWS, COLS>>,
+        rules: &RuleSet<ROWS, COLS>,
+    ) -> Self {
+        let mut grids = vec![];
+        let mut index: HashMap<Grid<ROWS, COLS>, u32, BuildGridHasher> = Default::default();
+        for grid in starts {
+            index.insert(grid.clone(), grids.len() as u32);
+            grids.push(grid);
+        }
+
+        // Pressing can make colors the starts lack, such as white tiles turning gray
+        let mut targets = Vec::with_capacity(grids.len() * ROWS * COLS);
+        let mut next = 0;
+        while next < grids.len() {
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    let pressed = grids[next].press_with(rules, row, col);
+                    let target = *index.entry(pressed).or_insert_with_key(|pressed| {
+                        grids.push(pressed.clone());
+                        grids.len() as u32 - 1
+                    });
+                    targets.push(target);
+                }
+            }
+            next += 1;
+        }
+
+        // Turn the presses around, so searches can run backwards from solved grids
+        let mut offsets = vec![0; grids.len() + 1];
+        for &target in &targets {
+            offsets[target as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut filled = offsets.clone();
+        let mut sources = vec![0; targets.len()];
+        for (press, &target) in targets.iter().enumerate() {
+            sources[filled[target as usize]] = (press / (ROWS * COLS)) as u32;
+            filled[target as usize] += 1;
+        }
+        Self {
+            grids,
+            targets,
+            offsets,
+            sources,
+        }
+    }
+
+    /// The fewest presses from each grid to one solved for `goals`, or [`UNSOLVED`].
+    pub(crate) fn distances(&self, goals: &[Color; 4]) -> Vec<u32> {
+        let mut distances = vec![UNSOLVED; self.grids.len()];
+        let mut queue = vec![];
+        for (i, grid) in self.grids.iter().enumerate() {
+            if grid.is_solved(goals) {
+                distances[i] = 0;
+                queue.push(i as u32);
+            }
+        }
+        let mut next = 0;
+        while next < queue.len() {
+            let grid = queue[next] as usize;
+            for &source in &self.sources[self.offsets[grid]..self.offsets[grid + 1]] {
+                if distances[source as usize] == UNSOLVED {
+                    distances[source as usize] = distances[grid] + 1;
+                    queue.push(source);
+                }
+            }
+            next += 1;
+        }
+        distances
+    }
+}
+
+/// The tally of one thread's goals, with the deepest puzzle as its optimal length and
+/// the indices of its goals and grid.
+#[derive(Default)]
+struct Tally {
+    lengths: Vec<u64>,
+    deepest: Option<(u32, usize, usize)>,
+}
+
+impl Tally {
+    fn add(&mut self, length: u32, goals: usize, grid: usize) {
+        let length_index = length as usize;
+        if self.lengths.len() <= length_index {
+            self.lengths.resize(length_index + 1, 0);
+        }
+        self.lengths[length_index] += 1;
+        self.keep_deepest((length, goals, grid));
+    }
+
+    /// Keeps the deeper puzzle, or the one enumerated first when they are as deep, so
+    /// the result does not depend on how goals were split between threads.
+    fn keep_deepest(&mut self, candidate: (u32, usize, usize)) {
+        let deeper = |(length, goals, grid): (u32, usize, usize),
+                      (best, best_goals, best_grid): (u32, usize, usize)| {
+            length > best || (length == best && (goals, grid) < (best_goals, best_grid))
+        };
+        if self.deepest.is_none_or(|best| deeper(candidate, best)) {
+            self.deepest = Some(candidate);
+        }
+    }
+
+    fn merge(&mut self, other: Tally) {
+        if self.lengths.len() < other.lengths.len() {
+            self.lengths.resize(other.lengths.len(), 0);
+        }
+        for (total, count) in self.lengths.iter_mut().zip(other.lengths) {
+            *total += count;
+        }
+        if let Some(deepest) = other.deepest {
+            self.keep_deepest(deepest);
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Solves every puzzle whose tiles are colors of `rules`, with every set of goals of
+    /// those colors other than gray, sharing the sets of goals between `jobs` threads.
+    ///
+    /// There are `colors ^ (ROWS * COLS)` grids for each set of goals, so this is only
+    /// practical for two to four colors; the rules can be narrowed to those first with
+    /// [`RuleSet::retain_colors`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `jobs` is 0, if the rules have no colors other than gray, or if there
+    /// are too many grids to count.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(rules)))]
+    pub fn enumerate(rules: &RuleSet<ROWS, COLS>, jobs: usize) -> Census<ROWS, COLS> {
+        assert!(jobs > 0, "enumerating needs at least one thread");
+        let palette: Vec<Color> = rules.colors().collect();
+        let goal_colors: Vec<Color> = palette
+            .iter()
+            .copied()
+            .filter(|&color| color != Color::Gray)
+            .collect();
+        assert!(
+            !goal_colors.is_empty(),
+            "rule set has no colors to enumerate goals from"
+        );
+
+        let graph = PressGraph::of_colors(&palette, rules);
+        let starts = palette.len().pow((ROWS * COLS) as u32);
+        let goal_sets = goal_colors.len().pow(4);
+        let goals = |mut digits: usize| -> [Color; 4] {
+            std::array::from_fn(|_| {
+                let color = goal_colors[digits % goal_colors.len()];
+                digits /= goal_colors.len();
+                color
+            })
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            grids = graph.grids.len(),
+            starts,
+            goal_sets,
+            "built press graph"
+        );
+
+        let next = AtomicUsize::new(0);
+        let tally = thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs.min(goal_sets))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut tally = Tally::default();
+                        loop {
+                            let goal_set = next.fetch_add(1, Ordering::Relaxed);
+                            if goal_set >= goal_sets {
+                                return tally;
+                            }
+                            let distances = graph.distances(&goals(goal_set));
+                            for (grid, &distance) in distances[..starts].iter().enumerate() {
+                                if distance != UNSOLVED {
+                                    tally.add(distance, goal_set, grid);
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .fold(Tally::default(), |mut tally, worker| {
+                    tally.merge(worker.join().expect("enumerating thread panicked"));
+                    tally
+                })
+        });
+
+        Census {
+            puzzles: (starts * goal_sets) as u64,
+            lengths: tally.lengths,
+            deepest: tally.deepest.map(|(_, goal_set, grid)| {
+                Puzzle::with_rules(goals(goal_set), graph.grids[grid].clone(), rules.clone())
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solution_length;
+
+    #[test]
+    fn every_puzzle_is_counted_at_its_optimal_length() {
+        let mut rules: RuleSet = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White));
+
+        let census = Puzzle::enumerate(&rules, 2);
+        assert_eq!(census.puzzles, 512);
+
+        let mut lengths = vec![];
+        for digits in 0..512usize {
+            let grid = Grid::from_fn(|row, col| {
+                if digits & (1 << (3 * row + col)) == 0 {
+                    Color::Gray
+                } else {
+                    Color::White
+                }
+            });
+            if let Some(length) = solution_length(&[Color::White; 4], &grid, &rules) {
+                if lengths.len() <= length {
+                    lengths.resize(length + 1, 0);
+                }
+                lengths[length] += 1;
+            }
+        }
+        assert_eq!(census.lengths, lengths);
+        assert_eq!(
+            census
+                .deepest
+                .as_ref()
+                .unwrap()
+                .solve()
+                .unwrap()
+                .presses
+                .len(),
+            census.max_depth().unwrap()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::ops::RangeInclusive;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::{
+    Color, Difficulty, Grid, Puzzle, RuleSet,
+    solver::{needs_color, solution_length},
+};
+
+/// Samples uniformly from the builtin colors.
+impl Distribution<Color> for StandardUniform {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Color {
+        *Color::BUILTIN
+            .choose(rng)
+            .expect("there are builtin colors")
+    }
+}
+
+/// Puzzles generated across threads by [`Puzzle::new_random_parallel`], in the order
+/// they are found. The iterator never ends, so take as many as are needed.
+pub struct Generated<const ROWS: usize = 3, const COLS: usize = 3> {
+    receiver: Receiver<Puzzle<ROWS, COLS>>,
+}
+
+impl<const ROWS: usize, const COLS: usize> Iterator for Generated<ROWS, COLS> {
+    type Item = Puzzle<ROWS, COLS>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    pub fn new_random() -> Self {
+        Self::new_random_with_rules(RuleSet::standard())
+    }
+
+    /// Generates a random solvable puzzle whose tiles follow the given rules.
+    ///
+    /// Tiles are drawn from the colors which have a rule in the rule set, so custom colors
+    /// appear once they are given a rule.
+    pub fn new_random_with_rules(rules: RuleSet<ROWS, COLS>) -> Self {
+        Self::new_random_with_rng(rules, &mut rand::rng())
+    }
+
+    /// Generates the same random solvable puzzle for each seed, given the same rules.
+    pub fn new_seeded(rules: RuleSet<ROWS, COLS>, seed: u64) -> Self {
+        Self::new_random_with_rng(rules, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Generates a random puzzle of the given difficulty.
+    pub fn new_random_with_difficulty(rules: RuleSet<ROWS, COLS>, difficulty: Difficulty) -> Self {
+        let (min, max) = difficulty.solution_lengths();
+        Self::new_random_with_solution_length(rules, min..=max.unwrap_or(usize::MAX))
+    }
+
+    /// Generates a random puzzle whose optimal solution takes a number of presses in the
+    /// given range. Ranges which few puzzles fall in, such as very long solutions, can
+    /// take a long time to generate.
+    pub fn new_random_with_solution_length(
+        rules: RuleSet<ROWS, COLS>,
+        lengths: RangeInclusive<usize>,
+    ) -> Self {
+        Self::generate(
+            rules,
+            &mut rand::rng(),
+            |len| lengths.contains(&len),
+            &[],
+            None,
+        )
+    }
+
+    /// Generates a random puzzle whose optimal solution takes a number of presses in the
+    /// given range and presses at least one tile of each color in `must_include`, however
+    /// the solution is chosen. A puzzle drilling pink rotations cannot then be solved
+    /// while ignoring its pink tiles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a color in `must_include` has no rule, as its tiles would never appear.
+    /// Colors whose presses never help, such as gray under the standard rules, are never
+    /// needed and generate forever.
+    pub fn new_random_including(
+        rules: RuleSet<ROWS, COLS>,
+        lengths: RangeInclusive<usize>,
+        must_include: &[Color],
+    ) -> Self {
+        Self::generate(
+            rules,
+            &mut rand::rng(),
+            |len| lengths.contains(&len),
+            must_include,
+            None,
+        )
+    }
+
+    /// Generates puzzles whose optimal solution takes a number of presses in the given
+    /// range on `jobs` threads, yielding each as soon as it is found. The threads stop
+    /// once the iterator is dropped and each has finished the puzzle it was working on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `jobs` is 0.
+    pub fn new_random_parallel(
+        rules: RuleSet<ROWS, COLS>,
+        lengths: RangeInclusive<usize>,
+        jobs: usize,
+    ) -> Generated<ROWS, COLS> {
+        assert!(jobs > 0, "generating needs at least one thread");
+        // A little room in the channel keeps threads busy while results are handled, without
+        // generating far ahead of what is taken
+        let (sender, receiver) = mpsc::sync_channel(jobs);
+        for _ in 0..jobs {
+            let (rules, lengths, sender) = (rules.clone(), lengths.clone(), sender.clone());
+            thread::spawn(move || {
+                let mut rng = rand::rng();
+                loop {
+                    let puzzle = Self::generate(
+                        rules.clone(),
+                        &mut rng,
+                        |len| lengths.contains(&len),
+                        &[],
+                        None,
+                    );
+                    if sender.send(puzzle).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        Generated { receiver }
+    }
+
+    fn new_random_with_rng(rules: RuleSet<ROWS, COLS>, rng: &mut impl Rng) -> Self {
+        Self::generate(rules, rng, |_| true, &[], None)
+    }
+
+    /// Generates random puzzles until one is solvable with an accepted solution length,
+    /// and whose optimal solutions all press each color in `must_include`. Goals are
+    /// random unless given.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub(crate) fn generate(
+        rules: RuleSet<ROWS, COLS>,
+        rng: &mut impl Rng,
+        accept: impl Fn(usize) -> bool,
+        must_include: &[Color],
+        goals: Option<[Color; 4]>,
+    ) -> Self {
+        let palette: Vec<Color> = rules.colors().collect();
+        assert!(
+            !palette.is_empty(),
+            "rule set has no colors to generate from"
+        );
+        if let Some(color) = must_include.iter().find(|color| !palette.contains(color)) {
+            panic!("{} tiles have no rule to generate them", color.name());
+        }
+
+        #[cfg(feature = "tracing")]
+        let mut attempts = 0u64;
+
+        // Randomly generate puzzles until we find one with a solution
+        loop {
+            #[cfg(feature = "tracing")]
+            {
+                attempts += 1;
+            }
+            let goals: [Color; 4] =
+                goals.unwrap_or_else(|| std::array::from_fn(|_| *palette.choose(rng).unwrap()));
+            // Goal cannot be gray - the puzzle would start in a solved state
+            if goals.contains(&Color::Gray) {
+                continue;
+            }
+
+            let grid = Grid::from_fn(|_, _| *palette.choose(rng).unwrap());
+
+            let presses = solution_length(&goals, &grid, &rules);
+            #[cfg(feature = "tracing")]
+            tracing::trace!(presses, "generated candidate");
+            let Some(presses) = presses.filter(|&presses| accept(presses)) else {
+                continue;
+            };
+            if must_include
+                .iter()
+                .all(|&color| needs_color(&goals, &grid, &rules, color, presses))
+            {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(attempts, "accepted puzzle");
+                return Self::with_rules(goals, grid, rules);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Standard rules for a few colors, whose small search spaces keep generation quick
+    /// in debug builds.
+    fn few_colors() -> RuleSet {
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| {
+            matches!(
+                color,
+                Color::Gray | Color::White | Color::Black | Color::Yellow
+            )
+        });
+        rules
+    }
+
+    #[test]
+    fn needs_color_checks_every_short_enough_solution() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let (goals, rules) = ([Color::White; 4], RuleSet::standard());
+        assert!(needs_color(&goals, &grid, &rules, Color::White, 2));
+        assert!(!needs_color(&goals, &grid, &rules, Color::Black, 2));
+        // Presses of any color can be avoided by a solution too short to exist
+        assert!(needs_color(&goals, &grid, &rules, Color::Black, 1));
+    }
+
+    #[test]
+    fn parallel_puzzles_have_accepted_lengths() {
+        let puzzles: Vec<Puzzle> = Puzzle::new_random_parallel(few_colors(), 2..=3, 3)
+            .take(6)
+            .collect();
+        assert_eq!(puzzles.len(), 6);
+        for puzzle in puzzles {
+            let presses = puzzle.solve().unwrap().presses.len();
+            assert!((2..=3).contains(&presses), "{presses} presses");
+        }
+    }
+
+    #[test]
+    fn included_colors_are_pressed() {
+        let rules = few_colors();
+        for _ in 0..5 {
+            let puzzle = Puzzle::new_random_including(rules.clone(), 2..=4, &[Color::Yellow]);
+            let solution = puzzle.solve().unwrap();
+            let pressed: Vec<Color> = puzzle
+                .original()
+                .trace_with(&rules, &solution.presses)
+                .iter()
+                .map(|press| press.color)
+                .collect();
+            assert!(pressed.contains(&Color::Yellow), "{pressed:?}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "solver")]
+mod alternatives;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+#[cfg(feature = "solver")]
+mod cache;
+#[cfg(feature = "rand")]
+mod campaign;
+mod color;
+#[cfg(feature = "rand")]
+mod design;
+#[cfg(feature = "solver")]
+mod diagnose;
+mod difficulty;
+#[cfg(feature = "solver")]
+mod enumerate;
+#[cfg(feature = "rand")]
+mod generate;
+mod puzzle;
+pub mod rules;
+mod share;
+#[cfg(feature = "rand")]
+mod simulate;
+#[cfg(feature = "solver")]
+mod solver;
+#[cfg(feature = "solver")]
+mod state;
+#[cfg(feature = "solver")]
+mod tablebase;
+mod unknown;
+
+#[cfg(feature = "solver")]
+pub use alternatives::PressCounts;
+#[cfg(feature = "solver")]
+pub use cache::DistanceCache;
+#[cfg(feature = "rand")]
+pub use campaign::ChainLink;
+pub use color::{Color, ColorLabels, RegisterColorError};
+#[cfg(feature = "rand")]
+pub use design::Design;
+#[cfg(feature = "solver")]
+pub use diagnose::Diagnosis;
+pub use difficulty::{Difficulty, DifficultyReport};
+#[cfg(feature = "solver")]
+pub use enumerate::Census;
+#[cfg(feature = "rand")]
+pub use generate::Generated;
+pub use puzzle::{
+    Corner, CornerPress, Grid, Move, PressPreview, Puzzle, RuleUse, TileChange, Transform,
+    WinCheck, check,
+};
+pub use rules::{RuleSet, TileRule};
+pub use share::{SHARE_CODE_LEN, ShareCodeError};
+#[cfg(feature = "rand")]
+pub use simulate::{Policy, Simulation};
+#[cfg(feature = "solver")]
+pub use solver::{
+    BOUNDED_MAX_PRESSES, SearchProgress, SearchStats, Solution, SolveError, SolveOptions, TieBreak,
+    state_bytes,
+};
+#[cfg(feature = "solver")]
+pub use state::{Found, GridGoals, PuzzleState, search};
+#[cfg(feature = "solver")]
+pub use tablebase::{Tablebase, TablebaseError};
+pub use unknown::PartialPuzzle;
+#[cfg(feature = "solver")]
+pub use unknown::PartialSolution;
@@ -0,0 +1,953 @@
+pub mod check;
+mod transform;
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::color::Color;
+use crate::rules::RuleSet;
+
+pub use transform::Transform;
+
+/// A Mora Jai puzzle's grid.
+///
+/// The row, column pairs of each tile in the standard 3x3 grid are as follows.
+/// -------------------
+/// | 2,0 | 2,1 | 2,2 |
+/// | 1,0 | 1,1 | 1,2 |
+/// | 0,0 | 0,1 | 0,2 |
+/// -------------------
+///
+/// Larger boards follow the same layout, with row 0 at the bottom and column 0 on the
+/// left.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Grid<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// Tiles indexed by row, then column
+    colors: [[Color; COLS]; ROWS],
+    /// The Zobrist hash of the tiles: the XOR of [`zobrist_key`] over every tile, kept
+    /// up to date as tiles are set so that hashing a grid costs nothing
+    hash: u64,
+}
+
+/// A pseudo-random key for a color at a tile, given by its index counting row by row.
+///
+/// Keys are mixed from the index and color by SplitMix64's finalizer rather than kept in
+/// a table, as grids of any size and any number of registered colors need them.
+fn zobrist_key(index: usize, color: Color) -> u64 {
+    let mut z = ((index as u64) << 32 | color.ordinal()).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl<const ROWS: usize, const COLS: usize> fmt::Debug for Grid<ROWS, COLS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grid")
+            .field("colors", &self.colors)
+            .finish()
+    }
+}
+
+/// Grids hash as their Zobrist hash alone, which [`GridHasher`] passes through
+/// unchanged.
+impl<const ROWS: usize, const COLS: usize> Hash for Grid<ROWS, COLS> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// A hasher for sets and maps of grids, which uses a grid's Zobrist hash as is instead of
+/// hashing it again.
+#[cfg(feature = "solver")]
+#[derive(Default)]
+pub(crate) struct GridHasher(u64);
+
+#[cfg(feature = "solver")]
+impl Hasher for GridHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // Only grids are hashed, through `write_u64`, but anything else still hashes
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 ^= n;
+    }
+}
+
+/// Builds [`GridHasher`]s for the solver's sets and maps of grids.
+#[cfg(feature = "solver")]
+pub(crate) type BuildGridHasher = std::hash::BuildHasherDefault<GridHasher>;
+
+impl Grid {
+    /// Builds a 3x3 grid from its tiles, listed row by row starting from the bottom.
+    pub fn new(colors: impl Into<[Color; 9]>) -> Self {
+        let colors = colors.into();
+        Self::from_fn(|row, col| colors[row * 3 + col])
+    }
+
+    /// Convenience function to build Mora Jai puzzle grids
+    pub fn from_rows(r2: [Color; 3], r1: [Color; 3], r0: [Color; 3]) -> Self {
+        Self::from_row_array([r2, r1, r0])
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Grid<ROWS, COLS> {
+    /// Builds a grid from its rows, listed top to bottom as they appear on the box.
+    pub fn from_row_array(rows: [[Color; COLS]; ROWS]) -> Self {
+        Self::from_fn(|row, col| rows[ROWS - 1 - row][col])
+    }
+
+    /// Builds a grid by calling `f` with the row and column of each tile.
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> Color) -> Self {
+        const { assert!(ROWS > 0 && COLS > 0, "grids must have at least one tile") };
+
+        let colors: [[Color; COLS]; ROWS] =
+            std::array::from_fn(|row| std::array::from_fn(|col| f(row, col)));
+        let hash = colors
+            .iter()
+            .flatten()
+            .enumerate()
+            .fold(0, |hash, (index, &color)| hash ^ zobrist_key(index, color));
+        Self { colors, hash }
+    }
+
+    pub const fn rows(&self) -> usize {
+        ROWS
+    }
+
+    pub const fn cols(&self) -> usize {
+        COLS
+    }
+
+    /// The coordinates of the corner tiles, in goal order (NW, NE, SW, SE).
+    pub const fn corner_tiles() -> [(usize, usize); 4] {
+        [(ROWS - 1, 0), (ROWS - 1, COLS - 1), (0, 0), (0, COLS - 1)]
+    }
+
+    pub fn is_solved(&self, goals: &[Color; 4]) -> bool {
+        Self::corner_tiles()
+            .into_iter()
+            .zip(goals)
+            .all(|((row, col), goal)| self.get(row, col) == goal)
+    }
+
+    fn valid_coord(row: usize, col: usize) -> bool {
+        row < ROWS && col < COLS
+    }
+
+    /// Returns the color at the given row and column.
+    /// Rows must be less than `ROWS` and columns less than `COLS`.
+    /// Panics if the row or column is invalid.
+    pub fn get(&self, row: usize, col: usize) -> &Color {
+        if !Self::valid_coord(row, col) {
+            panic!("invalid row or column");
+        }
+
+        &self.colors[row][col]
+    }
+
+    /// Sets the color at the given row and column.
+    /// Panics if the row or column is invalid.
+    pub fn set(&mut self, row: usize, col: usize, color: Color) {
+        if !Self::valid_coord(row, col) {
+            panic!("invalid row or column");
+        }
+
+        let index = row * COLS + col;
+        self.hash ^= zobrist_key(index, self.colors[row][col]) ^ zobrist_key(index, color);
+        self.colors[row][col] = color;
+    }
+
+    pub(crate) fn neighbours_clockwise(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        if !Self::valid_coord(row, col) {
+            panic!("invalid row or column");
+        }
+
+        let mut neighbours: Vec<(usize, usize)> = Vec::with_capacity(8);
+        let offsets: [(isize, isize); 8] = [
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ];
+
+        for (dr, dc) in offsets {
+            let row = row.checked_add_signed(dr).unwrap_or(usize::MAX);
+            let col = col.checked_add_signed(dc).unwrap_or(usize::MAX);
+
+            if Self::valid_coord(row, col) {
+                neighbours.push((row, col));
+            }
+        }
+
+        neighbours
+    }
+
+    /// Press a tile on this puzzle using the standard rules. The resulting puzzle is
+    /// returned.
+    pub fn press(&self, row: usize, col: usize) -> Self {
+        self.press_with(RuleSet::shared_standard(), row, col)
+    }
+
+    /// Press a tile on this puzzle using the given rules. The resulting puzzle is
+    /// returned.
+    pub fn press_with(&self, rules: &RuleSet<ROWS, COLS>, row: usize, col: usize) -> Self {
+        let color = self.get(row, col);
+        rules.apply(*color, self, row, col)
+    }
+
+    /// Describes what pressing a tile would do under the standard rules, without
+    /// pressing it.
+    pub fn preview_press(&self, row: usize, col: usize) -> PressPreview<ROWS, COLS> {
+        self.preview_press_with(RuleSet::shared_standard(), row, col)
+    }
+
+    /// Describes what pressing a tile would do under the given rules, without pressing
+    /// it.
+    pub fn preview_press_with(
+        &self,
+        rules: &RuleSet<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> PressPreview<ROWS, COLS> {
+        let color = *self.get(row, col);
+        let result = self.press_with(rules, row, col);
+        let rule = rules.get(color);
+
+        PressPreview {
+            row,
+            col,
+            color,
+            rule: rule.map(|rule| rule.describe()),
+            acting_as: rule.and_then(|rule| rule.acts_as(self, row, col)),
+            changes: self.diff(&result),
+            result,
+        }
+    }
+
+    /// Previews each press of a sequence in turn, starting from this grid. Each preview's
+    /// result is the grid the next press is applied to.
+    pub fn trace_with(
+        &self,
+        rules: &RuleSet<ROWS, COLS>,
+        presses: &[(usize, usize)],
+    ) -> Vec<PressPreview<ROWS, COLS>> {
+        let mut grid = self.clone();
+        presses
+            .iter()
+            .map(|&(row, col)| {
+                let preview = grid.preview_press_with(rules, row, col);
+                grid = preview.result.clone();
+                preview
+            })
+            .collect()
+    }
+
+    /// Which presses would change the grid under the given rules, indexed by row and
+    /// then column.
+    pub fn press_mask_with(&self, rules: &RuleSet<ROWS, COLS>) -> [[bool; COLS]; ROWS] {
+        std::array::from_fn(|row| {
+            std::array::from_fn(|col| self.press_with(rules, row, col) != *self)
+        })
+    }
+
+    /// The tiles which differ between this grid and another, in row then column order.
+    pub fn diff(&self, other: &Self) -> Vec<TileChange> {
+        let mut changes = vec![];
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let (from, to) = (*self.get(row, col), *other.get(row, col));
+                if from != to {
+                    changes.push(TileChange { row, col, from, to });
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// A single tile whose color changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileChange {
+    pub row: usize,
+    pub col: usize,
+    pub from: Color,
+    pub to: Color,
+}
+
+/// What pressing a tile would do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PressPreview<const ROWS: usize = 3, const COLS: usize = 3> {
+    pub row: usize,
+    pub col: usize,
+    /// The color of the pressed tile
+    pub color: Color,
+    /// A description of the rule that would fire, or `None` if the color has no rule
+    pub rule: Option<String>,
+    /// The color whose rule the press would follow in place of its own, such as the
+    /// middle tile's for a blue tile
+    pub acting_as: Option<Color>,
+    /// The tiles the press would change
+    pub changes: Vec<TileChange>,
+    /// The grid after the press
+    pub result: Grid<ROWS, COLS>,
+}
+
+impl<const ROWS: usize, const COLS: usize> PressPreview<ROWS, COLS> {
+    /// Whether the press would leave the grid unchanged.
+    pub fn is_no_op(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// How many presses of a solution followed the rule of one color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleUse {
+    /// The color of the tiles pressed
+    pub color: Color,
+    /// The color whose rule the presses followed in place of their own, if any
+    pub acting_as: Option<Color>,
+    pub presses: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    NE,
+    SE,
+    SW,
+    NW,
+}
+
+/// A press of a tile or corner during play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Tile(usize, usize),
+    Corner(Corner),
+}
+
+/// What pressing a corner did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CornerPress {
+    /// The corner's tile showed its goal color, so the corner is now locked
+    Locked,
+    /// The corner's tile did not show its goal color, so the box was reset to its
+    /// starting grid with every corner unlocked
+    Reset,
+}
+
+/// What counts as solving a puzzle.
+///
+/// The two differ part way through play: a corner tile can show its goal color without
+/// its corner being locked, and a press which changes a corner tile unlocks its corner
+/// even if a later press brings the goal color back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WinCheck {
+    /// Every corner tile of the grid shows its goal color, as [`Grid::is_solved`] checks
+    #[default]
+    GoalColors,
+    /// Every corner is locked, as [`Puzzle::is_solved`] checks
+    CornersLocked,
+}
+
+/// A move made during play with what it changed, so it can be undone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HistoryEntry<const ROWS: usize, const COLS: usize> {
+    action: Move,
+    state: Grid<ROWS, COLS>,
+    corners: [Color; 4],
+    resets: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle<const ROWS: usize = 3, const COLS: usize = 3> {
+    pub(super) goals: [Color; 4],
+    pub(super) corners: [Color; 4],
+    /// The original state of the puzzle grid, used for resets
+    pub(super) original: Grid<ROWS, COLS>,
+    /// Current state of the puzzle grid
+    state: Grid<ROWS, COLS>,
+    /// The rules applied when pressing tiles
+    pub(super) rules: RuleSet<ROWS, COLS>,
+    /// Every move made so far, oldest first, with the state before it
+    history: Vec<HistoryEntry<ROWS, COLS>>,
+    /// Resets by wrong corner presses among the moves made so far
+    resets: usize,
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    pub fn new(goals: [Color; 4], grid: Grid<ROWS, COLS>) -> Self {
+        Self::with_rules(goals, grid, RuleSet::standard())
+    }
+
+    /// Creates a puzzle whose tiles follow the given rules instead of the standard ones.
+    pub fn with_rules(
+        goals: [Color; 4],
+        grid: Grid<ROWS, COLS>,
+        rules: RuleSet<ROWS, COLS>,
+    ) -> Self {
+        Self {
+            goals,
+            corners: [const { Color::Gray }; 4],
+            original: grid.clone(),
+            state: grid,
+            rules,
+            history: vec![],
+            resets: 0,
+        }
+    }
+
+    pub fn rules(&self) -> &RuleSet<ROWS, COLS> {
+        &self.rules
+    }
+
+    pub fn current_state(&self) -> &Grid<ROWS, COLS> {
+        &self.state
+    }
+
+    /// The grid the puzzle started from, which it returns to on a reset.
+    pub fn original(&self) -> &Grid<ROWS, COLS> {
+        &self.original
+    }
+
+    /// The goal colors in NW, NE, SW, SE order.
+    pub fn goals(&self) -> &[Color; 4] {
+        &self.goals
+    }
+
+    pub fn goal(&self, corner: Corner) -> Color {
+        match corner {
+            Corner::NW => self.goals[0],
+            Corner::NE => self.goals[1],
+            Corner::SW => self.goals[2],
+            Corner::SE => self.goals[3],
+        }
+    }
+
+    pub fn get_tile(&self, row: usize, col: usize) -> Color {
+        *self.current_state().get(row, col)
+    }
+
+    pub fn get_corner(&self, corner: Corner) -> Color {
+        match corner {
+            Corner::SW => self.corners[0],
+            Corner::NW => self.corners[1],
+            Corner::SE => self.corners[2],
+            Corner::NE => self.corners[3],
+        }
+    }
+
+    fn get_corner_mut(&mut self, corner: Corner) -> &mut Color {
+        match corner {
+            Corner::SW => &mut self.corners[0],
+            Corner::NW => &mut self.corners[1],
+            Corner::SE => &mut self.corners[2],
+            Corner::NE => &mut self.corners[3],
+        }
+    }
+
+    /// The color of the tile in a corner, which pressing the corner checks against its
+    /// goal.
+    pub fn corner_tile(&self, corner: Corner) -> Color {
+        let (row, col) = Self::corner_to_tile(corner);
+        self.get_tile(row, col)
+    }
+
+    /// Whether the corner's tile shows its goal color, so that pressing the corner would
+    /// lock it rather than reset the box.
+    pub fn would_lock(&self, corner: Corner) -> bool {
+        self.corner_tile(corner) == self.goal(corner)
+    }
+
+    /// How many times a wrong corner press has reset the box. Undoing the press takes its
+    /// reset back.
+    pub fn reset_count(&self) -> usize {
+        self.resets
+    }
+
+    /// Whether every corner is locked. See [`WinCheck`] for how this differs from the
+    /// grid showing the goal colors.
+    pub fn is_solved(&self) -> bool {
+        [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+            .into_iter()
+            .all(|corner| self.get_corner(corner) == self.goal(corner))
+    }
+
+    /// Whether the puzzle is solved by the given check.
+    pub fn is_won(&self, check: WinCheck) -> bool {
+        match check {
+            WinCheck::GoalColors => self.state.is_solved(&self.goals),
+            WinCheck::CornersLocked => self.is_solved(),
+        }
+    }
+
+    /// Maps a Corner to its corresponding corner tile coordinate
+    fn corner_to_tile(corner: Corner) -> (usize, usize) {
+        let [nw, ne, sw, se] = Grid::<ROWS, COLS>::corner_tiles();
+        match corner {
+            Corner::NE => ne,
+            Corner::SE => se,
+            Corner::SW => sw,
+            Corner::NW => nw,
+        }
+    }
+
+    /// Describes what pressing a tile would do, without pressing it.
+    pub fn preview_press(&self, row: usize, col: usize) -> PressPreview<ROWS, COLS> {
+        self.state.preview_press_with(&self.rules, row, col)
+    }
+
+    /// Previews each press of a sequence in turn, starting from the original grid. This
+    /// describes a solution step by step.
+    pub fn trace(&self, presses: &[(usize, usize)]) -> Vec<PressPreview<ROWS, COLS>> {
+        self.original.trace_with(&self.rules, presses)
+    }
+
+    /// How many presses from the original grid followed each color's rule, in the order
+    /// each was first used, with a blue tile acting as another color counted apart
+    /// under that color. Presses which change nothing are left out.
+    pub fn rule_usage(&self, presses: &[(usize, usize)]) -> Vec<RuleUse> {
+        let mut usage: Vec<RuleUse> = vec![];
+        for press in self.trace(presses) {
+            if press.rule.is_none() || press.is_no_op() {
+                continue;
+            }
+            let used = (press.color, press.acting_as);
+            match usage
+                .iter_mut()
+                .find(|rule| (rule.color, rule.acting_as) == used)
+            {
+                Some(rule) => rule.presses += 1,
+                None => usage.push(RuleUse {
+                    color: press.color,
+                    acting_as: press.acting_as,
+                    presses: 1,
+                }),
+            }
+        }
+        usage
+    }
+
+    /// Which tile presses would change the current grid, indexed by row and then column.
+    pub fn press_mask(&self) -> [[bool; COLS]; ROWS] {
+        self.state.press_mask_with(&self.rules)
+    }
+
+    pub fn press_tile(&mut self, row: usize, col: usize) {
+        self.record(Move::Tile(row, col));
+        self.state = self.state.press_with(&self.rules, row, col);
+
+        // After a press, we need to reset corners which no longer match
+        for corner in [Corner::NE, Corner::SE, Corner::NW, Corner::SW] {
+            let (row, col) = Self::corner_to_tile(corner);
+            if self.get_tile(row, col) != self.get_corner(corner) {
+                *self.get_corner_mut(corner) = Color::Gray;
+            }
+        }
+    }
+
+    pub fn press_corner(&mut self, corner: Corner) {
+        self.press_corner_checked(corner);
+    }
+
+    /// Presses a corner like [`press_corner`](Self::press_corner), saying whether it
+    /// locked or reset the box.
+    pub fn press_corner_checked(&mut self, corner: Corner) -> CornerPress {
+        self.record(Move::Corner(corner));
+        if self.would_lock(corner) {
+            *self.get_corner_mut(corner) = self.goal(corner);
+            CornerPress::Locked
+        } else {
+            self.reset();
+            CornerPress::Reset
+        }
+    }
+
+    /// Makes a move, pressing a tile or corner.
+    pub fn apply(&mut self, action: Move) {
+        match action {
+            Move::Tile(row, col) => self.press_tile(row, col),
+            Move::Corner(corner) => self.press_corner(corner),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.corners = [const { Color::Gray }; 4];
+        self.state = self.original.clone();
+        self.resets += 1;
+    }
+
+    fn record(&mut self, action: Move) {
+        self.history.push(HistoryEntry {
+            action,
+            state: self.state.clone(),
+            corners: self.corners,
+            resets: self.resets,
+        });
+    }
+
+    /// The moves made so far, oldest first. Undone moves are not included.
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.history.iter().map(|entry| entry.action)
+    }
+
+    /// Takes back the last move, restoring the grid and locked corners to how they were
+    /// before it, including after a reset. Returns the move undone, or `None` if no
+    /// moves have been made.
+    pub fn undo(&mut self) -> Option<Move> {
+        let entry = self.history.pop()?;
+        self.state = entry.state;
+        self.corners = entry.corners;
+        self.resets = entry.resets;
+        Some(entry.action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_follow_presses() {
+        let grid = Grid::from_rows(
+            [Color::Pink, Color::White, Color::Black],
+            [Color::Yellow, Color::Gray, Color::Violet],
+            [Color::Blue, Color::Green, Color::Red],
+        );
+        let mut pressed = grid.clone();
+        for (row, col) in [(0, 0), (1, 1), (2, 0), (0, 2), (1, 2), (2, 2)] {
+            pressed = pressed.press(row, col);
+            let rebuilt: Grid = Grid::from_fn(|row, col| *pressed.get(row, col));
+            assert_eq!(pressed.hash, rebuilt.hash);
+        }
+        assert_ne!(pressed.hash, grid.hash);
+    }
+
+    #[test]
+    fn gray_works() {
+        let puzzle = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+
+        for row in 0..3 {
+            for col in 0..3 {
+                let new = puzzle.press(row, col);
+                assert_eq!(puzzle, new);
+            }
+        }
+    }
+
+    #[test]
+    fn white_center_works() {
+        let puzzle = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::White, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+
+        let new = puzzle.press(1, 1);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+            )
+        );
+    }
+
+    #[test]
+    fn white_corner_works() {
+        let puzzle = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::White, Color::Gray, Color::Gray],
+        );
+
+        let new = puzzle.press(0, 0);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::White, Color::Gray, Color::Gray],
+                [Color::Gray, Color::White, Color::Gray],
+            )
+        );
+    }
+
+    #[test]
+    fn black_works() {
+        let puzzle = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Black, Color::White, Color::Red],
+        );
+
+        let new = puzzle.press(0, 0);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Red, Color::Black, Color::White]
+            ),
+        );
+
+        let new = new.press(0, 1);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::White, Color::Red, Color::Black]
+            ),
+        );
+
+        let new = new.press(0, 2);
+        assert_eq!(puzzle, new);
+    }
+
+    #[test]
+    fn red_works() {
+        let puzzle = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Red, Color::Black],
+            [Color::Black, Color::Black, Color::Black],
+        );
+
+        let new = puzzle.press(1, 1);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Black, Color::Black, Color::Black],
+                [Color::Black, Color::Red, Color::Red],
+                [Color::Red, Color::Red, Color::Red],
+            ),
+        );
+    }
+
+    #[test]
+    fn blue_works() {
+        let puzzle = Grid::from_rows(
+            [Color::Blue, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Black, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+
+        let new = puzzle.press(2, 0);
+        assert_eq!(
+            new,
+            Grid::from_rows(
+                [Color::Gray, Color::Blue, Color::Gray],
+                [Color::Gray, Color::Black, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+            ),
+        );
+    }
+
+    #[test]
+    fn blue_does_not_recurse_forever() {
+        let puzzle = Grid::from_rows(
+            [Color::Blue, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Blue, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+
+        // Nothing should happen
+        let new = puzzle.press(2, 0);
+        assert_eq!(new, puzzle);
+    }
+
+    #[test]
+    fn green_works_on_larger_grid() {
+        let grid = Grid::<4, 4>::from_fn(|row, col| {
+            if (row, col) == (0, 1) {
+                Color::Green
+            } else {
+                Color::Gray
+            }
+        });
+
+        let new = grid.press(0, 1);
+        assert_eq!(new.get(3, 2), &Color::Green);
+        assert_eq!(new.get(0, 1), &Color::Gray);
+    }
+
+    #[test]
+    fn black_rotates_wide_row() {
+        let grid = Grid::<3, 5>::from_row_array([
+            [Color::Gray; 5],
+            [
+                Color::Black,
+                Color::White,
+                Color::Red,
+                Color::Pink,
+                Color::Blue,
+            ],
+            [Color::Gray; 5],
+        ]);
+
+        let new = grid.press(1, 0);
+        assert_eq!(
+            new,
+            Grid::from_row_array([
+                [Color::Gray; 5],
+                [
+                    Color::Blue,
+                    Color::Black,
+                    Color::White,
+                    Color::Red,
+                    Color::Pink
+                ],
+                [Color::Gray; 5],
+            ])
+        );
+    }
+
+    #[test]
+    fn corners_follow_grid_size() {
+        let mut puzzle = Puzzle::new([Color::Red; 4], Grid::<4, 5>::from_fn(|_, _| Color::Red));
+
+        for corner in [Corner::NW, Corner::NE, Corner::SW, Corner::SE] {
+            puzzle.press_corner(corner);
+        }
+        assert!(puzzle.is_solved());
+        assert!(puzzle.current_state().is_solved(&[Color::Red; 4]));
+    }
+
+    #[test]
+    fn preview_describes_press() {
+        let grid = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Black, Color::Red, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+
+        let preview = grid.preview_press(1, 0);
+        assert_eq!(preview.color, Color::Black);
+        assert_eq!(preview.result, grid.press(1, 0));
+        assert_eq!(
+            preview.changes,
+            vec![
+                TileChange {
+                    row: 1,
+                    col: 0,
+                    from: Color::Black,
+                    to: Color::Gray
+                },
+                TileChange {
+                    row: 1,
+                    col: 1,
+                    from: Color::Red,
+                    to: Color::Black
+                },
+                TileChange {
+                    row: 1,
+                    col: 2,
+                    from: Color::Gray,
+                    to: Color::Red
+                },
+            ]
+        );
+        assert!(!preview.is_no_op());
+
+        assert!(grid.preview_press(0, 0).is_no_op());
+        assert_eq!(
+            grid.press_mask_with(&RuleSet::standard()),
+            [[false; 3], [true, true, false], [false; 3]]
+        );
+    }
+
+    #[test]
+    fn trace_follows_presses() {
+        let puzzle = Puzzle::new(
+            [Color::Red; 4],
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Black, Color::White, Color::Red],
+            ),
+        );
+
+        let trace = puzzle.trace(&[(0, 0), (0, 1)]);
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].color, Color::Black);
+        assert_eq!(trace[1].color, Color::Black);
+        assert_eq!(trace[1].result, puzzle.original().press(0, 0).press(0, 1));
+    }
+
+    #[test]
+    fn rule_usage_counts_blue_apart() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Blue, Color::Gray, Color::Gray],
+            ),
+        );
+        // The gray corner pressed last changes nothing
+        let presses = [(0, 0), (1, 1), (2, 2)];
+        assert_eq!(puzzle.trace(&presses)[0].acting_as, Some(Color::White));
+        assert_eq!(
+            puzzle.rule_usage(&presses),
+            [
+                RuleUse {
+                    color: Color::Blue,
+                    acting_as: Some(Color::White),
+                    presses: 1
+                },
+                RuleUse {
+                    color: Color::White,
+                    acting_as: None,
+                    presses: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_restores_locks_and_resets() {
+        let mut puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        assert_eq!(puzzle.undo(), None);
+
+        puzzle.press_tile(0, 2);
+        assert_eq!(puzzle.press_corner_checked(Corner::NW), CornerPress::Locked);
+        let locked = puzzle.clone();
+
+        // A wrong corner press resets the puzzle, clearing the lock
+        assert!(!puzzle.would_lock(Corner::SW));
+        assert_eq!(puzzle.press_corner_checked(Corner::SW), CornerPress::Reset);
+        assert_eq!(puzzle.get_corner(Corner::NW), Color::Gray);
+        assert_eq!(puzzle.current_state(), puzzle.original());
+        assert_eq!(puzzle.reset_count(), 1);
+
+        assert_eq!(puzzle.undo(), Some(Move::Corner(Corner::SW)));
+        assert_eq!(puzzle, locked);
+        assert_eq!(puzzle.get_corner(Corner::NW), Color::White);
+        assert_eq!(puzzle.reset_count(), 0);
+
+        assert_eq!(
+            puzzle.moves().collect::<Vec<_>>(),
+            [Move::Tile(0, 2), Move::Corner(Corner::NW)]
+        );
+    }
+}
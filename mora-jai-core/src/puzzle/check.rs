@@ -0,0 +1,292 @@
+//! Invariants of grids, the standard rules and solutions, for property tests and
+//! fuzzing.
+//!
+//! Each check returns a [`Violation`] describing the first broken invariant it finds.
+//! Checks which press tiles panic, rather than return, if a rule reaches off the grid.
+
+use std::fmt;
+
+use crate::color::Color;
+use crate::puzzle::{Grid, Puzzle};
+use crate::rules::RuleSet;
+
+/// A broken invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation(pub String);
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Presses each tile of `grid` in turn, applying `press` to the tile's position and
+/// color.
+fn each_tile<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+    mut press: impl FnMut(usize, usize, Color) -> Result<(), Violation>,
+) -> Result<(), Violation> {
+    for row in 0..ROWS {
+        for col in 0..COLS {
+            press(row, col, *grid.get(row, col))?;
+        }
+    }
+    Ok(())
+}
+
+/// Presses `color` tiles `times` times, following the pressed tile wherever each press
+/// moves it, and checks the grid ends as it started.
+fn follow_presses<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+    color: Color,
+    times: impl Fn(usize, usize) -> usize,
+    next: impl Fn(usize, usize) -> (usize, usize),
+) -> Result<(), Violation> {
+    let rules = RuleSet::standard();
+    each_tile(grid, |row, col, tile| {
+        if tile != color {
+            return Ok(());
+        }
+        let times = times(row, col);
+        let mut pressed = grid.clone();
+        let (mut r, mut c) = (row, col);
+        for _ in 0..times {
+            pressed = pressed.press_with(&rules, r, c);
+            (r, c) = next(r, c);
+        }
+        if pressed != *grid {
+            return Err(Violation(format!(
+                "pressing the {} tile at ({row}, {col}) {times} times changed the grid",
+                color.name()
+            )));
+        }
+        Ok(())
+    })
+}
+
+/// Pressing any tile on the grid stays on the grid, and pressing a tile whose color has
+/// no rule leaves the grid unchanged.
+pub fn presses_stay_on_grid<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+) -> Result<(), Violation> {
+    each_tile(grid, |row, col, color| {
+        let pressed = grid.press_with(rules, row, col);
+        if rules.get(color).is_none() && pressed != *grid {
+            return Err(Violation(format!(
+                "pressing the {} tile at ({row}, {col}) has no rule but changed the grid",
+                color.name()
+            )));
+        }
+        Ok(())
+    })
+}
+
+/// Pressing a gray tile under the standard rules does nothing.
+pub fn gray_is_identity<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+) -> Result<(), Violation> {
+    follow_presses(grid, Color::Gray, |_, _| 1, |row, col| (row, col))
+}
+
+/// Pressing a black tile once for each column of its row, following it as the row
+/// rotates, puts the row back as it was under the standard rules.
+pub fn black_cycles_its_row<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+) -> Result<(), Violation> {
+    follow_presses(
+        grid,
+        Color::Black,
+        |_, _| COLS,
+        |row, col| (row, (col + 1) % COLS),
+    )
+}
+
+/// Pressing a pink tile once for each of its neighbours puts them back as they were
+/// under the standard rules, however many neighbours it has at the edge of the grid.
+pub fn pink_cycles_its_neighbours<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+) -> Result<(), Violation> {
+    follow_presses(
+        grid,
+        Color::Pink,
+        |row, col| grid.neighbours_clockwise(row, col).len(),
+        |row, col| (row, col),
+    )
+}
+
+/// Pressing a green tile, then pressing it again where it was swapped to, puts the grid
+/// back as it was under the standard rules.
+pub fn green_swaps_back<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+) -> Result<(), Violation> {
+    follow_presses(
+        grid,
+        Color::Green,
+        |_, _| 2,
+        |row, col| (ROWS - 1 - row, COLS - 1 - col),
+    )
+}
+
+/// Every standard rule invariant above, for one grid.
+pub fn standard_rules<const ROWS: usize, const COLS: usize>(
+    grid: &Grid<ROWS, COLS>,
+) -> Result<(), Violation> {
+    presses_stay_on_grid(grid, &RuleSet::standard())?;
+    gray_is_identity(grid)?;
+    black_cycles_its_row(grid)?;
+    pink_cycles_its_neighbours(grid)?;
+    green_swaps_back(grid)
+}
+
+/// Pressing the tiles of `solution` from the puzzle's original grid solves it.
+pub fn solution_verifies<const ROWS: usize, const COLS: usize>(
+    puzzle: &Puzzle<ROWS, COLS>,
+    solution: &[(usize, usize)],
+) -> Result<(), Violation> {
+    if let Some(&(row, col)) = solution
+        .iter()
+        .find(|&&(row, col)| row >= ROWS || col >= COLS)
+    {
+        return Err(Violation(format!(
+            "solution presses ({row}, {col}), which is off the grid"
+        )));
+    }
+    let end = match puzzle
+        .original()
+        .trace_with(puzzle.rules(), solution)
+        .last()
+    {
+        Some(press) => press.result.clone(),
+        None => puzzle.original().clone(),
+    };
+    if !end.is_solved(puzzle.goals()) {
+        return Err(Violation(format!(
+            "solution {solution:?} does not solve the puzzle"
+        )));
+    }
+    Ok(())
+}
+
+/// The solver's answer for a puzzle verifies and ends at the grid it reports, the
+/// counting solver agrees on its length, and the solvability check agrees on whether
+/// there is one.
+#[cfg(feature = "solver")]
+pub fn solver_output_verifies<const ROWS: usize, const COLS: usize>(
+    puzzle: &Puzzle<ROWS, COLS>,
+) -> Result<(), Violation> {
+    let solution = puzzle.solve();
+    let counted = puzzle.solve_counting();
+    if puzzle.is_solvable() != solution.is_some() {
+        return Err(Violation(format!(
+            "is_solvable disagrees with solve, which found {solution:?}"
+        )));
+    }
+    match (&solution, &counted) {
+        (None, None) => Ok(()),
+        (Some(solution), Some((counted, count))) => {
+            solution_verifies(puzzle, &solution.presses)?;
+            let end = solution
+                .presses
+                .iter()
+                .fold(puzzle.original().clone(), |grid, &(row, col)| {
+                    grid.press_with(puzzle.rules(), row, col)
+                });
+            if solution.grid != end {
+                return Err(Violation(format!(
+                    "solve reported a final grid of {:?} but its presses end at {end:?}",
+                    solution.grid
+                )));
+            }
+            if solution.presses.len() != counted.len() || *count == 0 {
+                return Err(Violation(format!(
+                    "solve found {} presses but solve_counting found {count} solutions of {}",
+                    solution.presses.len(),
+                    counted.len()
+                )));
+            }
+            Ok(())
+        }
+        _ => Err(Violation(format!(
+            "solve and solve_counting disagree on whether the puzzle is solvable: {solution:?} and {counted:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn color() -> impl Strategy<Value = Color> {
+        proptest::sample::select(Color::BUILTIN.to_vec())
+    }
+
+    fn grid<const ROWS: usize, const COLS: usize>() -> impl Strategy<Value = Grid<ROWS, COLS>> {
+        proptest::collection::vec(color(), ROWS * COLS)
+            .prop_map(|colors| Grid::from_fn(|row, col| colors[row * COLS + col]))
+    }
+
+    /// Puzzles of gray, white and black tiles, which keep the solver's search small
+    /// even when there is no solution.
+    #[cfg(feature = "solver")]
+    fn puzzle() -> impl Strategy<Value = Puzzle> {
+        let colors = vec![Color::Gray, Color::White, Color::Black];
+        let tiles = proptest::collection::vec(proptest::sample::select(colors.clone()), 9);
+        // Gray goals start the puzzle solved, which tests nothing
+        let goal = proptest::sample::select(colors[1..].to_vec());
+        (proptest::array::uniform4(goal), tiles).prop_map(|(goals, tiles)| {
+            Puzzle::new(goals, Grid::from_fn(|row, col| tiles[row * 3 + col]))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn standard_rules_hold(grid in grid::<3, 3>()) {
+            standard_rules(&grid).map_err(|e| TestCaseError::fail(e.0))?;
+        }
+
+        #[test]
+        fn standard_rules_hold_on_other_sizes(
+            wide in grid::<2, 4>(),
+            tall in grid::<4, 1>(),
+            tiny in grid::<1, 1>(),
+        ) {
+            standard_rules(&wide).map_err(|e| TestCaseError::fail(e.0))?;
+            standard_rules(&tall).map_err(|e| TestCaseError::fail(e.0))?;
+            standard_rules(&tiny).map_err(|e| TestCaseError::fail(e.0))?;
+        }
+
+        #[test]
+        fn empty_rules_change_nothing(grid in grid::<3, 3>()) {
+            presses_stay_on_grid(&grid, &RuleSet::empty()).map_err(|e| TestCaseError::fail(e.0))?;
+        }
+    }
+
+    #[cfg(feature = "solver")]
+    proptest! {
+        // Each case runs the solver, so fewer cases keep the suite quick
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn solver_output_always_verifies(puzzle in puzzle()) {
+            solver_output_verifies(&puzzle).map_err(|e| TestCaseError::fail(e.0))?;
+        }
+    }
+
+    #[test]
+    fn wrong_solutions_are_caught() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let puzzle = Puzzle::new([Color::White; 4], grid);
+        assert_eq!(solution_verifies(&puzzle, &[(0, 2), (0, 1)]), Ok(()));
+        assert!(solution_verifies(&puzzle, &[(0, 2)]).is_err());
+        assert!(solution_verifies(&puzzle, &[(3, 0)]).is_err());
+    }
+}
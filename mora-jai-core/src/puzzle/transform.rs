@@ -0,0 +1,262 @@
+//! Mirroring and rotating grids and puzzles, for normalizing puzzles which differ only
+//! by symmetry and for making practice variants of known boxes.
+//!
+//! Some rules depend on direction: yellow tiles move up, violet tiles move down and pink
+//! tiles turn their neighbours clockwise. A transformed puzzle is therefore a puzzle of
+//! its own, and its solution is not always the original solution transformed.
+
+use crate::color::Color;
+use crate::puzzle::{Corner, Grid, HistoryEntry, Move, Puzzle};
+
+/// The corners in goal order.
+const GOAL_ORDER: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+/// A flip or turn which keeps a grid's shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// Left and right swap, as in a mirror held beside the box
+    MirrorHorizontal,
+    /// Top and bottom swap, as in a mirror held below the box
+    MirrorVertical,
+    /// A half turn, swapping both left and right and top and bottom
+    Rotate180,
+}
+
+impl Transform {
+    /// Every transform, for finding all the variants of a puzzle.
+    pub const ALL: [Transform; 3] = [
+        Transform::MirrorHorizontal,
+        Transform::MirrorVertical,
+        Transform::Rotate180,
+    ];
+
+    /// Where the tile at `row` and `col` of a `ROWS` by `COLS` grid ends up. Each
+    /// transform undoes itself, so this is also where the tile came from.
+    pub fn tile<const ROWS: usize, const COLS: usize>(
+        self,
+        row: usize,
+        col: usize,
+    ) -> (usize, usize) {
+        match self {
+            Transform::MirrorHorizontal => (row, COLS - 1 - col),
+            Transform::MirrorVertical => (ROWS - 1 - row, col),
+            Transform::Rotate180 => (ROWS - 1 - row, COLS - 1 - col),
+        }
+    }
+
+    /// Where `corner` ends up.
+    pub fn corner(self, corner: Corner) -> Corner {
+        use Corner::*;
+        match (self, corner) {
+            (Transform::MirrorHorizontal, NW) | (Transform::MirrorVertical, SE) => NE,
+            (Transform::MirrorHorizontal, NE) | (Transform::MirrorVertical, SW) => NW,
+            (Transform::MirrorHorizontal, SW) | (Transform::MirrorVertical, NE) => SE,
+            (Transform::MirrorHorizontal, SE) | (Transform::MirrorVertical, NW) => SW,
+            (Transform::Rotate180, NW) => SE,
+            (Transform::Rotate180, NE) => SW,
+            (Transform::Rotate180, SW) => NE,
+            (Transform::Rotate180, SE) => NW,
+        }
+    }
+
+    /// The move pressing the same tile or corner after the transform.
+    pub fn action<const ROWS: usize, const COLS: usize>(self, action: Move) -> Move {
+        match action {
+            Move::Tile(row, col) => {
+                let (row, col) = self.tile::<ROWS, COLS>(row, col);
+                Move::Tile(row, col)
+            }
+            Move::Corner(corner) => Move::Corner(self.corner(corner)),
+        }
+    }
+
+    /// Goals in goal order (NW, NE, SW, SE), each moved with its corner.
+    pub fn goals(self, goals: &[Color; 4]) -> [Color; 4] {
+        GOAL_ORDER.map(|corner| {
+            let from = self.corner(corner);
+            goals[GOAL_ORDER.iter().position(|&c| c == from).unwrap()]
+        })
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Grid<ROWS, COLS> {
+    /// The grid after `transform`.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        Self::from_fn(|row, col| {
+            let (row, col) = transform.tile::<ROWS, COLS>(row, col);
+            self.colors[row][col]
+        })
+    }
+
+    /// The grid with its left and right sides swapped.
+    pub fn mirrored_horizontal(&self) -> Self {
+        self.transformed(Transform::MirrorHorizontal)
+    }
+
+    /// The grid with its top and bottom swapped.
+    pub fn mirrored_vertical(&self) -> Self {
+        self.transformed(Transform::MirrorVertical)
+    }
+
+    /// The grid turned upside down.
+    pub fn rotated_180(&self) -> Self {
+        self.transformed(Transform::Rotate180)
+    }
+
+    /// The grid turned a quarter turn clockwise, so its rows become columns.
+    pub fn rotated_clockwise(&self) -> Grid<COLS, ROWS> {
+        Grid::from_fn(|row, col| self.colors[col][COLS - 1 - row])
+    }
+
+    /// The grid turned a quarter turn counterclockwise, so its rows become columns.
+    pub fn rotated_counterclockwise(&self) -> Grid<COLS, ROWS> {
+        Grid::from_fn(|row, col| self.colors[ROWS - 1 - col][row])
+    }
+}
+
+/// The corners in the order [`Puzzle`] keeps their colors.
+const CORNER_SLOTS: [Corner; 4] = [Corner::SW, Corner::NW, Corner::SE, Corner::NE];
+
+/// A puzzle's corner colors, each moved with its corner.
+fn move_corners(corners: [Color; 4], transform: Transform) -> [Color; 4] {
+    CORNER_SLOTS.map(|corner| {
+        let from = transform.corner(corner);
+        corners[CORNER_SLOTS.iter().position(|&c| c == from).unwrap()]
+    })
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// The puzzle after `transform`, with its goals, locked corners and moves so far
+    /// following their tiles. The rules are kept as they are, so where they depend on
+    /// direction, such as yellow tiles moving up, the transformed puzzle plays
+    /// differently and may have another solution or none at all.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        Self {
+            goals: transform.goals(&self.goals),
+            corners: move_corners(self.corners, transform),
+            original: self.original.transformed(transform),
+            state: self.state.transformed(transform),
+            rules: self.rules.clone(),
+            history: self
+                .history
+                .iter()
+                .map(|entry| HistoryEntry {
+                    action: transform.action::<ROWS, COLS>(entry.action),
+                    state: entry.state.transformed(transform),
+                    corners: move_corners(entry.corners, transform),
+                    resets: entry.resets,
+                })
+                .collect(),
+            resets: self.resets,
+        }
+    }
+
+    /// The puzzle with its left and right sides swapped.
+    pub fn mirrored_horizontal(&self) -> Self {
+        self.transformed(Transform::MirrorHorizontal)
+    }
+
+    /// The puzzle with its top and bottom swapped.
+    pub fn mirrored_vertical(&self) -> Self {
+        self.transformed(Transform::MirrorVertical)
+    }
+
+    /// The puzzle turned upside down.
+    pub fn rotated_180(&self) -> Self {
+        self.transformed(Transform::Rotate180)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(goals: [Color; 4]) -> Puzzle {
+        Puzzle::new(
+            goals,
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        )
+    }
+
+    #[test]
+    fn grids_flip_and_turn() {
+        let grid = Grid::from_rows(
+            [Color::Pink, Color::White, Color::Black],
+            [Color::Yellow, Color::Gray, Color::Violet],
+            [Color::Blue, Color::Green, Color::Red],
+        );
+        assert_eq!(
+            grid.mirrored_horizontal(),
+            Grid::from_rows(
+                [Color::Black, Color::White, Color::Pink],
+                [Color::Violet, Color::Gray, Color::Yellow],
+                [Color::Red, Color::Green, Color::Blue],
+            )
+        );
+        assert_eq!(
+            grid.rotated_clockwise(),
+            Grid::from_rows(
+                [Color::Blue, Color::Yellow, Color::Pink],
+                [Color::Green, Color::Gray, Color::White],
+                [Color::Red, Color::Violet, Color::Black],
+            )
+        );
+        assert_eq!(grid.rotated_clockwise().rotated_counterclockwise(), grid);
+        assert_eq!(
+            grid.rotated_clockwise().rotated_clockwise(),
+            grid.rotated_180()
+        );
+        assert_eq!(
+            grid.mirrored_horizontal().mirrored_vertical(),
+            grid.rotated_180()
+        );
+        for transform in Transform::ALL {
+            assert_eq!(grid.transformed(transform).transformed(transform), grid);
+        }
+    }
+
+    #[test]
+    fn puzzles_carry_goals_corners_and_moves_along() {
+        let mut puzzle = sample([Color::White, Color::Black, Color::Red, Color::Blue]);
+        puzzle.press_tile(0, 2);
+        puzzle.press_corner(Corner::SE);
+
+        let mirrored = puzzle.mirrored_horizontal();
+        assert_eq!(
+            mirrored.goals(),
+            &[Color::Black, Color::White, Color::Blue, Color::Red]
+        );
+        assert_eq!(
+            mirrored.current_state(),
+            &puzzle.current_state().mirrored_horizontal()
+        );
+        assert_eq!(
+            mirrored.get_corner(Corner::SW),
+            puzzle.get_corner(Corner::SE)
+        );
+        assert_eq!(
+            mirrored.moves().collect::<Vec<_>>(),
+            [Move::Tile(0, 0), Move::Corner(Corner::SW)]
+        );
+        for transform in Transform::ALL {
+            assert_eq!(puzzle.transformed(transform).transformed(transform), puzzle);
+        }
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn symmetric_rules_give_mirrored_solutions() {
+        // White and gray tiles do not depend on direction, so solutions mirror too
+        let puzzle = sample([Color::White; 4]);
+        let solution = puzzle.solve().unwrap().presses;
+        let mirrored = puzzle.mirrored_horizontal().solve().unwrap().presses;
+        assert_eq!(
+            (solution, mirrored),
+            (vec![(0, 2), (0, 1)], vec![(0, 0), (0, 1)])
+        );
+    }
+}
@@ -0,0 +1,708 @@
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::color::Color;
+use crate::puzzle::Grid;
+
+/// The effect of pressing a tile.
+///
+/// Each color's behaviour is a `TileRule` registered in a [`RuleSet`]. Implement this
+/// trait to experiment with house rules without touching the core press logic.
+pub trait TileRule<const ROWS: usize, const COLS: usize>: Send + Sync {
+    /// Applies this rule for a press of the tile at the given row and column, returning
+    /// the resulting grid.
+    ///
+    /// `rules` is the rule set the press is being resolved against, for rules which
+    /// defer to the rules of other colors.
+    fn apply(
+        &self,
+        rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS>;
+
+    /// A short description of what this rule does, for display to players.
+    fn describe(&self) -> String {
+        String::from("custom rule")
+    }
+
+    /// How hard players find it to foresee what this rule does, from 0 for a rule
+    /// anyone can follow to 1 for the most confusing. Difficulty reports weigh presses
+    /// by it.
+    fn obscurity(&self) -> f64 {
+        0.5
+    }
+
+    /// A description of this rule and its settings, the same for any rule that behaves
+    /// the same way, by which rule sets are compared. Rules returning `None`, as they do
+    /// unless they say otherwise, are only equal to themselves.
+    fn key(&self) -> Option<String> {
+        None
+    }
+
+    /// The color whose rule this rule follows for a press of the tile at the given row
+    /// and column, for rules which defer to the rules of other colors.
+    fn acts_as(&self, _grid: &Grid<ROWS, COLS>, _row: usize, _col: usize) -> Option<Color> {
+        None
+    }
+}
+
+/// The tiles orthogonally adjacent to the given tile.
+fn orthogonal<const ROWS: usize, const COLS: usize>(row: usize, col: usize) -> Vec<(usize, usize)> {
+    let mut adjacent: Vec<(usize, usize)> = Vec::with_capacity(4);
+    if row > 0 {
+        adjacent.push((row - 1, col));
+    }
+    if row + 1 < ROWS {
+        adjacent.push((row + 1, col));
+    }
+    if col > 0 {
+        adjacent.push((row, col - 1));
+    }
+    if col + 1 < COLS {
+        adjacent.push((row, col + 1));
+    }
+    adjacent
+}
+
+/// Does nothing. The standard gray rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inert;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for Inert {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        _row: usize,
+        _col: usize,
+    ) -> Grid<ROWS, COLS> {
+        grid.clone()
+    }
+
+    fn describe(&self) -> String {
+        String::from("does nothing")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.0
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Toggles the pressed tile and all orthogonally adjacent white or gray tiles between
+/// white and gray. The standard white rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToggleAdjacent;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for ToggleAdjacent {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        let mut adjacent = orthogonal::<ROWS, COLS>(row, col);
+        adjacent.push((row, col));
+
+        for (row, col) in adjacent.into_iter() {
+            match grid.get(row, col) {
+                Color::White => copy.set(row, col, Color::Gray),
+                Color::Gray => copy.set(row, col, Color::White),
+                _ => {}
+            }
+        }
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        String::from("toggles itself and adjacent white and gray tiles")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.4
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Rotates the pressed tile's row one place to the right. The standard black rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RotateRow;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for RotateRow {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        _col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        for col in 0..COLS {
+            // Index of column directly to the right of col, wrapping if necessary.
+            let right_col = (col + 1) % COLS;
+            copy.set(row, right_col, *grid.get(row, col));
+        }
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        String::from("rotates its row to the right")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.2
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Recolors every tile on the grid according to a color mapping. The standard red rule
+/// turns all black tiles red and all white tiles black.
+#[derive(Debug, Clone, Default)]
+pub struct Recolor {
+    map: BTreeMap<Color, Color>,
+}
+
+impl Recolor {
+    pub fn new(map: impl IntoIterator<Item = (Color, Color)>) -> Self {
+        Self {
+            map: map.into_iter().collect(),
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for Recolor {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        _row: usize,
+        _col: usize,
+    ) -> Grid<ROWS, COLS> {
+        Grid::from_fn(|row, col| {
+            let color = grid.get(row, col);
+            *self.map.get(color).unwrap_or(color)
+        })
+    }
+
+    fn describe(&self) -> String {
+        let changes: Vec<String> = self
+            .map
+            .iter()
+            .map(|(from, to)| format!("{} tiles become {}", from.name(), to.name()))
+            .collect();
+        format!("recolors the grid: {}", changes.join(", "))
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.5
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// If there is a majority color among the orthogonal neighbours, the pressed tile becomes
+/// that color. The standard orange rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Majority;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for Majority {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        let mut counts: BTreeMap<Color, u8> = Default::default();
+        for (row, col) in orthogonal::<ROWS, COLS>(row, col) {
+            *counts.entry(*grid.get(row, col)).or_insert(0) += 1;
+        }
+
+        let Some(&max) = counts.values().max() else {
+            return copy;
+        };
+
+        let max_colors: Vec<Color> = counts
+            .into_iter()
+            .filter(|&(_, count)| count == max)
+            .map(|(color, _)| color)
+            .collect();
+
+        // If only one color has the maximum, it is the majority color
+        if max_colors.len() == 1 {
+            copy.set(row, col, max_colors[0]);
+        }
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        String::from("becomes the majority color of its neighbours")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.6
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Swaps the pressed tile with the tile in the opposite position on the grid. The
+/// standard green rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwapOpposite;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for SwapOpposite {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        let opposing_row = ROWS - 1 - row;
+        let opposing_col = COLS - 1 - col;
+        copy.set(opposing_row, opposing_col, *grid.get(row, col));
+        copy.set(row, col, *grid.get(opposing_row, opposing_col));
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        String::from("swaps with the opposite tile")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.3
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Swaps the pressed tile with the tile at a fixed offset from it.
+///
+/// If the offset leads off the grid, nothing happens unless `wrap` is set, in which case
+/// the offset wraps around to the other side. The standard yellow rule swaps with the
+/// tile directly above and the standard violet rule with the tile directly below, both
+/// without wrapping.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapOffset {
+    /// Rows to move, with positive values moving up
+    pub rows: isize,
+    /// Columns to move, with positive values moving right
+    pub cols: isize,
+    pub wrap: bool,
+}
+
+impl SwapOffset {
+    pub const UP: Self = Self {
+        rows: 1,
+        cols: 0,
+        wrap: false,
+    };
+    pub const DOWN: Self = Self {
+        rows: -1,
+        cols: 0,
+        wrap: false,
+    };
+
+    pub fn wrapping(self) -> Self {
+        Self { wrap: true, ..self }
+    }
+
+    fn target<const ROWS: usize, const COLS: usize>(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Option<(usize, usize)> {
+        if self.wrap {
+            let row = (row as isize + self.rows).rem_euclid(ROWS as isize) as usize;
+            let col = (col as isize + self.cols).rem_euclid(COLS as isize) as usize;
+            Some((row, col))
+        } else {
+            let row = row.checked_add_signed(self.rows)?;
+            let col = col.checked_add_signed(self.cols)?;
+            (row < ROWS && col < COLS).then_some((row, col))
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for SwapOffset {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        if let Some((other_row, other_col)) = self.target::<ROWS, COLS>(row, col) {
+            copy.set(other_row, other_col, *grid.get(row, col));
+            copy.set(row, col, *grid.get(other_row, other_col));
+        }
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        let direction = match (self.rows, self.cols) {
+            (1, 0) => String::from("above"),
+            (-1, 0) => String::from("below"),
+            (0, 1) => String::from("to the right"),
+            (0, -1) => String::from("to the left"),
+            (rows, cols) => format!("{rows} rows up and {cols} columns right"),
+        };
+        let wrap = if self.wrap { ", wrapping around" } else { "" };
+        format!("swaps with the tile {direction}{wrap}")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.3
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Rotates the pressed tile's neighbours (including diagonals) one place clockwise. The
+/// standard pink rule.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RotateNeighbours;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for RotateNeighbours {
+    fn apply(
+        &self,
+        _rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let mut copy = grid.clone();
+
+        let neighbours = grid.neighbours_clockwise(row, col);
+        // A lone tile has nothing to rotate
+        if neighbours.is_empty() {
+            return copy;
+        }
+
+        // This window accounts for every pair except the (last, first) pair
+        // which we handle below
+        for window in neighbours.windows(2) {
+            let first = window[1];
+            let second = window[0];
+
+            copy.set(second.0, second.1, *grid.get(first.0, first.1));
+        }
+
+        let first = neighbours[0];
+        let second = neighbours.last().unwrap();
+        copy.set(second.0, second.1, *grid.get(first.0, first.1));
+
+        copy
+    }
+
+    fn describe(&self) -> String {
+        String::from("rotates its neighbours clockwise")
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.9
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// Behaves as if the pressed tile had the color of the middle tile. The standard blue
+/// rule.
+///
+/// On boards with an even number of rows or columns, the tile just above and right of
+/// the center point counts as the middle. If the middle tile has the same color as the
+/// pressed tile nothing happens, on pain of infinite recursion.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mimic;
+
+impl<const ROWS: usize, const COLS: usize> TileRule<ROWS, COLS> for Mimic {
+    fn apply(
+        &self,
+        rules: &RuleSet<ROWS, COLS>,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        let middle_color = *grid.get(ROWS / 2, COLS / 2);
+        if middle_color == *grid.get(row, col) {
+            return grid.clone();
+        }
+
+        rules.apply(middle_color, grid, row, col)
+    }
+
+    fn describe(&self) -> String {
+        String::from("acts as the middle tile")
+    }
+
+    fn acts_as(&self, grid: &Grid<ROWS, COLS>, row: usize, col: usize) -> Option<Color> {
+        let middle_color = *grid.get(ROWS / 2, COLS / 2);
+        (middle_color != *grid.get(row, col)).then_some(middle_color)
+    }
+
+    fn obscurity(&self) -> f64 {
+        0.8
+    }
+
+    fn key(&self) -> Option<String> {
+        Some(format!("{self:?}"))
+    }
+}
+
+/// The rules governing what pressing each color of tile does.
+///
+/// Colors without a registered rule do nothing when pressed.
+#[derive(Clone)]
+pub struct RuleSet<const ROWS: usize = 3, const COLS: usize = 3> {
+    rules: BTreeMap<Color, Arc<dyn TileRule<ROWS, COLS>>>,
+}
+
+impl<const ROWS: usize, const COLS: usize> RuleSet<ROWS, COLS> {
+    /// A rule set with no rules, in which every press does nothing.
+    pub fn empty() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// The rules of the boxes in Blue Prince.
+    pub fn standard() -> Self {
+        Self::shared_standard().clone()
+    }
+
+    /// The standard rules, built once for each grid size and shared, for presses which
+    /// only borrow them.
+    pub(crate) fn shared_standard() -> &'static Self {
+        type Shared = HashMap<TypeId, &'static (dyn Any + Send + Sync)>;
+        static SHARED: LazyLock<RwLock<Shared>> = LazyLock::new(Default::default);
+
+        let id = TypeId::of::<Self>();
+        let found = SHARED.read().expect("never poisoned").get(&id).copied();
+        let rules = found.unwrap_or_else(|| {
+            let mut shared = SHARED.write().expect("never poisoned");
+            *shared
+                .entry(id)
+                .or_insert_with(|| Box::leak(Box::new(Self::build_standard())))
+        });
+        rules
+            .downcast_ref()
+            .expect("rules are shared by their type")
+    }
+
+    fn build_standard() -> Self {
+        Self::empty()
+            .with_rule(Color::Gray, Inert)
+            .with_rule(Color::White, ToggleAdjacent)
+            .with_rule(Color::Black, RotateRow)
+            .with_rule(
+                Color::Red,
+                Recolor::new([(Color::Black, Color::Red), (Color::White, Color::Black)]),
+            )
+            .with_rule(Color::Orange, Majority)
+            .with_rule(Color::Green, SwapOpposite)
+            .with_rule(Color::Yellow, SwapOffset::UP)
+            .with_rule(Color::Violet, SwapOffset::DOWN)
+            .with_rule(Color::Pink, RotateNeighbours)
+            .with_rule(Color::Blue, Mimic)
+    }
+
+    /// Replaces the rule for the given color.
+    pub fn with_rule(mut self, color: Color, rule: impl TileRule<ROWS, COLS> + 'static) -> Self {
+        self.set_rule(color, rule);
+        self
+    }
+
+    /// Replaces the rule for the given color.
+    pub fn set_rule(&mut self, color: Color, rule: impl TileRule<ROWS, COLS> + 'static) {
+        self.rules.insert(color, Arc::new(rule));
+    }
+
+    /// Removes the rule for the given color, so that pressing it does nothing.
+    pub fn remove_rule(&mut self, color: Color) {
+        self.rules.remove(&color);
+    }
+
+    /// Removes the rules for colors not accepted by `keep`. Random puzzles only draw from
+    /// colors with a rule, so this limits which tiles they contain.
+    pub fn retain_colors(&mut self, mut keep: impl FnMut(Color) -> bool) {
+        self.rules.retain(|&color, _| keep(color));
+    }
+
+    /// The colors which have a rule, in order.
+    pub fn colors(&self) -> impl Iterator<Item = Color> + '_ {
+        self.rules.keys().copied()
+    }
+
+    pub fn get(&self, color: Color) -> Option<&dyn TileRule<ROWS, COLS>> {
+        self.rules.get(&color).map(|rule| rule.as_ref())
+    }
+
+    /// Applies the rule for `color` to a press at the given tile.
+    pub fn apply(
+        &self,
+        color: Color,
+        grid: &Grid<ROWS, COLS>,
+        row: usize,
+        col: usize,
+    ) -> Grid<ROWS, COLS> {
+        match self.get(color) {
+            Some(rule) => rule.apply(self, grid, row, col),
+            None => grid.clone(),
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Default for RuleSet<ROWS, COLS> {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> fmt::Debug for RuleSet<ROWS, COLS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.rules.keys()).finish()
+    }
+}
+
+/// Rule sets are equal when every color has the same rule, either the very same or one
+/// with the same [`key`](TileRule::key).
+impl<const ROWS: usize, const COLS: usize> PartialEq for RuleSet<ROWS, COLS> {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: &Arc<dyn TileRule<ROWS, COLS>>, b: &Arc<dyn TileRule<ROWS, COLS>>| {
+            Arc::ptr_eq(a, b) || a.key().is_some_and(|key| b.key() == Some(key))
+        };
+        self.rules.len() == other.rules.len()
+            && self
+                .rules
+                .iter()
+                .zip(&other.rules)
+                .all(|((a_color, a), (b_color, b))| a_color == b_color && same(a, b))
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Eq for RuleSet<ROWS, COLS> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Puzzle;
+
+    #[test]
+    fn rule_sets_compare_by_their_rules() {
+        assert_eq!(RuleSet::<3, 3>::build_standard(), RuleSet::build_standard());
+        assert!(std::ptr::eq(
+            RuleSet::<3, 3>::shared_standard(),
+            RuleSet::shared_standard()
+        ));
+        let wrapping = RuleSet::standard().with_rule(Color::Yellow, SwapOffset::UP.wrapping());
+        assert_ne!(wrapping, RuleSet::<3, 3>::standard());
+
+        let grid = Grid::new([Color::White; 9]);
+        assert_eq!(
+            Puzzle::new([Color::Gray; 4], grid.clone()),
+            Puzzle::new([Color::Gray; 4], grid)
+        );
+    }
+
+    #[test]
+    fn yellow_can_wrap() {
+        let wrapping = RuleSet::standard().with_rule(Color::Yellow, SwapOffset::UP.wrapping());
+        let grid = Grid::from_rows(
+            [Color::Yellow, Color::Red, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Pink, Color::Gray, Color::Gray],
+        );
+
+        // The standard rule leaves a yellow tile on the top row in place
+        assert_eq!(grid.press(2, 0), grid);
+        assert_eq!(
+            grid.press_with(&wrapping, 2, 0),
+            Grid::from_rows(
+                [Color::Pink, Color::Red, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Yellow, Color::Gray, Color::Gray],
+            )
+        );
+    }
+
+    #[test]
+    fn missing_rule_does_nothing() {
+        let mut rules = RuleSet::standard();
+        rules.remove_rule(Color::Black);
+
+        let grid = Grid::from_rows(
+            [Color::Black, Color::White, Color::Red],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+        assert_eq!(grid.press_with(&rules, 2, 0), grid);
+    }
+
+    #[test]
+    fn retain_colors_keeps_chosen_rules() {
+        let mut rules = RuleSet::<3, 3>::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::Pink));
+        assert_eq!(
+            rules.colors().collect::<Vec<_>>(),
+            [Color::Gray, Color::Pink]
+        );
+    }
+
+    #[test]
+    fn blue_mimics_custom_rule() {
+        let rules = RuleSet::standard().with_rule(Color::Yellow, SwapOffset::UP.wrapping());
+        let grid = Grid::from_rows(
+            [Color::Blue, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Yellow, Color::Gray],
+            [Color::Red, Color::Gray, Color::Gray],
+        );
+
+        assert_eq!(
+            grid.press_with(&rules, 2, 0),
+            Grid::from_rows(
+                [Color::Red, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Yellow, Color::Gray],
+                [Color::Blue, Color::Gray, Color::Gray],
+            )
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Share codes: short tokens for passing puzzles around, safe to paste into URLs and
+//! chat.
+//!
+//! A code packs the four goals (NW, NE, SW, SE) and nine tiles (top row first), each as a
+//! digit for its builtin color, into one number written in Crockford's base 32.
+
+use std::fmt;
+
+use crate::{Color, Grid, Puzzle, RuleSet};
+
+/// The number of characters in a share code.
+pub const SHARE_CODE_LEN: usize = 9;
+
+/// Crockford's base 32 alphabet, which leaves out letters easily mistaken for digits.
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareCodeError {
+    /// Codes are always `SHARE_CODE_LEN` characters long
+    WrongLength(usize),
+    InvalidChar(char),
+    /// The characters are valid but do not encode a puzzle
+    OutOfRange,
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareCodeError::WrongLength(len) => write!(
+                f,
+                "share codes are {SHARE_CODE_LEN} characters long, not {len}"
+            ),
+            ShareCodeError::InvalidChar(c) => write!(f, "invalid character {c:?} in share code"),
+            ShareCodeError::OutOfRange => write!(f, "share code does not describe a puzzle"),
+        }
+    }
+}
+
+impl std::error::Error for ShareCodeError {}
+
+/// The value of a code character, reading the letters Crockford's alphabet leaves out as
+/// the digits they resemble.
+fn char_value(c: char) -> Option<u64> {
+    let c = match c.to_ascii_lowercase() {
+        'o' => '0',
+        'i' | 'l' => '1',
+        c => c,
+    };
+    ALPHABET
+        .iter()
+        .position(|&a| char::from(a) == c)
+        .map(|i| i as u64)
+}
+
+impl Puzzle {
+    /// The share code for this puzzle's goals and original grid, or `None` if it uses
+    /// custom colors, which codes cannot describe.
+    pub fn to_share_code(&self) -> Option<String> {
+        let tiles = (0..3)
+            .rev()
+            .flat_map(|row| (0..3).map(move |col| (row, col)))
+            .map(|(row, col)| *self.original().get(row, col));
+
+        let mut value = 0u64;
+        for color in self.goals().iter().copied().chain(tiles) {
+            let digit = Color::BUILTIN.iter().position(|&c| c == color)?;
+            value = value * Color::BUILTIN.len() as u64 + digit as u64;
+        }
+
+        let mut code = [0u8; SHARE_CODE_LEN];
+        for c in code.iter_mut().rev() {
+            *c = ALPHABET[(value % 32) as usize];
+            value /= 32;
+        }
+        Some(String::from_utf8(code.to_vec()).expect("the alphabet is ASCII"))
+    }
+
+    /// Reads a puzzle from its share code, with the standard rules.
+    pub fn from_share_code(code: &str) -> Result<Self, ShareCodeError> {
+        Self::from_share_code_with_rules(code, RuleSet::standard())
+    }
+
+    /// Reads a puzzle from its share code, with the given rules.
+    pub fn from_share_code_with_rules(code: &str, rules: RuleSet) -> Result<Self, ShareCodeError> {
+        let len = code.chars().count();
+        if len != SHARE_CODE_LEN {
+            return Err(ShareCodeError::WrongLength(len));
+        }
+        let mut value = 0u64;
+        for c in code.chars() {
+            value = value * 32 + char_value(c).ok_or(ShareCodeError::InvalidChar(c))?;
+        }
+
+        let base = Color::BUILTIN.len() as u64;
+        let mut colors = [Color::Gray; 13];
+        for color in colors.iter_mut().rev() {
+            *color = Color::BUILTIN[(value % base) as usize];
+            value /= base;
+        }
+        if value != 0 {
+            return Err(ShareCodeError::OutOfRange);
+        }
+
+        let [nw, ne, sw, se, tiles @ ..] = colors;
+        let [a, b, c, d, e, f, g, h, i] = tiles;
+        let grid = Grid::from_rows([a, b, c], [d, e, f], [g, h, i]);
+        Ok(Puzzle::with_rules([nw, ne, sw, se], grid, rules))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_codes_round_trip() {
+        let grid = Grid::from_rows(
+            [Color::Black, Color::White, Color::Red],
+            [Color::Gray, Color::Orange, Color::Pink],
+            [Color::Violet, Color::Gray, Color::Black],
+        );
+        let puzzle = Puzzle::new([Color::Green, Color::Red, Color::Blue, Color::Yellow], grid);
+
+        let code = puzzle.to_share_code().unwrap();
+        assert_eq!(code.len(), SHARE_CODE_LEN);
+        let decoded = Puzzle::from_share_code(&code).unwrap();
+        assert_eq!(decoded.goals(), puzzle.goals());
+        assert_eq!(decoded.original(), puzzle.original());
+        assert_eq!(
+            Puzzle::from_share_code(&code.to_uppercase())
+                .unwrap()
+                .to_share_code(),
+            Some(code)
+        );
+    }
+
+    #[test]
+    fn bad_share_codes_are_rejected() {
+        assert_eq!(
+            Puzzle::from_share_code("abc").unwrap_err(),
+            ShareCodeError::WrongLength(3)
+        );
+        assert_eq!(
+            Puzzle::from_share_code("abcdefgh!").unwrap_err(),
+            ShareCodeError::InvalidChar('!')
+        );
+        assert_eq!(
+            Puzzle::from_share_code("zzzzzzzzz").unwrap_err(),
+            ShareCodeError::OutOfRange
+        );
+    }
+}
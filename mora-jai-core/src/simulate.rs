@@ -0,0 +1,187 @@
+//! Simulating players who make mistakes, for measuring how much a wrong corner press,
+//! which resets the whole box, costs compared to a wrong tile press.
+
+use rand::Rng;
+
+use crate::Puzzle;
+use crate::enumerate::{PressGraph, UNSOLVED};
+
+/// How a simulated player plays. Without mistakes, the player presses tiles along an
+/// optimal solution, choosing among equally short ones in keypad order, then presses
+/// the four corners to lock them. After a mistake the player carries on from wherever
+/// the box is, again along an optimal solution, and if no solution is left it resets the
+/// box on purpose by pressing a corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Policy {
+    /// The chance, before each tile press, of pressing a corner whose tile does not
+    /// show its goal, resetting the box
+    pub wrong_corner: f64,
+    /// The chance of pressing a random tile instead of the one an optimal solution
+    /// presses next
+    pub wrong_tile: f64,
+    /// The number of moves after which the player gives up
+    pub max_moves: usize,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            wrong_corner: 0.0,
+            wrong_tile: 0.0,
+            max_moves: 1000,
+        }
+    }
+}
+
+/// The outcome of each trial of a simulation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Simulation {
+    /// The moves each trial took to lock every corner, counting tile and corner presses,
+    /// or `None` if the player gave up
+    pub moves: Vec<Option<usize>>,
+    /// How many times the box was reset in each trial
+    pub resets: Vec<usize>,
+}
+
+impl Simulation {
+    /// The fraction of trials in which the box was solved, from 0 to 1.
+    pub fn solved_fraction(&self) -> f64 {
+        if self.moves.is_empty() {
+            return 0.0;
+        }
+        self.moves.iter().flatten().count() as f64 / self.moves.len() as f64
+    }
+
+    /// The average number of moves taken by the trials in which the box was solved, if
+    /// it was solved in any.
+    pub fn mean_moves(&self) -> Option<f64> {
+        let solved: Vec<usize> = self.moves.iter().flatten().copied().collect();
+        (!solved.is_empty()).then(|| solved.iter().sum::<usize>() as f64 / solved.len() as f64)
+    }
+
+    /// The average number of resets per trial.
+    pub fn mean_resets(&self) -> f64 {
+        if self.resets.is_empty() {
+            return 0.0;
+        }
+        self.resets.iter().sum::<usize>() as f64 / self.resets.len() as f64
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Plays the puzzle from its start `trials` times as a player following `policy`,
+    /// or returns `None` if it has no solution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either chance in `policy` is not between 0 and 1.
+    pub fn simulate(&self, policy: &Policy, trials: usize) -> Option<Simulation> {
+        simulate(self, policy, trials, &mut rand::rng())
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn simulate<const ROWS: usize, const COLS: usize>(
+    puzzle: &Puzzle<ROWS, COLS>,
+    policy: &Policy,
+    trials: usize,
+    rng: &mut impl Rng,
+) -> Option<Simulation> {
+    assert!(
+        (0.0..=1.0).contains(&policy.wrong_corner) && (0.0..=1.0).contains(&policy.wrong_tile),
+        "mistake chances must be between 0 and 1"
+    );
+    let graph = PressGraph::build([puzzle.original().clone()], puzzle.rules());
+    let distances = graph.distances(puzzle.goals());
+    if distances[0] == UNSOLVED {
+        return None;
+    }
+    let presses = ROWS * COLS;
+    // The press an error-free player makes from each grid
+    let best: Vec<usize> = (0..graph.grids.len())
+        .map(|grid| {
+            let targets = &graph.targets[grid * presses..(grid + 1) * presses];
+            targets
+                .iter()
+                .position(|&target| {
+                    distances[grid].checked_sub(1) == Some(distances[target as usize])
+                })
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut simulation = Simulation::default();
+    for _ in 0..trials {
+        let (mut grid, mut moves, mut resets) = (0, 0, 0);
+        let solved = loop {
+            if distances[grid] == 0 {
+                // Every corner tile shows its goal, so each corner press locks it
+                break moves + 4 <= policy.max_moves;
+            }
+            if moves >= policy.max_moves {
+                break false;
+            }
+            moves += 1;
+
+            // Some corner tile is not showing its goal, or the box would be solved
+            if distances[grid] == UNSOLVED || rng.random_bool(policy.wrong_corner) {
+                grid = 0;
+                resets += 1;
+                continue;
+            }
+            let press = if rng.random_bool(policy.wrong_tile) {
+                rng.random_range(0..presses)
+            } else {
+                best[grid]
+            };
+            grid = graph.targets[grid * presses + press] as usize;
+        };
+        simulation.moves.push(solved.then_some(moves + 4));
+        simulation.resets.push(resets);
+    }
+    Some(simulation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Grid};
+
+    fn sample() -> Puzzle {
+        Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        )
+    }
+
+    #[test]
+    fn mistakes_cost_moves() {
+        let puzzle = sample();
+        let perfect = puzzle.simulate(&Policy::default(), 10).unwrap();
+        assert_eq!(perfect.moves, vec![Some(2 + 4); 10]);
+        assert_eq!(perfect.mean_resets(), 0.0);
+
+        let policy = Policy {
+            wrong_corner: 0.5,
+            wrong_tile: 0.2,
+            ..Policy::default()
+        };
+        let sloppy = puzzle.simulate(&policy, 200).unwrap();
+        assert_eq!(sloppy.solved_fraction(), 1.0);
+        assert!(sloppy.mean_moves().unwrap() > 6.0);
+        assert!(sloppy.mean_resets() > 0.0);
+
+        let hopeless = Policy {
+            wrong_corner: 1.0,
+            max_moves: 50,
+            ..Policy::default()
+        };
+        let gave_up = puzzle.simulate(&hopeless, 5).unwrap();
+        assert_eq!(gave_up.solved_fraction(), 0.0);
+        assert_eq!(gave_up.resets, vec![50; 5]);
+    }
+}
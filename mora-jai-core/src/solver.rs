@@ -0,0 +1,1149 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use crate::puzzle::BuildGridHasher;
+use crate::state::{GridGoals, search_hashed};
+use crate::{Color, Corner, Grid, Move, Puzzle, RuleSet, WinCheck};
+
+/// Limits on the resources a search may use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveOptions {
+    /// The most grid states to hold in memory at once, counting both visited states and
+    /// states waiting to be visited. `None` means no limit.
+    pub max_states: Option<usize>,
+    /// The longest a search may run. `None` means no limit.
+    pub time_limit: Option<Duration>,
+    /// Keep at most this many states in memory by forgetting the states of older layers
+    /// of the search, and if one layer alone would not fit, searching only part of it.
+    /// Solutions may then be longer than optimal, and a search finding none cannot tell
+    /// whether one exists. Searches give up after [`BOUNDED_MAX_PRESSES`] presses, as
+    /// forgotten states can be searched again forever. `None` keeps every state, so
+    /// solutions are optimal. Only [`Puzzle::solve_with`], [`Puzzle::solve_with_stats`] and
+    /// [`Puzzle::solve_moves_with`] bound their memory.
+    pub memory_bound: Option<usize>,
+    /// What the search aims for. From the original grid, with no corners locked, the
+    /// tile presses reaching goal colors are the same either way, so only
+    /// [`Puzzle::solve_moves_with`], which searches from the current state, reads it.
+    pub win_check: WinCheck,
+    /// Which of several equally short solutions is returned.
+    pub tie_break: TieBreak,
+}
+
+/// The order in which presses are tried, deciding which of several equally short
+/// solutions a search returns. Each tile is ranked by the order, and the solution
+/// returned is the one whose presses' ranks come first in dictionary order, so it stays
+/// the same however the search itself changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Tiles by keypad number, from 1 at the bottom left to 9 at the top right
+    #[default]
+    Keypad,
+    /// The corner tiles by keypad number, then the rest by keypad number
+    CornersFirst,
+    /// Tiles in reading order, from the top left to the bottom right, as in notation
+    Reading,
+}
+
+impl TieBreak {
+    /// Every tile as a row and column, in the order presses are tried.
+    pub fn order<const ROWS: usize, const COLS: usize>(self) -> Vec<(usize, usize)> {
+        let keypad = (0..ROWS).flat_map(|row| (0..COLS).map(move |col| (row, col)));
+        match self {
+            TieBreak::Keypad => keypad.collect(),
+            TieBreak::CornersFirst => {
+                let corners = Grid::<ROWS, COLS>::corner_tiles();
+                let (mut order, rest): (Vec<_>, Vec<_>) =
+                    keypad.partition(|tile| corners.contains(tile));
+                order.extend(rest);
+                order
+            }
+            TieBreak::Reading => (0..ROWS)
+                .rev()
+                .flat_map(|row| (0..COLS).map(move |col| (row, col)))
+                .collect(),
+        }
+    }
+}
+
+/// The most presses a memory-bounded search looks for a solution within, far more than
+/// any puzzle in the game needs.
+pub const BOUNDED_MAX_PRESSES: usize = 64;
+
+/// How many states are visited between checks of the clock.
+const CLOCK_INTERVAL: usize = 1024;
+
+/// Checks a search against its limits as it goes.
+pub(crate) struct Limits {
+    max_states: Option<usize>,
+    deadline: Option<Instant>,
+    pub(crate) visited: usize,
+    /// The most states held at once so far
+    peak: usize,
+    /// States a memory bound made the search drop
+    dropped: usize,
+}
+
+impl Limits {
+    pub(crate) fn start(options: &SolveOptions) -> Self {
+        Self {
+            max_states: options.max_states,
+            // The clock is only read when there is a time limit, as some targets have none
+            deadline: options.time_limit.map(|limit| Instant::now() + limit),
+            visited: 0,
+            peak: 0,
+            dropped: 0,
+        }
+    }
+
+    /// How much work the search has done so far.
+    pub(crate) fn stats(&self) -> SearchStats {
+        SearchStats {
+            visited: self.visited,
+            peak_states: self.peak,
+            dropped: self.dropped,
+        }
+    }
+
+    /// Counts a visited state, failing if the search holding `states` states has gone
+    /// past its limits.
+    pub(crate) fn visit(&mut self, states: usize) -> Result<(), SolveError> {
+        self.peak = self.peak.max(states);
+        if self
+            .max_states
+            .is_some_and(|max_states| states > max_states)
+        {
+            return Err(SolveError::StateLimitReached);
+        }
+        if let Some(deadline) = self.deadline
+            && self.visited.is_multiple_of(CLOCK_INTERVAL)
+            && Instant::now() >= deadline
+        {
+            return Err(SolveError::TimeLimitReached);
+        }
+        self.visited += 1;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    /// The search needed more states than `SolveOptions::max_states` allows
+    StateLimitReached,
+    /// The search ran for longer than `SolveOptions::time_limit` allows
+    TimeLimitReached,
+    /// The search was stopped by its progress callback
+    Cancelled,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::StateLimitReached => write!(f, "search exceeded its state limit"),
+            SolveError::TimeLimitReached => write!(f, "search exceeded its time limit"),
+            SolveError::Cancelled => write!(f, "search was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// A solution found by the solver, with the state it leaves the puzzle in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The tile presses, in order
+    pub presses: Vec<(usize, usize)>,
+    /// The grid once every press is made, whose corner tiles show their goal colors
+    pub grid: Grid<ROWS, COLS>,
+    /// Which corners are still locked once every press is made, in goal order (NW, NE,
+    /// SW, SE). Only [`Puzzle::solve_current`] starts with corners locked; the rest
+    /// are pressed to finish.
+    pub locked: [bool; 4],
+    pub stats: SearchStats,
+}
+
+/// How much work a search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of states whose presses were tried
+    pub visited: usize,
+    /// The most states held in memory at once
+    pub peak_states: usize,
+    /// The states a [`SolveOptions::memory_bound`] made the search drop. If any were,
+    /// the solution found may be longer than optimal, and finding none does not mean
+    /// there is none.
+    pub dropped: usize,
+}
+
+/// A rough estimate of the bytes a search holds for each state, for turning a memory
+/// budget into a [`SolveOptions::memory_bound`] and [`SearchStats::peak_states`] back
+/// into memory.
+pub const fn state_bytes<const ROWS: usize, const COLS: usize>() -> usize {
+    // Each grid is held once among the states seen and once waiting with its path, of
+    // a dozen or so presses, and hash tables keep about an eighth of their room spare
+    let held = 2 * size_of::<Grid<ROWS, COLS>>()
+        + size_of::<Vec<(usize, usize)>>()
+        + 12 * size_of::<(usize, usize)>();
+    held + held / 8
+}
+
+/// How far a search has got, reported as it starts on each layer of states, those
+/// reached with one more press than the last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchProgress<'a, const ROWS: usize = 3, const COLS: usize = 3> {
+    /// The presses made to reach states of this layer
+    pub depth: usize,
+    /// The number of states visited so far
+    pub visited: usize,
+    /// The number of states waiting to be visited
+    pub queued: usize,
+    /// The first state of the layer
+    pub grid: &'a Grid<ROWS, COLS>,
+}
+
+impl<const ROWS: usize, const COLS: usize> Solution<ROWS, COLS> {
+    /// The moves which finish the puzzle: the tile presses, then a press of each corner
+    /// left unlocked.
+    pub fn moves(&self) -> Vec<Move> {
+        let presses = self.presses.iter().map(|&(row, col)| Move::Tile(row, col));
+        let corners = [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+            .into_iter()
+            .zip(self.locked)
+            .filter(|&(_, locked)| !locked)
+            .map(|(corner, _)| Move::Corner(corner));
+        presses.chain(corners).collect()
+    }
+}
+
+/// Search for a solution's presses within the given limits.
+pub(crate) fn solve_with<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    options: &SolveOptions,
+) -> Result<Option<Vec<(usize, usize)>>, SolveError> {
+    let mut limits = Limits::start(options);
+    let solution = search(goals, grid, rules, options, &mut limits, &mut |_| {
+        ControlFlow::Continue(())
+    })?;
+    Ok(solution.map(|solution| solution.presses))
+}
+
+/// Search for a solution within the given limits, checked and counted by `limits`,
+/// calling `progress` as each layer of the search starts and stopping if it breaks.
+/// Memory-bounded searches do not report progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err(level = "debug"))
+)]
+fn search<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    options: &SolveOptions,
+    limits: &mut Limits,
+    progress: &mut dyn FnMut(SearchProgress<'_, ROWS, COLS>) -> ControlFlow<()>,
+) -> Result<Option<Solution<ROWS, COLS>>, SolveError> {
+    if let Some(bound) = options.memory_bound {
+        return solve_bounded(goals, grid, rules, bound, options.tie_break, limits);
+    }
+
+    let context = GridGoals::new(*goals, rules.clone(), options.tie_break);
+    let found = search_hashed::<_, BuildGridHasher>(
+        grid.clone(),
+        &context,
+        limits,
+        &mut |depth, visited, queued, grid| {
+            progress(SearchProgress {
+                depth,
+                visited,
+                queued,
+                grid,
+            })
+        },
+    )?;
+    Ok(found.map(|found| Solution {
+        presses: found.moves,
+        grid: found.state,
+        locked: [false; 4],
+        stats: limits.stats(),
+    }))
+}
+
+/// Search for a solution holding at most `bound` states, layer by layer. Each layer's
+/// states are kept until memory runs short, when the oldest layers are forgotten first,
+/// which only costs searching their states again. Once only the layer being searched is
+/// left, states past the bound are dropped, which can lose the optimal solution.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err(level = "debug"))
+)]
+fn solve_bounded<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    bound: usize,
+    tie_break: TieBreak,
+    limits: &mut Limits,
+) -> Result<Option<Solution<ROWS, COLS>>, SolveError> {
+    type Layer<const ROWS: usize, const COLS: usize> = HashSet<Grid<ROWS, COLS>, BuildGridHasher>;
+
+    if grid.is_solved(goals) {
+        return Ok(Some(Solution {
+            presses: vec![],
+            grid: grid.clone(),
+            locked: [false; 4],
+            stats: SearchStats::default(),
+        }));
+    }
+
+    let mut layer = vec![(grid.clone(), vec![])];
+    // The states of recent layers, oldest first, and how many they hold together
+    let mut recent: VecDeque<Layer<ROWS, COLS>> =
+        VecDeque::from([Layer::from_iter([grid.clone()])]);
+    let mut held = 1;
+    let order = tie_break.order::<ROWS, COLS>();
+
+    for _ in 0..BOUNDED_MAX_PRESSES {
+        let mut next = vec![];
+        let mut next_seen: Layer<ROWS, COLS> = Default::default();
+        for (grid, path) in &layer {
+            for &(row, col) in &order {
+                let new_grid = grid.press_with(rules, row, col);
+                if next_seen.contains(&new_grid)
+                    || recent.iter().any(|seen| seen.contains(&new_grid))
+                {
+                    continue;
+                }
+                limits.visit(held)?;
+
+                let mut new_path = path.clone();
+                new_path.push((row, col));
+                if new_grid.is_solved(goals) {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        presses = new_path.len(),
+                        dropped = limits.dropped,
+                        "found solution"
+                    );
+                    return Ok(Some(Solution {
+                        presses: new_path,
+                        grid: new_grid,
+                        locked: [false; 4],
+                        stats: limits.stats(),
+                    }));
+                }
+
+                while held >= bound && recent.len() > 1 {
+                    held -= recent.pop_front().map_or(0, |seen| seen.len());
+                }
+                if held >= bound {
+                    limits.dropped += 1;
+                    continue;
+                }
+                held += 1;
+                next_seen.insert(new_grid.clone());
+                next.push((new_grid, new_path));
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            depth = next[0].1.len(),
+            held,
+            layers = recent.len() + 1,
+            "searching next layer"
+        );
+        recent.push_back(next_seen);
+        layer = next;
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(dropped = limits.dropped, "no solution found");
+    Ok(None)
+}
+
+/// Search for the fewest moves, tile and corner presses alike, which lock every corner
+/// from `grid` with `locked` corners already locked, in goal order. Pressing a corner
+/// whose tile does not match its goal resets the grid to `original` and unlocks every
+/// corner, which can be the quickest way on part way through play.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err(level = "debug"))
+)]
+fn solve_locked<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    original: &Grid<ROWS, COLS>,
+    (grid, locked): (&Grid<ROWS, COLS>, [bool; 4]),
+    rules: &RuleSet<ROWS, COLS>,
+    options: &SolveOptions,
+) -> Result<Option<Vec<Move>>, SolveError> {
+    type State<const ROWS: usize, const COLS: usize> = (Grid<ROWS, COLS>, [bool; 4]);
+
+    let corners = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+    let tiles = Grid::<ROWS, COLS>::corner_tiles();
+    let order = options.tie_break.order::<ROWS, COLS>();
+
+    let start: State<ROWS, COLS> = (grid.clone(), locked);
+    let mut queue = VecDeque::from([(start.clone(), vec![])]);
+    let mut seen: HashSet<State<ROWS, COLS>, BuildGridHasher> = HashSet::from_iter([start]);
+    let mut limits = Limits::start(options);
+
+    while let Some(((grid, locked), path)) = queue.pop_front() {
+        if locked.iter().all(|&locked| locked) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(moves = path.len(), visited = seen.len(), "found solution");
+            return Ok(Some(path));
+        }
+        limits.visit(seen.len() + queue.len())?;
+
+        let tile_presses = order.iter().map(|&(row, col)| Move::Tile(row, col));
+        for action in tile_presses.chain(corners.map(Move::Corner)) {
+            let next = match action {
+                Move::Tile(row, col) => {
+                    let new_grid = grid.press_with(rules, row, col);
+                    // Corners stay locked only while their tile keeps its goal color
+                    let still_locked = std::array::from_fn(|i| {
+                        let (row, col) = tiles[i];
+                        locked[i] && *new_grid.get(row, col) == goals[i]
+                    });
+                    (new_grid, still_locked)
+                }
+                Move::Corner(corner) => {
+                    let i = corners.iter().position(|&c| c == corner).unwrap();
+                    let (row, col) = tiles[i];
+                    if *grid.get(row, col) == goals[i] {
+                        let mut now_locked = locked;
+                        now_locked[i] = true;
+                        (grid.clone(), now_locked)
+                    } else {
+                        (original.clone(), [false; 4])
+                    }
+                }
+            };
+            if seen.insert(next.clone()) {
+                let mut new_path = path.clone();
+                new_path.push(action);
+                queue.push_back((next, new_path));
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(visited = seen.len(), "no solution");
+    Ok(None)
+}
+
+/// The number of presses in a shortest solution, or None if no solution exists.
+///
+/// Unlike [`solve`], no path is kept for each state, and the search stops as soon as a
+/// solved grid is reached rather than when it is dequeued.
+pub(crate) fn solution_length<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+) -> Option<usize> {
+    search_length(goals, grid, rules, None, usize::MAX)
+}
+
+/// The number of presses in a shortest solution of at most `max_presses` presses.
+#[cfg(feature = "rand")]
+pub(crate) fn solution_length_within<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    max_presses: usize,
+) -> Option<usize> {
+    search_length(goals, grid, rules, None, max_presses)
+}
+
+/// Whether every solution of at most `presses` presses presses a tile of `color`, such
+/// as to check that every optimal solution does.
+#[cfg(feature = "rand")]
+pub(crate) fn needs_color<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    color: Color,
+    presses: usize,
+) -> bool {
+    search_length(goals, grid, rules, Some(color), presses).is_none()
+}
+
+/// A path-free search for the length of a shortest solution of at most `max_presses`
+/// presses, never pressing tiles of the `avoid` color.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+fn search_length<const ROWS: usize, const COLS: usize>(
+    goals: &[Color; 4],
+    grid: &Grid<ROWS, COLS>,
+    rules: &RuleSet<ROWS, COLS>,
+    avoid: Option<Color>,
+    max_presses: usize,
+) -> Option<usize> {
+    if grid.is_solved(goals) {
+        return Some(0);
+    }
+
+    let mut seen: HashSet<Grid<ROWS, COLS>, BuildGridHasher> = Default::default();
+    seen.insert(grid.clone());
+    let mut layer = vec![grid.clone()];
+    let mut depth = 0;
+
+    while !layer.is_empty() && depth < max_presses {
+        depth += 1;
+        let mut next = vec![];
+        for grid in &layer {
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    if avoid == Some(*grid.get(row, col)) {
+                        continue;
+                    }
+                    let new_grid = grid.press_with(rules, row, col);
+                    if !seen.insert(new_grid.clone()) {
+                        continue;
+                    }
+                    if new_grid.is_solved(goals) {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(presses = depth, visited = seen.len(), "found solution");
+                        return Some(depth);
+                    }
+                    next.push(new_grid);
+                }
+            }
+        }
+        layer = next;
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(visited = seen.len(), "no solution");
+    None
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    pub fn solve(&self) -> Option<Solution<ROWS, COLS>> {
+        self.solve_with(&SolveOptions::default())
+            .expect("unlimited searches always finish")
+    }
+
+    /// Whether the puzzle has a solution, without building one. This is faster than
+    /// [`Puzzle::solve`] when only a yes or no is needed, such as when validating
+    /// puzzles sent by others.
+    pub fn is_solvable(&self) -> bool {
+        solution_length(&self.goals, &self.original, &self.rules).is_some()
+    }
+
+    /// Search for a solution within the given limits.
+    pub fn solve_with(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<Option<Solution<ROWS, COLS>>, SolveError> {
+        Ok(self.solve_with_stats(options)?.0)
+    }
+
+    /// Search like [`Puzzle::solve_with`], also returning how much work the search did
+    /// when it finds no solution, such as whether a memory bound made it drop states.
+    pub fn solve_with_stats(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<(Option<Solution<ROWS, COLS>>, SearchStats), SolveError> {
+        let mut limits = Limits::start(options);
+        let solution = search(
+            &self.goals,
+            &self.original,
+            &self.rules,
+            options,
+            &mut limits,
+            &mut |_| ControlFlow::Continue(()),
+        )?;
+        Ok((solution, limits.stats()))
+    }
+
+    /// Search for a solution from the current grid rather than the original, such as
+    /// for hints part way through play. Locked corners do not need to stay locked, as
+    /// they can be locked again once every corner tile matches its goal.
+    pub fn solve_current(&self) -> Option<Solution<ROWS, COLS>> {
+        self.solve_current_reporting(&SolveOptions::default(), |_| ControlFlow::Continue(()))
+            .expect("unlimited searches always finish")
+    }
+
+    /// Search like [`Puzzle::solve_current`] within the given limits, calling `progress`
+    /// as the search starts on each layer of states, such as to show the solver at work.
+    /// The search stops with [`SolveError::Cancelled`] if `progress` breaks, such as once
+    /// no one is watching.
+    pub fn solve_current_reporting(
+        &self,
+        options: &SolveOptions,
+        mut progress: impl FnMut(SearchProgress<'_, ROWS, COLS>) -> ControlFlow<()>,
+    ) -> Result<Option<Solution<ROWS, COLS>>, SolveError> {
+        let Some(mut solution) = search(
+            &self.goals,
+            self.current_state(),
+            &self.rules,
+            options,
+            &mut Limits::start(options),
+            &mut progress,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        let mut finished = self.clone();
+        for &(row, col) in &solution.presses {
+            finished.press_tile(row, col);
+        }
+        solution.locked = [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+            .map(|corner| finished.get_corner(corner) == finished.goal(corner));
+        Ok(Some(solution))
+    }
+
+    /// Search for the fewest moves which solve the puzzle from the current state by
+    /// `options.win_check`, within the given limits. For [`WinCheck::GoalColors`] these
+    /// are tile presses alone, and for [`WinCheck::CornersLocked`] they include the
+    /// corner presses, which may reset the puzzle if that is quicker. Only searches for
+    /// goal colors bound their memory.
+    pub fn solve_moves_with(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<Option<Vec<Move>>, SolveError> {
+        match options.win_check {
+            WinCheck::GoalColors => {
+                let presses = solve_with(&self.goals, self.current_state(), &self.rules, options)?;
+                Ok(presses.map(|presses| {
+                    presses
+                        .into_iter()
+                        .map(|(row, col)| Move::Tile(row, col))
+                        .collect()
+                }))
+            }
+            WinCheck::CornersLocked => {
+                let locked = [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+                    .map(|corner| self.get_corner(corner) == self.goal(corner));
+                solve_locked(
+                    &self.goals,
+                    &self.original,
+                    (self.current_state(), locked),
+                    &self.rules,
+                    options,
+                )
+            }
+        }
+    }
+
+    /// An optimal way to finish the puzzle from the current state: tile presses followed
+    /// by locking each corner not already locked. Returns `None` if the puzzle cannot be
+    /// solved from here.
+    pub fn remaining_moves(&self) -> Option<Vec<Move>> {
+        Some(self.solve_current()?.moves())
+    }
+
+    /// The moves which make tile presses from the current state, such as those of a
+    /// solution, and then lock each corner left unlocked.
+    pub fn moves_for(&self, presses: &[(usize, usize)]) -> Vec<Move> {
+        // Presses can unlock corners, so check which need locking once they are made
+        let mut finished = self.clone();
+        let mut moves = vec![];
+        for &(row, col) in presses {
+            finished.press_tile(row, col);
+            moves.push(Move::Tile(row, col));
+        }
+        moves.extend(
+            [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+                .into_iter()
+                .filter(|&corner| finished.get_corner(corner) != finished.goal(corner))
+                .map(Move::Corner),
+        );
+        moves
+    }
+
+    /// The moves which make tile presses from the current state, such as those of a
+    /// solution, with each corner pressed as soon as its tile matches its goal for the
+    /// rest of the presses, so no later press unlocks it. Corners already locked which
+    /// stay locked are not pressed again, and corners whose tile does not end up
+    /// matching are left out, as pressing them would reset the puzzle.
+    pub fn schedule_corners(&self, presses: &[(usize, usize)]) -> Vec<Move> {
+        let mut grids = vec![self.current_state().clone()];
+        for &(row, col) in presses {
+            let grid = grids[grids.len() - 1].press_with(&self.rules, row, col);
+            grids.push(grid);
+        }
+
+        // The number of presses after which each corner can be pressed, if ever
+        let corners = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+        let tiles = Grid::<ROWS, COLS>::corner_tiles();
+        let press_after: [Option<usize>; 4] = std::array::from_fn(|i| {
+            let ((row, col), goal) = (tiles[i], self.goals[i]);
+            let matching = grids
+                .iter()
+                .rev()
+                .take_while(|grid| *grid.get(row, col) == goal)
+                .count();
+            let safe_after = (matching > 0).then(|| grids.len() - matching);
+            let stays_locked = self.get_corner(corners[i]) == goal && safe_after == Some(0);
+            safe_after.filter(|_| !stays_locked)
+        });
+
+        let mut moves = vec![];
+        for i in 0..=presses.len() {
+            moves.extend(
+                corners
+                    .into_iter()
+                    .zip(press_after)
+                    .filter(|&(_, after)| after == Some(i))
+                    .map(|(corner, _)| Move::Corner(corner)),
+            );
+            if let Some(&(row, col)) = presses.get(i) {
+                moves.push(Move::Tile(row, col));
+            }
+        }
+        moves
+    }
+
+    /// The next move of an optimal solution from the current state: a tile press, or
+    /// once every corner tile matches its goal, a corner which still needs locking.
+    /// Returns `None` if the puzzle is already solved or cannot be solved from here.
+    pub fn hint(&self) -> Option<Move> {
+        if self.is_solved() {
+            return None;
+        }
+        self.remaining_moves()?.first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search for the presses of a solution without limits.
+    fn solve<const ROWS: usize, const COLS: usize>(
+        goals: &[Color; 4],
+        grid: &Grid<ROWS, COLS>,
+        rules: &RuleSet<ROWS, COLS>,
+    ) -> Option<Vec<(usize, usize)>> {
+        solve_with(goals, grid, rules, &SolveOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn solve_works() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+
+        let solution = solve(&[Color::White; 4], &grid, &RuleSet::standard());
+
+        assert_eq!(Some(vec![(0, 2), (0, 1)]), solution);
+    }
+
+    #[test]
+    fn solve_works_on_larger_grid() {
+        let grid = Grid::<4, 4>::from_row_array([
+            [Color::Gray, Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray, Color::Yellow],
+        ]);
+
+        let solution = solve(
+            &[Color::Gray, Color::Gray, Color::Gray, Color::Yellow],
+            &grid,
+            &RuleSet::standard(),
+        );
+        assert_eq!(Some(vec![]), solution);
+
+        let solution = solve(
+            &[Color::Gray, Color::Yellow, Color::Gray, Color::Gray],
+            &grid,
+            &RuleSet::standard(),
+        );
+        assert_eq!(Some(vec![(0, 3), (1, 3), (2, 3)]), solution);
+    }
+
+    #[test]
+    fn solve_current_starts_from_the_current_grid() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let mut puzzle = Puzzle::new([Color::White; 4], grid);
+
+        puzzle.press_tile(0, 2);
+        let current = puzzle.solve_current().unwrap();
+        assert_eq!(current.presses, [(0, 1)]);
+        assert_eq!(current.grid, puzzle.current_state().press(0, 1));
+        assert_eq!(puzzle.solve().unwrap().presses, [(0, 2), (0, 1)]);
+
+        // Corners locked beforehand which stay locked are not pressed again
+        puzzle.press_corner(Corner::NW);
+        let current = puzzle.solve_current().unwrap();
+        assert_eq!(current.locked, [true, false, false, false]);
+        assert_eq!(
+            current.moves(),
+            [
+                Move::Tile(0, 1),
+                Move::Corner(Corner::NE),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_is_reported_for_each_layer() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let puzzle = Puzzle::new([Color::White; 4], grid.clone());
+
+        let mut layers = vec![];
+        let options = SolveOptions::default();
+        let solution = puzzle.solve_current_reporting(&options, |progress| {
+            layers.push((progress.depth, progress.visited, progress.grid.clone()));
+            ControlFlow::Continue(())
+        });
+        assert_eq!(solution.unwrap().unwrap().presses.len(), 2);
+        let depths: Vec<usize> = layers.iter().map(|(depth, ..)| *depth).collect();
+        assert_eq!(depths, [0, 1, 2]);
+        assert_eq!(layers[0], (0, 1, grid));
+        assert!(layers[1].1 < layers[2].1);
+
+        let cancelled = puzzle.solve_current_reporting(&options, |progress| {
+            if progress.depth < 1 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        assert_eq!(cancelled, Err(SolveError::Cancelled));
+    }
+
+    #[test]
+    fn ties_break_by_the_chosen_order() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::Gray, Color::White, Color::Gray],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        let optimal = puzzle.solutions(100, 0);
+        assert!(optimal.len() > 1);
+
+        for tie_break in [TieBreak::Keypad, TieBreak::CornersFirst, TieBreak::Reading] {
+            let options = SolveOptions {
+                tie_break,
+                ..Default::default()
+            };
+            let order = tie_break.order::<3, 3>();
+            let rank = |presses: &Vec<(usize, usize)>| -> Vec<usize> {
+                let rank = |press| order.iter().position(|&tile| tile == press).unwrap();
+                presses.iter().map(|&press| rank(press)).collect()
+            };
+            let first = optimal.iter().min_by_key(|presses| rank(presses)).unwrap();
+
+            let solution = puzzle.solve_with(&options).unwrap().unwrap();
+            assert_eq!(&solution.presses, first, "{tie_break:?}");
+            let (counted, _) = puzzle.solve_counting_with(&options).unwrap().unwrap();
+            assert_eq!(&counted, first, "{tie_break:?}");
+        }
+        assert_eq!(puzzle.solve().unwrap().presses, [(0, 2), (0, 1)]);
+        let reading = SolveOptions {
+            tie_break: TieBreak::Reading,
+            ..Default::default()
+        };
+        let solution = puzzle.solve_with(&reading).unwrap().unwrap();
+        assert_eq!(solution.presses, [(2, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn hint_finishes_with_corners() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let mut puzzle = Puzzle::new([Color::White; 4], grid);
+        assert_eq!(puzzle.hint(), Some(Move::Tile(0, 2)));
+        assert_eq!(
+            puzzle.remaining_moves().unwrap(),
+            [
+                Move::Tile(0, 2),
+                Move::Tile(0, 1),
+                Move::Corner(Corner::NW),
+                Move::Corner(Corner::NE),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ]
+        );
+
+        puzzle.press_tile(0, 2);
+        puzzle.press_tile(0, 1);
+        assert_eq!(puzzle.hint(), Some(Move::Corner(Corner::NW)));
+        puzzle.press_corner(Corner::NW);
+        assert_eq!(puzzle.hint(), Some(Move::Corner(Corner::NE)));
+    }
+
+    #[test]
+    fn remaining_moves_solve_the_puzzle() {
+        let grid = Grid::from_rows(
+            [Color::Green, Color::Gray, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Yellow, Color::Gray, Color::White],
+        );
+        let goals = [Color::Green, Color::White, Color::Yellow, Color::White];
+        let mut puzzle = Puzzle::new(goals, grid);
+
+        for action in puzzle.remaining_moves().unwrap() {
+            puzzle.apply(action);
+        }
+        assert!(puzzle.is_solved());
+        assert_eq!(puzzle.remaining_moves(), Some(vec![]));
+    }
+
+    #[test]
+    fn win_checks_target_colors_or_locks() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let mut puzzle = Puzzle::new([Color::White; 4], grid);
+        let colors = SolveOptions::default();
+        let locks = SolveOptions {
+            win_check: WinCheck::CornersLocked,
+            ..Default::default()
+        };
+
+        puzzle.press_tile(0, 2);
+        puzzle.press_tile(0, 1);
+        assert!(puzzle.is_won(WinCheck::GoalColors));
+        assert!(!puzzle.is_won(WinCheck::CornersLocked));
+        assert_eq!(puzzle.solve_moves_with(&colors), Ok(Some(vec![])));
+        assert_eq!(
+            puzzle.solve_moves_with(&locks),
+            Ok(Some(vec![
+                Move::Corner(Corner::NW),
+                Move::Corner(Corner::NE),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ]))
+        );
+
+        // Pressing the center scatters the white tiles, which a reset undoes in one move
+        for _ in 0..3 {
+            puzzle.press_tile(1, 1);
+        }
+        let moves = puzzle.solve_moves_with(&locks).unwrap().unwrap();
+        assert!(moves.len() <= 7);
+        for action in moves {
+            puzzle.apply(action);
+        }
+        assert!(puzzle.is_won(WinCheck::CornersLocked));
+    }
+
+    #[test]
+    fn solution_length_agrees_with_solve() {
+        let rules = RuleSet::standard();
+        let grids = [
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+            Grid::from_rows(
+                [Color::Green, Color::Gray, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Yellow, Color::Gray, Color::White],
+            ),
+            Grid::from_rows(
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Black, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+            ),
+        ];
+        for grid in &grids {
+            for goals in [
+                [Color::White; 4],
+                [Color::Green, Color::White, Color::Yellow, Color::White],
+                [Color::Gray; 4],
+            ] {
+                assert_eq!(
+                    solution_length(&goals, grid, &rules),
+                    solve(&goals, grid, &rules).map(|solution| solution.len()),
+                );
+            }
+        }
+
+        let unsolvable = Puzzle::new([Color::White; 4], grids[2].clone());
+        assert!(!unsolvable.is_solvable());
+        assert!(Puzzle::new([Color::Gray; 4], grids[2].clone()).is_solvable());
+    }
+
+    #[test]
+    fn corners_are_pressed_once_safe() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let mut puzzle = Puzzle::new([Color::White; 4], grid);
+        let solution = puzzle.solve().unwrap();
+        let moves = puzzle.schedule_corners(&solution.presses);
+        assert_eq!(
+            moves,
+            [
+                Move::Corner(Corner::NW),
+                Move::Corner(Corner::NE),
+                Move::Tile(0, 2),
+                Move::Tile(0, 1),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ]
+        );
+
+        // Locked corners which stay locked are skipped
+        puzzle.apply(Move::Corner(Corner::NW));
+        assert_eq!(puzzle.schedule_corners(&solution.presses), moves[1..]);
+        for action in moves[1..].iter().copied() {
+            puzzle.apply(action);
+        }
+        assert!(puzzle.is_solved());
+    }
+
+    #[test]
+    fn state_limit_stops_search() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let rules = RuleSet::standard();
+
+        let limited = SolveOptions {
+            max_states: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_with(&[Color::White; 4], &grid, &rules, &limited),
+            Err(SolveError::StateLimitReached)
+        );
+
+        let roomy = SolveOptions {
+            max_states: Some(10_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_with(&[Color::White; 4], &grid, &rules, &roomy),
+            Ok(Some(vec![(0, 2), (0, 1)]))
+        );
+    }
+
+    #[test]
+    fn bounded_memory_still_solves() {
+        let grid = Grid::from_rows(
+            [Color::Green, Color::Gray, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Yellow, Color::Gray, Color::White],
+        );
+        let goals = [Color::Green, Color::White, Color::Yellow, Color::White];
+        let puzzle = Puzzle::new(goals, grid.clone());
+        let optimal = puzzle.solve().unwrap();
+
+        let roomy = SolveOptions {
+            memory_bound: Some(100_000),
+            ..Default::default()
+        };
+        let bounded = puzzle.solve_with(&roomy).unwrap().unwrap();
+        assert_eq!(bounded.presses, optimal.presses);
+        assert_eq!(bounded.grid, optimal.grid);
+
+        for bound in [1, 4, 20] {
+            let tight = SolveOptions {
+                memory_bound: Some(bound),
+                ..Default::default()
+            };
+            if let Some(solution) = puzzle.solve_with(&tight).unwrap() {
+                assert!(solution.presses.len() >= optimal.presses.len());
+                crate::check::solution_verifies(&puzzle, &solution.presses).unwrap();
+            }
+        }
+
+        let unsolvable = Grid::from_rows(
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Black, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+        let bounded = SolveOptions {
+            memory_bound: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_with(
+                &[Color::White; 4],
+                &unsolvable,
+                &RuleSet::standard(),
+                &bounded
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn stats_tell_whether_a_bound_dropped_states() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let puzzle = Puzzle::new([Color::White; 4], grid);
+
+        let (solution, stats) = puzzle.solve_with_stats(&SolveOptions::default()).unwrap();
+        assert_eq!(solution.unwrap().stats, stats);
+        assert!(stats.peak_states > 0);
+        assert_eq!(stats.dropped, 0);
+
+        let tight = SolveOptions {
+            memory_bound: Some(2),
+            ..Default::default()
+        };
+        let (_, stats) = puzzle.solve_with_stats(&tight).unwrap();
+        assert!(stats.dropped > 0);
+        assert!(stats.peak_states <= 2);
+    }
+
+    #[test]
+    fn time_limit_stops_search() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let rules = RuleSet::standard();
+
+        let expired = SolveOptions {
+            time_limit: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_with(&[Color::White; 4], &grid, &rules, &expired),
+            Err(SolveError::TimeLimitReached)
+        );
+
+        let patient = SolveOptions {
+            time_limit: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_with(&[Color::White; 4], &grid, &rules, &patient),
+            Ok(Some(vec![(0, 2), (0, 1)]))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! The breadth-first search behind the solver, over any kind of state with moves
+//! between states, so that variants with their own grids, rules or goals can reuse it.
+
+use std::collections::{HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::ops::ControlFlow;
+
+use crate::solver::Limits;
+use crate::{Color, Corner, Grid, Move, Puzzle, RuleSet, SolveError, SolveOptions, TieBreak};
+
+/// A state of a puzzle, which a search looks for the fewest moves from to a goal.
+pub trait PuzzleState: Sized {
+    /// What every state of a search shares, such as the goals and rules
+    type Context;
+    /// A move from one state to another
+    type Move: Clone;
+    /// What tells states apart, so that each is searched once. States with equal keys
+    /// are taken to be the same.
+    type Key: Eq + Hash;
+
+    fn key(&self) -> Self::Key;
+
+    /// Each move from this state with the state it leads to, in the order they are
+    /// tried. Of several equally short solutions, a search returns the one whose moves
+    /// come first in this order.
+    fn successors(&self, context: &Self::Context) -> impl Iterator<Item = (Self::Move, Self)>;
+
+    fn is_goal(&self, context: &Self::Context) -> bool;
+}
+
+/// A goal state found by a search, with the fewest moves reaching it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S: PuzzleState> {
+    pub moves: Vec<S::Move>,
+    pub state: S,
+    /// The number of states whose moves were tried
+    pub visited: usize,
+}
+
+/// How far a search has got, as it starts on each layer of states: the moves made to
+/// reach the layer, the states visited and queued so far, and the layer's first state.
+/// Breaking stops the search.
+pub(crate) type Progress<'a, S> = &'a mut dyn FnMut(usize, usize, usize, &S) -> ControlFlow<()>;
+
+/// Searches breadth first from `start` for a goal state within the limits of `options`,
+/// returning `None` if no goal state can be reached. Only the state limit and time limit
+/// of `options` are read.
+pub fn search<S: PuzzleState>(
+    start: S,
+    context: &S::Context,
+    options: &SolveOptions,
+) -> Result<Option<Found<S>>, SolveError> {
+    let mut limits = Limits::start(options);
+    search_hashed::<S, RandomState>(start, context, &mut limits, &mut |_, _, _, _| {
+        ControlFlow::Continue(())
+    })
+}
+
+/// [`search`] within `limits`, keeping the keys of states seen in sets hashed by `H`,
+/// such as grids' own hasher, calling `progress` as each layer of the search starts.
+/// The search fails with [`SolveError::Cancelled`] if `progress` breaks.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, err(level = "debug"))
+)]
+pub(crate) fn search_hashed<S: PuzzleState, H: BuildHasher + Default>(
+    start: S,
+    context: &S::Context,
+    limits: &mut Limits,
+    progress: Progress<'_, S>,
+) -> Result<Option<Found<S>>, SolveError> {
+    let mut queue: VecDeque<(S, Vec<S::Move>)> = VecDeque::from([(start, vec![])]);
+    let mut seen: HashSet<S::Key, H> = Default::default();
+    let mut depth = None;
+
+    while let Some((state, path)) = queue.pop_front() {
+        if !seen.insert(state.key()) {
+            continue;
+        }
+
+        if depth != Some(path.len()) {
+            depth = Some(path.len());
+            let (visited, queued) = (seen.len(), queue.len());
+            #[cfg(feature = "tracing")]
+            tracing::trace!(depth = path.len(), visited, queued, "searching next layer");
+            if progress(path.len(), visited, queued, &state).is_break() {
+                return Err(SolveError::Cancelled);
+            }
+        }
+
+        if state.is_goal(context) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(moves = path.len(), visited = seen.len(), "found solution");
+            return Ok(Some(Found {
+                moves: path,
+                state,
+                visited: limits.visited,
+            }));
+        }
+
+        limits.visit(seen.len() + queue.len())?;
+
+        for (action, next) in state.successors(context) {
+            let mut new_path = path.clone();
+            new_path.push(action);
+            queue.push_back((next, new_path));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(visited = seen.len(), "no solution");
+    Ok(None)
+}
+
+/// The goals and rules a grid is searched under, with the order its presses are tried.
+#[derive(Clone)]
+pub struct GridGoals<const ROWS: usize = 3, const COLS: usize = 3> {
+    goals: [Color; 4],
+    rules: RuleSet<ROWS, COLS>,
+    order: Vec<(usize, usize)>,
+}
+
+impl<const ROWS: usize, const COLS: usize> GridGoals<ROWS, COLS> {
+    /// Goals in goal order (NW, NE, SW, SE), reached by pressing tiles under `rules` in
+    /// the order of `tie_break`.
+    pub fn new(goals: [Color; 4], rules: RuleSet<ROWS, COLS>, tie_break: TieBreak) -> Self {
+        Self {
+            goals,
+            rules,
+            order: tie_break.order::<ROWS, COLS>(),
+        }
+    }
+}
+
+/// Grids are moved between by pressing tiles, and reach their goal once every corner
+/// tile shows its goal color.
+impl<const ROWS: usize, const COLS: usize> PuzzleState for Grid<ROWS, COLS> {
+    type Context = GridGoals<ROWS, COLS>;
+    type Move = (usize, usize);
+    type Key = Self;
+
+    fn key(&self) -> Self {
+        self.clone()
+    }
+
+    fn successors(
+        &self,
+        context: &GridGoals<ROWS, COLS>,
+    ) -> impl Iterator<Item = ((usize, usize), Self)> {
+        context
+            .order
+            .iter()
+            .map(|&(row, col)| ((row, col), self.press_with(&context.rules, row, col)))
+    }
+
+    fn is_goal(&self, context: &GridGoals<ROWS, COLS>) -> bool {
+        self.is_solved(&context.goals)
+    }
+}
+
+/// Puzzles are moved between by pressing tiles, in keypad order, and then corners, and
+/// reach their goal once every corner is locked. Pressing a corner whose tile does not
+/// show its goal resets the puzzle, which can be the quickest way on.
+impl<const ROWS: usize, const COLS: usize> PuzzleState for Puzzle<ROWS, COLS> {
+    type Context = ();
+    type Move = Move;
+    /// The grid and corner colors, as the moves made to reach a state do not matter
+    type Key = (Grid<ROWS, COLS>, [Color; 4]);
+
+    fn key(&self) -> Self::Key {
+        (self.current_state().clone(), self.corners)
+    }
+
+    fn successors(&self, _: &()) -> impl Iterator<Item = (Move, Self)> {
+        let tiles = (0..ROWS).flat_map(|row| (0..COLS).map(move |col| Move::Tile(row, col)));
+        let corners = [Corner::NW, Corner::NE, Corner::SW, Corner::SE].map(Move::Corner);
+        tiles.chain(corners).map(|action| {
+            let mut next = self.clone();
+            next.apply(action);
+            (action, next)
+        })
+    }
+
+    fn is_goal(&self, _: &()) -> bool {
+        self.is_solved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_and_puzzles_search_alike() {
+        let grid = Grid::from_rows(
+            [Color::White, Color::White, Color::White],
+            [Color::White, Color::Gray, Color::White],
+            [Color::Gray, Color::Gray, Color::White],
+        );
+        let options = SolveOptions::default();
+
+        let goals = GridGoals::new([Color::White; 4], RuleSet::standard(), TieBreak::Keypad);
+        let found = search(grid.clone(), &goals, &options).unwrap().unwrap();
+        assert_eq!(found.moves, [(0, 2), (0, 1)]);
+        assert!(found.state.is_solved(&[Color::White; 4]));
+
+        let puzzle = Puzzle::new([Color::White; 4], grid);
+        let found = search(puzzle, &(), &options).unwrap().unwrap();
+        assert!(found.state.is_solved());
+        assert_eq!(
+            found.moves,
+            [
+                Move::Tile(0, 2),
+                Move::Tile(0, 1),
+                Move::Corner(Corner::NW),
+                Move::Corner(Corner::NE),
+                Move::Corner(Corner::SW),
+                Move::Corner(Corner::SE),
+            ]
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Endgame tablebases: the fewest presses from every grid of a few colors to every set
+//! of goals, worked out once and saved, so that solutions and hints for those grids are
+//! looked up rather than searched for.
+//!
+//! A tablebase is written as bytes by [`Tablebase::to_bytes`]: the magic `MJTB`, a
+//! version, the grid's rows and columns, the color codes of the tiles and goals, every
+//! grid as indices into those colors, and then a byte for each grid with each set of
+//! goals holding its distance, or 255 if it cannot be solved.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::enumerate::{PressGraph, UNSOLVED};
+use crate::puzzle::BuildGridHasher;
+use crate::{Color, Corner, Grid, Move, Puzzle, RuleSet, SearchStats, Solution, TieBreak};
+
+const MAGIC: &[u8; 4] = b"MJTB";
+const VERSION: u8 = 1;
+
+/// Marks grids which cannot be solved in the saved distances.
+const UNSOLVED_BYTE: u8 = u8::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TablebaseError {
+    /// The bytes do not start with the tablebase magic
+    NotATablebase,
+    UnsupportedVersion(u8),
+    /// The tablebase is for grids of another size
+    WrongSize {
+        rows: usize,
+        cols: usize,
+    },
+    /// A color code which is neither builtin nor registered
+    UnknownColor(char),
+    /// The bytes end early or hold indices out of range
+    Corrupt,
+}
+
+impl fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablebaseError::NotATablebase => write!(f, "not a tablebase"),
+            TablebaseError::UnsupportedVersion(version) => {
+                write!(f, "tablebase version {version} is not supported")
+            }
+            TablebaseError::WrongSize { rows, cols } => {
+                write!(f, "tablebase is for {rows}x{cols} grids")
+            }
+            TablebaseError::UnknownColor(code) => {
+                write!(f, "tablebase has unknown color code {code:?}")
+            }
+            TablebaseError::Corrupt => write!(f, "tablebase is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for TablebaseError {}
+
+/// The fewest presses solving every grid of some colors, with every set of goals of
+/// those colors other than gray, under one rule set.
+#[derive(Clone)]
+pub struct Tablebase<const ROWS: usize = 3, const COLS: usize = 3> {
+    rules: RuleSet<ROWS, COLS>,
+    /// The colors goals are drawn from, in the order goal sets are numbered by
+    goal_colors: Vec<Color>,
+    grids: Vec<Grid<ROWS, COLS>>,
+    index: HashMap<Grid<ROWS, COLS>, u32, BuildGridHasher>,
+    /// The fewest presses from each grid for each set of goals, or [`UNSOLVED_BYTE`],
+    /// one run of `grids.len()` for each set of goals
+    distances: Vec<u8>,
+}
+
+impl<const ROWS: usize, const COLS: usize> Tablebase<ROWS, COLS> {
+    /// Solves every grid whose tiles are colors of `rules`, and every grid pressing
+    /// them reaches, for every set of goals of those colors other than gray.
+    ///
+    /// As with [`Puzzle::enumerate`], this is only practical for two to four colors;
+    /// the rules can be narrowed to those first with [`RuleSet::retain_colors`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rules have no colors other than gray, if there are too many grids
+    /// to count, or if a grid takes more than 254 presses.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(rules)))]
+    pub fn build(rules: &RuleSet<ROWS, COLS>) -> Self {
+        let palette: Vec<Color> = rules.colors().collect();
+        let goal_colors: Vec<Color> = palette
+            .iter()
+            .copied()
+            .filter(|&color| color != Color::Gray)
+            .collect();
+        assert!(
+            !goal_colors.is_empty(),
+            "rule set has no colors to draw goals from"
+        );
+
+        let graph = PressGraph::of_colors(&palette, rules);
+        let mut distances = Vec::with_capacity(goal_colors.len().pow(4) * graph.grids.len());
+        for goal_set in 0..goal_colors.len().pow(4) {
+            let goals = goals_of(&goal_colors, goal_set);
+            distances.extend(graph.distances(&goals).into_iter().map(|distance| {
+                match distance {
+                    UNSOLVED => UNSOLVED_BYTE,
+                    distance => u8::try_from(distance)
+                        .ok()
+                        .filter(|&distance| distance != UNSOLVED_BYTE)
+                        .expect("no grid takes more than 254 presses"),
+                }
+            }));
+        }
+        Self::new(rules.clone(), goal_colors, graph.grids, distances)
+    }
+
+    fn new(
+        rules: RuleSet<ROWS, COLS>,
+        goal_colors: Vec<Color>,
+        grids: Vec<Grid<ROWS, COLS>>,
+        distances: Vec<u8>,
+    ) -> Self {
+        let index = grids
+            .iter()
+            .enumerate()
+            .map(|(i, grid)| (grid.clone(), i as u32))
+            .collect();
+        Self {
+            rules,
+            goal_colors,
+            grids,
+            index,
+            distances,
+        }
+    }
+
+    /// The number of grids in the table.
+    pub fn len(&self) -> usize {
+        self.grids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+
+    /// The colors goals are drawn from.
+    pub fn goal_colors(&self) -> &[Color] {
+        &self.goal_colors
+    }
+
+    /// The number of the set of goals in the table, if its colors are all in it.
+    fn goal_set(&self, goals: &[Color; 4]) -> Option<usize> {
+        goals.iter().rev().try_fold(0, |set, goal| {
+            let digit = self.goal_colors.iter().position(|color| color == goal)?;
+            Some(set * self.goal_colors.len() + digit)
+        })
+    }
+
+    /// The fewest presses solving `grid` for `goals`, `Some(None)` if it cannot be
+    /// solved, or `None` if the table does not hold the grid or goals.
+    pub fn distance(&self, goals: &[Color; 4], grid: &Grid<ROWS, COLS>) -> Option<Option<usize>> {
+        let set = self.goal_set(goals)?;
+        let &grid = self.index.get(grid)?;
+        let distance = self.distances[set * self.grids.len() + grid as usize];
+        Some((distance != UNSOLVED_BYTE).then_some(distance.into()))
+    }
+
+    /// An optimal solution from `grid` to `goals`, the first in the order of
+    /// `tie_break` as a search would find, `Some(None)` if it cannot be solved, or
+    /// `None` if the table does not hold the grid or goals, or was built with other
+    /// rules than it was loaded with.
+    pub fn solution(
+        &self,
+        goals: &[Color; 4],
+        grid: &Grid<ROWS, COLS>,
+        tie_break: TieBreak,
+    ) -> Option<Option<Vec<(usize, usize)>>> {
+        let Some(mut distance) = self.distance(goals, grid)? else {
+            return Some(None);
+        };
+        let order = tie_break.order::<ROWS, COLS>();
+        let mut grid = grid.clone();
+        let mut presses = vec![];
+        // Each step takes the first press bringing the goals one press closer
+        while distance > 0 {
+            let (press, next) = order
+                .iter()
+                .map(|&(row, col)| ((row, col), grid.press_with(&self.rules, row, col)))
+                .find(|(_, next)| self.distance(goals, next) == Some(Some(distance - 1)))?;
+            presses.push(press);
+            grid = next;
+            distance -= 1;
+        }
+        Some(Some(presses))
+    }
+
+    /// The table as bytes, to be read back by [`Tablebase::from_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grids have more than 255 colors between them.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut colors: Vec<Color> = vec![];
+        for grid in &self.grids {
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    if !colors.contains(grid.get(row, col)) {
+                        colors.push(*grid.get(row, col));
+                    }
+                }
+            }
+        }
+        for &color in &self.goal_colors {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+        let color_index = |color: &Color| -> u8 {
+            let index = colors
+                .iter()
+                .position(|c| c == color)
+                .expect("colors are listed");
+            u8::try_from(index).expect("at most 255 colors")
+        };
+
+        let mut bytes = MAGIC.to_vec();
+        let color_count = u8::try_from(colors.len()).expect("at most 255 colors");
+        bytes.extend([VERSION, ROWS as u8, COLS as u8, color_count]);
+        for color in &colors {
+            bytes.extend(u32::from(color.code()).to_le_bytes());
+        }
+        bytes.push(self.goal_colors.len() as u8);
+        bytes.extend(self.goal_colors.iter().map(color_index));
+        bytes.extend((self.grids.len() as u32).to_le_bytes());
+        for grid in &self.grids {
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    bytes.push(color_index(grid.get(row, col)));
+                }
+            }
+        }
+        bytes.extend(&self.distances);
+        bytes
+    }
+
+    /// Reads a table written by [`Tablebase::to_bytes`], which must have been built
+    /// with `rules`. Custom colors must be registered first.
+    pub fn from_bytes(bytes: &[u8], rules: RuleSet<ROWS, COLS>) -> Result<Self, TablebaseError> {
+        let mut reader = Reader { bytes };
+        if reader.take(4).ok() != Some(MAGIC.as_slice()) {
+            return Err(TablebaseError::NotATablebase);
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(TablebaseError::UnsupportedVersion(version));
+        }
+        let (rows, cols) = (reader.byte()?.into(), reader.byte()?.into());
+        if (rows, cols) != (ROWS, COLS) {
+            return Err(TablebaseError::WrongSize { rows, cols });
+        }
+
+        let colors = (0..reader.byte()?)
+            .map(|_| {
+                let code = u32::from_le_bytes(reader.array()?);
+                let code = char::from_u32(code).ok_or(TablebaseError::Corrupt)?;
+                Color::from_code(code).ok_or(TablebaseError::UnknownColor(code))
+            })
+            .collect::<Result<Vec<Color>, _>>()?;
+        let color = |index: u8| {
+            colors
+                .get(usize::from(index))
+                .copied()
+                .ok_or(TablebaseError::Corrupt)
+        };
+
+        let goal_colors = (0..reader.byte()?)
+            .map(|_| color(reader.byte()?))
+            .collect::<Result<Vec<Color>, _>>()?;
+        let grid_count = u32::from_le_bytes(reader.array()?) as usize;
+        let tiles = reader.take(
+            grid_count
+                .checked_mul(ROWS * COLS)
+                .ok_or(TablebaseError::Corrupt)?,
+        )?;
+        let grids = tiles
+            .chunks_exact(ROWS * COLS)
+            .map(|tiles| {
+                let tiles = tiles
+                    .iter()
+                    .map(|&index| color(index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Grid::from_fn(|row, col| tiles[row * COLS + col]))
+            })
+            .collect::<Result<Vec<_>, TablebaseError>>()?;
+
+        let distances = goal_colors
+            .len()
+            .pow(4)
+            .checked_mul(grid_count)
+            .ok_or(TablebaseError::Corrupt)?;
+        let distances = reader.take(distances)?.to_vec();
+        if !reader.bytes.is_empty() {
+            return Err(TablebaseError::Corrupt);
+        }
+        Ok(Self::new(rules, goal_colors, grids, distances))
+    }
+}
+
+/// Reads a tablebase's bytes from the front.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], TablebaseError> {
+        if self.bytes.len() < len {
+            return Err(TablebaseError::Corrupt);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, TablebaseError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TablebaseError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+}
+
+/// The goals numbered `set`, with NW's color its lowest digit in base
+/// `goal_colors.len()`.
+fn goals_of(goal_colors: &[Color], mut set: usize) -> [Color; 4] {
+    std::array::from_fn(|_| {
+        let color = goal_colors[set % goal_colors.len()];
+        set /= goal_colors.len();
+        color
+    })
+}
+
+impl<const ROWS: usize, const COLS: usize> Puzzle<ROWS, COLS> {
+    /// Solves like [`Puzzle::solve`], looking the solution up in `tablebase` if it holds
+    /// the puzzle and searching otherwise.
+    pub fn solve_with_tablebase(
+        &self,
+        tablebase: &Tablebase<ROWS, COLS>,
+    ) -> Option<Solution<ROWS, COLS>> {
+        let Some(presses) = tablebase.solution(&self.goals, &self.original, TieBreak::default())
+        else {
+            return self.solve();
+        };
+        let presses = presses?;
+        let grid = presses
+            .iter()
+            .fold(self.original.clone(), |grid, &(row, col)| {
+                grid.press_with(&self.rules, row, col)
+            });
+        Some(Solution {
+            presses,
+            grid,
+            locked: [false; 4],
+            stats: SearchStats::default(),
+        })
+    }
+
+    /// A hint like [`Puzzle::hint`], looked up in `tablebase` if it holds the current
+    /// grid and searched for otherwise.
+    pub fn hint_with(&self, tablebase: &Tablebase<ROWS, COLS>) -> Option<Move> {
+        if self.is_solved() {
+            return None;
+        }
+        let Some(presses) =
+            tablebase.solution(&self.goals, self.current_state(), TieBreak::default())
+        else {
+            return self.hint();
+        };
+        match presses?.first() {
+            Some(&(row, col)) => Some(Move::Tile(row, col)),
+            // The corner tiles show their goals, so what is left is locking them
+            None => [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+                .into_iter()
+                .find(|&corner| self.get_corner(corner) != self.goal(corner))
+                .map(Move::Corner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solution_length;
+
+    #[test]
+    fn lookups_agree_with_searches_after_a_round_trip() {
+        let mut rules: RuleSet = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White | Color::Black));
+        let tablebase = Tablebase::build(&rules);
+        let tablebase = Tablebase::from_bytes(&tablebase.to_bytes(), rules.clone()).unwrap();
+        assert_eq!(tablebase.goal_colors(), [Color::White, Color::Black]);
+
+        let grid = Grid::from_rows(
+            [Color::White, Color::Black, Color::White],
+            [Color::Gray, Color::White, Color::Gray],
+            [Color::Black, Color::Gray, Color::White],
+        );
+        for goals in [
+            [Color::White; 4],
+            [Color::Black, Color::White, Color::White, Color::Black],
+        ] {
+            let expected = solution_length(&goals, &grid, &rules);
+            assert_eq!(tablebase.distance(&goals, &grid), Some(expected));
+
+            let puzzle = Puzzle::with_rules(goals, grid.clone(), rules.clone());
+            let solution = puzzle.solve().map(|solution| solution.presses);
+            let looked_up = puzzle.solve_with_tablebase(&tablebase);
+            assert_eq!(looked_up.map(|solution| solution.presses), solution);
+            assert_eq!(puzzle.hint_with(&tablebase), puzzle.hint());
+        }
+
+        // Goals of colors the table lacks are left to the solver
+        assert_eq!(tablebase.distance(&[Color::Red; 4], &grid), None);
+        assert_eq!(
+            Tablebase::<3, 3>::from_bytes(b"MJTB\x09", rules).err(),
+            Some(TablebaseError::UnsupportedVersion(9))
+        );
+    }
+}
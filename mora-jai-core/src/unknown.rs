@@ -0,0 +1,200 @@
+//! Solving puzzles with tiles whose colors are not known, such as tiles too blurry to
+//! make out in a screenshot, by solving every puzzle they could be.
+
+use crate::{Color, Grid, Puzzle, RuleSet};
+#[cfg(feature = "solver")]
+use crate::{SolveError, SolveOptions};
+
+/// A puzzle some of whose tiles are not known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialPuzzle<const ROWS: usize = 3, const COLS: usize = 3> {
+    goals: [Color; 4],
+    /// The color of each tile, indexed by row and then column, or `None` if unknown
+    tiles: [[Option<Color>; COLS]; ROWS],
+    rules: RuleSet<ROWS, COLS>,
+}
+
+/// A puzzle unknown tiles could make up, with its optimal solution if it has one.
+#[cfg(feature = "solver")]
+type Completion<const ROWS: usize, const COLS: usize> =
+    (Puzzle<ROWS, COLS>, Option<Vec<(usize, usize)>>);
+
+/// What solves a puzzle with unknown tiles.
+#[cfg(feature = "solver")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartialSolution<const ROWS: usize = 3, const COLS: usize = 3> {
+    /// These presses solve the puzzle whatever colors the unknown tiles are
+    Universal(Vec<(usize, usize)>),
+    /// No solution found solves every puzzle the tiles could be, so each is listed with
+    /// its own optimal solution, or `None` if it has none
+    PerCompletion(Vec<Completion<ROWS, COLS>>),
+}
+
+impl<const ROWS: usize, const COLS: usize> PartialPuzzle<ROWS, COLS> {
+    /// A puzzle whose tiles, indexed by row and then column, are `None` where unknown.
+    pub fn new(
+        goals: [Color; 4],
+        tiles: [[Option<Color>; COLS]; ROWS],
+        rules: RuleSet<ROWS, COLS>,
+    ) -> Self {
+        Self {
+            goals,
+            tiles,
+            rules,
+        }
+    }
+
+    /// The goal colors in NW, NE, SW, SE order.
+    pub fn goals(&self) -> &[Color; 4] {
+        &self.goals
+    }
+
+    /// The color of a tile, or `None` if it is unknown.
+    pub fn tile(&self, row: usize, col: usize) -> Option<Color> {
+        self.tiles[row][col]
+    }
+
+    /// The unknown tiles as rows and columns, in keypad order.
+    pub fn unknowns(&self) -> Vec<(usize, usize)> {
+        (0..ROWS)
+            .flat_map(|row| (0..COLS).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.tiles[row][col].is_none())
+            .collect()
+    }
+
+    /// How many puzzles the tiles could make up, or `None` if too many to count. Each
+    /// unknown tile multiplies their number by the number of colors the rules know.
+    pub fn completion_count(&self) -> Option<usize> {
+        let unknowns = u32::try_from(self.unknowns().len()).ok()?;
+        self.rules.colors().count().checked_pow(unknowns)
+    }
+
+    /// Every puzzle the tiles could make up, with each unknown tile any color the rules
+    /// know, made as they are needed.
+    pub fn completions(&self) -> impl Iterator<Item = Puzzle<ROWS, COLS>> + '_ {
+        let colors: Vec<Color> = self.rules.colors().collect();
+        let unknowns = self.unknowns();
+        // The color of each unknown tile as an index into `colors`, counting up like an
+        // odometer, or `None` once every combination is made
+        let mut digits =
+            (!colors.is_empty() || unknowns.is_empty()).then(|| vec![0; unknowns.len()]);
+
+        std::iter::from_fn(move || {
+            let current = digits.as_mut()?;
+            let mut tiles = self.tiles;
+            for (&(row, col), &digit) in unknowns.iter().zip(current.iter()) {
+                tiles[row][col] = Some(colors[digit]);
+            }
+            let grid = Grid::from_fn(|row, col| tiles[row][col].expect("every tile is set"));
+
+            let turned = current.iter_mut().position(|digit| {
+                *digit = (*digit + 1) % colors.len();
+                *digit != 0
+            });
+            if turned.is_none() {
+                digits = None;
+            }
+            Some(Puzzle::with_rules(self.goals, grid, self.rules.clone()))
+        })
+    }
+
+    /// Solves every completion of the puzzle within the given limits, each searched
+    /// apart. If one of their optimal solutions solves every completion, the shortest
+    /// such is returned, and no solution for every completion is shorter, as none can
+    /// be shorter than any completion's optimal solution. Each unknown tile multiplies
+    /// the searches by the number of colors, so callers should keep to few.
+    #[cfg(feature = "solver")]
+    pub fn solve_with(
+        &self,
+        options: &SolveOptions,
+    ) -> Result<PartialSolution<ROWS, COLS>, SolveError> {
+        let mut solved = vec![];
+        for puzzle in self.completions() {
+            let solution = puzzle.solve_with(options)?;
+            solved.push((puzzle, solution.map(|solution| solution.presses)));
+        }
+
+        let mut candidates: Vec<&Vec<(usize, usize)>> = solved
+            .iter()
+            .filter_map(|(_, solution)| solution.as_ref())
+            .collect();
+        candidates.sort_by_key(|presses| presses.len());
+        let universal = candidates.into_iter().find(|presses| {
+            solved
+                .iter()
+                .all(|(puzzle, _)| solves(puzzle, presses.as_slice()))
+        });
+        Ok(match universal {
+            Some(presses) => PartialSolution::Universal(presses.clone()),
+            None => PartialSolution::PerCompletion(solved),
+        })
+    }
+}
+
+/// Whether pressing `presses` from a puzzle's original grid leaves its goal colors in
+/// the corners.
+#[cfg(feature = "solver")]
+fn solves<const ROWS: usize, const COLS: usize>(
+    puzzle: &Puzzle<ROWS, COLS>,
+    presses: &[(usize, usize)],
+) -> bool {
+    presses
+        .iter()
+        .fold(puzzle.original().clone(), |grid, &(row, col)| {
+            grid.press_with(puzzle.rules(), row, col)
+        })
+        .is_solved(puzzle.goals())
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_tiles_are_solved_for_every_color() {
+        let mut rules = RuleSet::standard();
+        rules.retain_colors(|color| matches!(color, Color::Gray | Color::White));
+        let tiles = [
+            [Some(Color::Gray), Some(Color::Gray), Some(Color::White)],
+            [Some(Color::White), Some(Color::Gray), Some(Color::White)],
+            [Some(Color::White), Some(Color::White), Some(Color::White)],
+        ];
+
+        // The middle tile is no corner, so the same presses solve whatever its color
+        let mut middle = tiles;
+        middle[1][1] = None;
+        let puzzle = PartialPuzzle::new([Color::White; 4], middle, rules.clone());
+        assert_eq!(puzzle.unknowns(), [(1, 1)]);
+        assert_eq!(puzzle.completion_count(), Some(2));
+        assert_eq!(puzzle.completions().count(), 2);
+        let options = SolveOptions::default();
+        assert_eq!(
+            puzzle.solve_with(&options),
+            Ok(PartialSolution::Universal(vec![(0, 2), (0, 1)]))
+        );
+
+        // A white corner is already solved, but a gray one needs pressing
+        let mut corner = tiles;
+        corner[0][0] = None;
+        let puzzle = PartialPuzzle::new([Color::White; 4], corner, rules);
+        let PartialSolution::PerCompletion(solved) = puzzle.solve_with(&options).unwrap() else {
+            panic!("no one solution should solve both");
+        };
+        assert_eq!(solved.len(), 2);
+        assert!(solved.iter().all(|(_, solution)| solution.is_some()));
+    }
+
+    #[test]
+    fn completions_are_made_as_needed() {
+        let rules = RuleSet::standard();
+        let colors = rules.colors().count();
+        let first_color = rules.colors().next().unwrap();
+        let puzzle = PartialPuzzle::new([Color::White; 4], [[None; 3]; 3], rules);
+        assert_eq!(puzzle.completion_count(), colors.checked_pow(9));
+
+        let first: Vec<_> = puzzle.completions().take(2).collect();
+        assert_eq!(first.len(), 2);
+        assert_ne!(first[0], first[1]);
+        assert_eq!(first[0].original(), &Grid::new([first_color; 9]));
+    }
+}
@@ -0,0 +1,69 @@
+/* C interface to the Mora Jai puzzle and solver. */
+
+#ifndef MORA_JAI_H
+#define MORA_JAI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MORA_JAI_OK 0
+#define MORA_JAI_INVALID_ARGUMENT (-1)
+#define MORA_JAI_NO_SOLUTION 1
+
+/* An opaque handle to a puzzle. */
+typedef struct MoraJaiPuzzle MoraJaiPuzzle;
+
+typedef enum MoraJaiCorner {
+    MORA_JAI_NW = 0,
+    MORA_JAI_NE = 1,
+    MORA_JAI_SW = 2,
+    MORA_JAI_SE = 3,
+} MoraJaiCorner;
+
+/* A sequence of presses: `moves` holds `len` row, column pairs, so `2 * len` bytes.
+ * Row 0 is the bottom row and column 0 the left column. */
+typedef struct MoraJaiSolution {
+    uint8_t *moves;
+    size_t len;
+} MoraJaiSolution;
+
+/* Parses a puzzle from its 13-character notation. Returns NULL if it is invalid. */
+MoraJaiPuzzle *mora_jai_puzzle_parse(const char *notation);
+
+/* Generates a random solvable puzzle. */
+MoraJaiPuzzle *mora_jai_puzzle_random(void);
+
+/* Releases a puzzle. Passing NULL does nothing. */
+void mora_jai_puzzle_free(MoraJaiPuzzle *puzzle);
+
+/* Presses a tile. Returns MORA_JAI_OK or MORA_JAI_INVALID_ARGUMENT. */
+int32_t mora_jai_puzzle_press_tile(MoraJaiPuzzle *puzzle, size_t row, size_t col);
+
+/* Presses a corner, one of the MoraJaiCorner values, locking it or resetting the
+ * puzzle. Returns MORA_JAI_OK, or MORA_JAI_INVALID_ARGUMENT for any other value. */
+int32_t mora_jai_puzzle_press_corner(MoraJaiPuzzle *puzzle, int32_t corner);
+
+/* Whether every corner is locked to its goal color. */
+bool mora_jai_puzzle_is_solved(const MoraJaiPuzzle *puzzle);
+
+/* The notation character of a tile, or 0 for invalid arguments. */
+uint32_t mora_jai_puzzle_get_tile(const MoraJaiPuzzle *puzzle, size_t row, size_t col);
+
+/* Solves the puzzle from its original grid. Returns MORA_JAI_OK with a solution which
+ * must be released with mora_jai_solution_free, MORA_JAI_NO_SOLUTION with an empty
+ * solution, or MORA_JAI_INVALID_ARGUMENT. */
+int32_t mora_jai_puzzle_solve(const MoraJaiPuzzle *puzzle, MoraJaiSolution *out);
+
+/* Releases a solution's moves and clears it. Passing NULL does nothing. */
+void mora_jai_solution_free(MoraJaiSolution *solution);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MORA_JAI_H */
@@ -0,0 +1,308 @@
+//! A C ABI for the puzzle and solver. See `include/mora_jai.h` for the matching header.
+//!
+//! Puzzles are handed out as opaque pointers which must be released with
+//! [`mora_jai_puzzle_free`], and solutions as flat arrays of row, column pairs which must
+//! be released with [`mora_jai_solution_free`].
+
+use std::ffi::{CStr, c_char};
+
+use mora_jai_core::{Corner, Puzzle, RuleSet};
+use mora_jai_io::notation;
+
+/// Return code for a successful call.
+pub const MORA_JAI_OK: i32 = 0;
+/// Return code for a call given a null pointer or an out of range argument.
+pub const MORA_JAI_INVALID_ARGUMENT: i32 = -1;
+/// Return code for a solve which found that no solution exists.
+pub const MORA_JAI_NO_SOLUTION: i32 = 1;
+
+/// An opaque handle to a puzzle.
+pub struct MoraJaiPuzzle {
+    inner: Puzzle,
+}
+
+/// The corners of the box, matching `MoraJaiCorner` in the header. C passes them as
+/// plain integers, checked with [`TryFrom`], as any other value would be undefined
+/// behavior for an enum.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum MoraJaiCorner {
+    NW = 0,
+    NE = 1,
+    SW = 2,
+    SE = 3,
+}
+
+impl TryFrom<i32> for MoraJaiCorner {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MoraJaiCorner::NW),
+            1 => Ok(MoraJaiCorner::NE),
+            2 => Ok(MoraJaiCorner::SW),
+            3 => Ok(MoraJaiCorner::SE),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<MoraJaiCorner> for Corner {
+    fn from(corner: MoraJaiCorner) -> Self {
+        match corner {
+            MoraJaiCorner::NW => Corner::NW,
+            MoraJaiCorner::NE => Corner::NE,
+            MoraJaiCorner::SW => Corner::SW,
+            MoraJaiCorner::SE => Corner::SE,
+        }
+    }
+}
+
+/// A sequence of presses. `moves` holds `len` row, column pairs, so `2 * len` bytes.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MoraJaiSolution {
+    pub moves: *mut u8,
+    pub len: usize,
+}
+
+/// Parses a puzzle from its null-terminated 13-character notation.
+///
+/// Returns null if the notation is invalid.
+///
+/// # Safety
+/// `notation` must be null or point to a valid null-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_parse(notation: *const c_char) -> *mut MoraJaiPuzzle {
+    if notation.is_null() {
+        return std::ptr::null_mut();
+    }
+    // SAFETY: the caller guarantees a valid null-terminated string
+    let notation = unsafe { CStr::from_ptr(notation) };
+    let Ok(notation) = notation.to_str() else {
+        return std::ptr::null_mut();
+    };
+
+    match notation::parse_puzzle(notation, &RuleSet::standard()) {
+        Some(inner) => Box::into_raw(Box::new(MoraJaiPuzzle { inner })),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Generates a random solvable puzzle.
+#[unsafe(no_mangle)]
+pub extern "C" fn mora_jai_puzzle_random() -> *mut MoraJaiPuzzle {
+    Box::into_raw(Box::new(MoraJaiPuzzle {
+        inner: Puzzle::new_random(),
+    }))
+}
+
+/// Releases a puzzle. Passing null does nothing.
+///
+/// # Safety
+/// `puzzle` must be null or a pointer returned by this library which has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_free(puzzle: *mut MoraJaiPuzzle) {
+    if !puzzle.is_null() {
+        // SAFETY: the caller guarantees the pointer came from Box::into_raw
+        drop(unsafe { Box::from_raw(puzzle) });
+    }
+}
+
+/// Presses the tile at the given row and column, where row 0 is the bottom row.
+///
+/// # Safety
+/// `puzzle` must be null or a valid puzzle pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_press_tile(
+    puzzle: *mut MoraJaiPuzzle,
+    row: usize,
+    col: usize,
+) -> i32 {
+    // SAFETY: the caller guarantees the pointer is null or valid
+    let Some(puzzle) = (unsafe { puzzle.as_mut() }) else {
+        return MORA_JAI_INVALID_ARGUMENT;
+    };
+    if row >= 3 || col >= 3 {
+        return MORA_JAI_INVALID_ARGUMENT;
+    }
+
+    puzzle.inner.press_tile(row, col);
+    MORA_JAI_OK
+}
+
+/// Presses a corner, one of the values of [`MoraJaiCorner`], locking it if its tile
+/// matches the goal or resetting the puzzle otherwise.
+///
+/// # Safety
+/// `puzzle` must be null or a valid puzzle pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_press_corner(
+    puzzle: *mut MoraJaiPuzzle,
+    corner: i32,
+) -> i32 {
+    // SAFETY: the caller guarantees the pointer is null or valid
+    let Some(puzzle) = (unsafe { puzzle.as_mut() }) else {
+        return MORA_JAI_INVALID_ARGUMENT;
+    };
+    let Ok(corner) = MoraJaiCorner::try_from(corner) else {
+        return MORA_JAI_INVALID_ARGUMENT;
+    };
+
+    puzzle.inner.press_corner(corner.into());
+    MORA_JAI_OK
+}
+
+/// Whether every corner is locked to its goal color. A null puzzle is never solved.
+///
+/// # Safety
+/// `puzzle` must be null or a valid puzzle pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_is_solved(puzzle: *const MoraJaiPuzzle) -> bool {
+    // SAFETY: the caller guarantees the pointer is null or valid
+    unsafe { puzzle.as_ref() }.is_some_and(|puzzle| puzzle.inner.is_solved())
+}
+
+/// The notation character of the tile at the given row and column, or 0 if the puzzle
+/// is null or the position is out of range.
+///
+/// # Safety
+/// `puzzle` must be null or a valid puzzle pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_get_tile(
+    puzzle: *const MoraJaiPuzzle,
+    row: usize,
+    col: usize,
+) -> u32 {
+    // SAFETY: the caller guarantees the pointer is null or valid
+    let Some(puzzle) = (unsafe { puzzle.as_ref() }) else {
+        return 0;
+    };
+    if row >= 3 || col >= 3 {
+        return 0;
+    }
+
+    notation::color_to_char(puzzle.inner.get_tile(row, col)) as u32
+}
+
+/// Solves the puzzle from its original grid, writing the presses to `out`.
+///
+/// Returns `MORA_JAI_OK` with a solution to be freed by [`mora_jai_solution_free`], or
+/// `MORA_JAI_NO_SOLUTION` with an empty solution if the puzzle cannot be solved.
+///
+/// # Safety
+/// `puzzle` must be null or a valid puzzle pointer, and `out` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_puzzle_solve(
+    puzzle: *const MoraJaiPuzzle,
+    out: *mut MoraJaiSolution,
+) -> i32 {
+    // SAFETY: the caller guarantees both pointers are null or valid
+    let (Some(puzzle), Some(out)) = (unsafe { puzzle.as_ref() }, unsafe { out.as_mut() }) else {
+        return MORA_JAI_INVALID_ARGUMENT;
+    };
+
+    let Some(solution) = puzzle.inner.solve() else {
+        *out = MoraJaiSolution {
+            moves: std::ptr::null_mut(),
+            len: 0,
+        };
+        return MORA_JAI_NO_SOLUTION;
+    };
+
+    let len = solution.presses.len();
+    let moves: Box<[u8]> = solution
+        .presses
+        .into_iter()
+        .flat_map(|(row, col)| [row as u8, col as u8])
+        .collect();
+    *out = MoraJaiSolution {
+        moves: Box::into_raw(moves).cast(),
+        len,
+    };
+    MORA_JAI_OK
+}
+
+/// Releases a solution's moves and clears it. Passing null does nothing.
+///
+/// # Safety
+/// `solution` must be null or point to a solution filled in by [`mora_jai_puzzle_solve`]
+/// which has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mora_jai_solution_free(solution: *mut MoraJaiSolution) {
+    // SAFETY: the caller guarantees the pointer is null or valid
+    let Some(solution) = (unsafe { solution.as_mut() }) else {
+        return;
+    };
+
+    if !solution.moves.is_null() {
+        let moves = std::ptr::slice_from_raw_parts_mut(solution.moves, 2 * solution.len);
+        // SAFETY: moves was created from a boxed slice of exactly 2 * len bytes
+        drop(unsafe { Box::from_raw(moves) });
+    }
+    solution.moves = std::ptr::null_mut();
+    solution.len = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_through_ffi() {
+        let puzzle = unsafe { mora_jai_puzzle_parse(c"wwwwwww-w---w".as_ptr()) };
+        assert!(!puzzle.is_null());
+
+        let mut solution = MoraJaiSolution {
+            moves: std::ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(
+            unsafe { mora_jai_puzzle_solve(puzzle, &mut solution) },
+            MORA_JAI_OK
+        );
+        let moves = unsafe { std::slice::from_raw_parts(solution.moves, 2 * solution.len) };
+        assert_eq!(moves, [0, 2, 0, 1]);
+
+        for pair in moves.chunks(2) {
+            let code =
+                unsafe { mora_jai_puzzle_press_tile(puzzle, pair[0].into(), pair[1].into()) };
+            assert_eq!(code, MORA_JAI_OK);
+        }
+        for corner in [
+            MoraJaiCorner::NW,
+            MoraJaiCorner::NE,
+            MoraJaiCorner::SW,
+            MoraJaiCorner::SE,
+        ] {
+            unsafe { mora_jai_puzzle_press_corner(puzzle, corner as i32) };
+        }
+        assert!(unsafe { mora_jai_puzzle_is_solved(puzzle) });
+
+        unsafe {
+            mora_jai_solution_free(&mut solution);
+            mora_jai_puzzle_free(puzzle);
+        }
+        assert!(solution.moves.is_null());
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert!(unsafe { mora_jai_puzzle_parse(c"not a puzzle".as_ptr()) }.is_null());
+        assert!(unsafe { mora_jai_puzzle_parse(std::ptr::null()) }.is_null());
+
+        let puzzle = mora_jai_puzzle_random();
+        assert_eq!(
+            unsafe { mora_jai_puzzle_press_tile(puzzle, 3, 0) },
+            MORA_JAI_INVALID_ARGUMENT
+        );
+        assert_eq!(unsafe { mora_jai_puzzle_get_tile(puzzle, 0, 3) }, 0);
+        for corner in [4, -1] {
+            assert_eq!(
+                unsafe { mora_jai_puzzle_press_corner(puzzle, corner) },
+                MORA_JAI_INVALID_ARGUMENT
+            );
+        }
+        unsafe { mora_jai_puzzle_free(puzzle) };
+    }
+}
@@ -0,0 +1,199 @@
+//! A notation laid out like the printed board, for copying puzzles down row by row as
+//! they are seen: a goals line, then the three rows of tiles, top row first.
+//!
+//! ```text
+//! Goals: white white white white
+//! w|www|w
+//!  |w-w|
+//! w|--w|w
+//! ```
+//!
+//! Parsing is lenient. Colors may be written by code or by name and separated by
+//! spaces, commas or semicolons, the `Goals:` label is optional, and a row written
+//! between `|` separators, as printed with its corner buttons either side, is read
+//! between them. The goals line can come last instead of first if it is labelled.
+
+use std::fmt;
+
+use mora_jai_core::{Color, ColorLabels, Grid, Puzzle, RuleSet};
+
+/// Why a puzzle written as a board could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// The board has this many non-blank lines rather than a goals line and three rows
+    LineCount { found: usize },
+    /// The line of the board (counting from 1) has a color which is not known
+    UnknownColor { line: usize, color: String },
+    /// The line of the board (counting from 1) has the wrong number of colors
+    ColorCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl BoardError {
+    /// The line of the board (counting from 1) with the problem, if it is in one line.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            BoardError::LineCount { .. } => None,
+            BoardError::UnknownColor { line, .. } | BoardError::ColorCount { line, .. } => {
+                Some(*line)
+            }
+        }
+    }
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::LineCount { found } => write!(
+                f,
+                "expected a goals line and 3 rows of tiles but found {found} lines"
+            ),
+            BoardError::UnknownColor { line, color } => {
+                write!(f, "line {line} of the board: unknown color {color:?}")
+            }
+            BoardError::ColorCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} of the board: expected {expected} colors but found {found}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// Whether text looks like a board rather than notation on one line.
+pub fn is_board(s: &str) -> bool {
+    s.lines().filter(|line| !line.trim().is_empty()).count() > 1
+}
+
+/// Reads the colors of one line, numbered `line` within the board.
+fn colors<const N: usize>(
+    text: &str,
+    line: usize,
+    labels: &ColorLabels,
+) -> Result<[Color; N], BoardError> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, '|' | ',' | ';'))
+        .filter(|word| !word.is_empty())
+        .collect();
+    // A single word is a run of codes, such as `w-w`
+    let words: Vec<String> = match words[..] {
+        [codes] if codes.chars().count() > 1 && labels.from_name(codes).is_none() => {
+            codes.chars().map(String::from).collect()
+        }
+        _ => words.iter().map(|word| word.to_string()).collect(),
+    };
+    if words.len() != N {
+        return Err(BoardError::ColorCount {
+            line,
+            expected: N,
+            found: words.len(),
+        });
+    }
+
+    let mut colors = [Color::Gray; N];
+    for (color, word) in colors.iter_mut().zip(&words) {
+        let mut chars = word.chars();
+        let found = match (chars.next(), chars.next()) {
+            (Some(code), None) => labels.from_code(code),
+            _ => labels
+                .from_name(word)
+                .or_else(|| labels.from_name(&word.to_lowercase())),
+        };
+        *color = found.ok_or_else(|| BoardError::UnknownColor {
+            line,
+            color: word.clone(),
+        })?;
+    }
+    Ok(colors)
+}
+
+/// The goals written on a line, without any `Goals` label.
+fn strip_goals_label(line: &str) -> Option<&str> {
+    let label = line.get(..5)?;
+    label
+        .eq_ignore_ascii_case("goals")
+        .then(|| line[5..].trim_start_matches(':'))
+}
+
+/// Parses a puzzle written as a board, whose tiles follow the given rules.
+pub fn parse_board(s: &str, rules: &RuleSet) -> Option<Puzzle> {
+    try_parse_board_with_labels(s, rules, &ColorLabels::default()).ok()
+}
+
+/// Parses a puzzle written as a board with the codes and names of `labels`, reporting
+/// which line of the board parsing failed on.
+pub fn try_parse_board_with_labels(
+    s: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<Puzzle, BoardError> {
+    let lines: Vec<(usize, &str)> = s
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let [first, second, third, fourth] = lines[..] else {
+        return Err(BoardError::LineCount { found: lines.len() });
+    };
+
+    let (goals, rows) = match strip_goals_label(fourth.1) {
+        Some(goals) => ((fourth.0, goals), [first, second, third]),
+        None => (
+            (first.0, strip_goals_label(first.1).unwrap_or(first.1)),
+            [second, third, fourth],
+        ),
+    };
+    let goals = colors::<4>(goals.1, goals.0, labels)?;
+    let [top, middle, bottom] = rows.map(|(line, text)| {
+        // A row printed between its corner buttons, such as `w|www|w`
+        let text = match (text.find('|'), text.rfind('|')) {
+            (Some(start), Some(end)) if start < end => &text[start + 1..end],
+            _ => text,
+        };
+        colors::<3>(text, line, labels)
+    });
+
+    let grid = Grid::from_rows(top?, middle?, bottom?);
+    Ok(Puzzle::with_rules(goals, grid, rules.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation;
+
+    #[test]
+    fn boards_read_like_they_are_printed() {
+        let rules = RuleSet::standard();
+        let expected = notation::parse_puzzle("wwwkwwww-w--w", &rules).unwrap();
+
+        let printed = "Goals: white white white black\nw|www|w\n |w-w| \nw|--w|w\n";
+        assert_eq!(parse_board(printed, &rules), Some(expected.clone()));
+        let loose = "\n  www ; \nw, -, w\nGray gray white\ngoals: w w w k\n";
+        assert_eq!(parse_board(loose, &rules), Some(expected));
+
+        let err =
+            try_parse_board_with_labels("wwwk\nwww\nwxw\n--w", &rules, &ColorLabels::default())
+                .unwrap_err();
+        assert_eq!(
+            err,
+            BoardError::UnknownColor {
+                line: 3,
+                color: String::from("x")
+            }
+        );
+        assert_eq!(
+            try_parse_board_with_labels("wwwk\nwww\nw-w", &rules, &ColorLabels::default()),
+            Err(BoardError::LineCount { found: 3 })
+        );
+    }
+}
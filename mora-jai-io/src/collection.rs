@@ -0,0 +1,187 @@
+//! Collections of puzzles with metadata, kept as TOML or JSON files so puzzle packs can
+//! be shared and reviewed.
+//!
+//! ```toml
+//! name = "Sanctum trunks"
+//! author = "Ada"
+//!
+//! [[puzzles]]
+//! name = "Warm up"
+//! difficulty = "easy"
+//! notation = "wwwwwww-w---w"
+//! notes = "Two presses along the bottom row"
+//!
+//! [[puzzles]]
+//! name = "Night sky"
+//! author = "Grace"
+//! notation = "kkbykgw-wrgby"
+//! ```
+//!
+//! Only the names and notation are required. An entry without an author is by the
+//! collection's author.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use mora_jai_core::{Difficulty, Puzzle, RuleSet};
+
+use crate::notation::{self, ParseError};
+
+/// A named collection of puzzles.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub puzzles: Vec<Entry>,
+}
+
+/// One puzzle of a collection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    /// Who made the puzzle, if not the collection's author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// How hard the puzzle is meant to be, which may not match its optimal solution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+    /// The puzzle in notation
+    pub notation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Entry {
+    /// Parses the entry's puzzle, whose tiles follow the given rules.
+    pub fn puzzle(&self, rules: &RuleSet) -> Result<Puzzle, ParseError> {
+        notation::try_parse_puzzle(&self.notation, rules)
+    }
+}
+
+#[derive(Debug)]
+pub enum CollectionError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    TomlWrite(toml::ser::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionError::Io(e) => write!(f, "failed to access collection file: {e}"),
+            CollectionError::Toml(e) => write!(f, "invalid collection file: {e}"),
+            CollectionError::TomlWrite(e) => write!(f, "failed to write collection: {e}"),
+            CollectionError::Json(e) => write!(f, "invalid collection file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CollectionError::Io(e) => Some(e),
+            CollectionError::Toml(e) => Some(e),
+            CollectionError::TomlWrite(e) => Some(e),
+            CollectionError::Json(e) => Some(e),
+        }
+    }
+}
+
+/// Whether a path names a JSON file rather than a TOML one.
+fn is_json(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("json")
+}
+
+impl Collection {
+    pub fn from_toml(s: &str) -> Result<Self, CollectionError> {
+        toml::from_str(s).map_err(CollectionError::Toml)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, CollectionError> {
+        serde_json::from_str(s).map_err(CollectionError::Json)
+    }
+
+    pub fn to_toml(&self) -> Result<String, CollectionError> {
+        toml::to_string(self).map_err(CollectionError::TomlWrite)
+    }
+
+    pub fn to_json(&self) -> Result<String, CollectionError> {
+        serde_json::to_string_pretty(self).map_err(CollectionError::Json)
+    }
+
+    /// Reads a collection file, treating files with a `.json` extension as JSON and
+    /// anything else as TOML.
+    pub fn load(path: &Path) -> Result<Self, CollectionError> {
+        let contents = std::fs::read_to_string(path).map_err(CollectionError::Io)?;
+        if is_json(path) {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+    }
+
+    /// Writes the collection to a file, as JSON if it has a `.json` extension and TOML
+    /// otherwise.
+    pub fn save(&self, path: &Path) -> Result<(), CollectionError> {
+        let contents = if is_json(path) {
+            self.to_json()?
+        } else {
+            self.to_toml()?
+        };
+        std::fs::write(path, contents).map_err(CollectionError::Io)
+    }
+
+    /// The entry with the given name, if any.
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.puzzles.iter().find(|entry| entry.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collections_round_trip_through_toml_and_json() {
+        let collection = Collection::from_toml(
+            r#"
+            name = "Sanctum trunks"
+            author = "Ada"
+
+            [[puzzles]]
+            name = "Warm up"
+            difficulty = "easy"
+            notation = "wwwwwww-w---w"
+            notes = "Two presses along the bottom row"
+
+            [[puzzles]]
+            name = "Night sky"
+            notation = "kkbykgw-wrgby"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(collection.puzzles.len(), 2);
+        let warm_up = collection.entry("Warm up").unwrap();
+        assert_eq!(warm_up.difficulty, Some(Difficulty::Easy));
+        assert!(warm_up.puzzle(&RuleSet::standard()).is_ok());
+        assert_eq!(collection.entry("Night sky").unwrap().author, None);
+
+        let toml = collection.to_toml().unwrap();
+        assert_eq!(Collection::from_toml(&toml).unwrap(), collection);
+        let json = collection.to_json().unwrap();
+        assert_eq!(Collection::from_json(&json).unwrap(), collection);
+    }
+
+    #[test]
+    fn unknown_difficulties_are_rejected() {
+        let result = Collection::from_json(
+            r#"{"name": "x", "puzzles": [{"name": "a", "difficulty": "brutal", "notation": "wwwwwww-w---w"}]}"#,
+        );
+        assert!(matches!(result, Err(CollectionError::Json(_))));
+    }
+}
@@ -0,0 +1,334 @@
+//! Reading puzzles out of screenshots of the in-game box.
+//!
+//! Screenshots are expected to be cropped to the face of the box: the four goal
+//! markers sit in the corners of the image and the 3x3 tile grid fills the middle.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use image::RgbImage;
+use mora_jai_core::{Color, ColorLabels};
+use serde::Serialize;
+
+/// Approximate in-game colors of each tile, used for nearest-color matching.
+const PALETTE: [(Color, [f32; 3]); 10] = [
+    (Color::Gray, [128.0, 128.0, 128.0]),
+    (Color::White, [235.0, 235.0, 235.0]),
+    (Color::Black, [20.0, 20.0, 20.0]),
+    (Color::Red, [200.0, 30.0, 30.0]),
+    (Color::Orange, [240.0, 140.0, 20.0]),
+    (Color::Green, [40.0, 170.0, 60.0]),
+    (Color::Yellow, [240.0, 220.0, 40.0]),
+    (Color::Violet, [120.0, 40.0, 180.0]),
+    (Color::Pink, [240.0, 150.0, 190.0]),
+    (Color::Blue, [30.0, 80.0, 220.0]),
+];
+
+/// Centers of the goal markers in notation order (NW, NE, SW, SE), as fractions of the
+/// image's width and height.
+const GOAL_CENTERS: [(f32, f32); 4] = [(0.08, 0.08), (0.92, 0.08), (0.08, 0.92), (0.92, 0.92)];
+
+/// Centers of the tiles in notation order (top row first), as fractions of the image's
+/// width and height.
+const TILE_CENTERS: [(f32, f32); 9] = [
+    (0.3, 0.3),
+    (0.5, 0.3),
+    (0.7, 0.3),
+    (0.3, 0.5),
+    (0.5, 0.5),
+    (0.7, 0.5),
+    (0.3, 0.7),
+    (0.5, 0.7),
+    (0.7, 0.7),
+];
+
+/// Half the side length of each sampled square, as a fraction of the image's smaller side.
+const SAMPLE_RADIUS: f32 = 0.03;
+
+/// Labels for each sampled cell in notation order, used when reporting uncertain cells.
+const CELL_LABELS: [&str; 13] = [
+    "goal nw", "goal ne", "goal sw", "goal se", "tile 7", "tile 8", "tile 9", "tile 4", "tile 5",
+    "tile 6", "tile 1", "tile 2", "tile 3",
+];
+
+/// The color read from one region of a screenshot.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub color: Color,
+    /// How clearly the sampled region matched its color, from 0 (ambiguous) to 1.
+    pub confidence: f32,
+}
+
+/// A puzzle read from a screenshot.
+#[derive(Debug, Clone)]
+pub struct ImageParse {
+    /// The goals followed by the tiles, in notation order.
+    pub samples: [Sample; 13],
+}
+
+impl ImageParse {
+    /// The puzzle in the 13-character notation accepted by the solver.
+    pub fn notation(&self) -> String {
+        self.notation_with_labels(&ColorLabels::default())
+    }
+
+    /// The puzzle in notation, written with the codes of `labels`.
+    pub fn notation_with_labels(&self, labels: &ColorLabels) -> String {
+        self.samples.iter().map(|s| labels.code(s.color)).collect()
+    }
+
+    /// The confidence of the least certain cell.
+    pub fn confidence(&self) -> f32 {
+        self.samples
+            .iter()
+            .map(|s| s.confidence)
+            .fold(1.0, f32::min)
+    }
+
+    /// Labels of the cells whose confidence falls below the given threshold.
+    pub fn uncertain_cells(&self, min_confidence: f32) -> Vec<&'static str> {
+        self.samples
+            .iter()
+            .zip(CELL_LABELS)
+            .filter(|(s, _)| s.confidence < min_confidence)
+            .map(|(_, label)| label)
+            .collect()
+    }
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Reference colors to match against: the approximate in-game colors, followed by the
+/// display colors of any registered custom colors.
+fn palette() -> Vec<(Color, [f32; 3])> {
+    let custom = Color::all()
+        .into_iter()
+        .filter(|c| matches!(c, Color::Custom(_)))
+        .map(|c| (c, c.rgb().map(f32::from)));
+    PALETTE.into_iter().chain(custom).collect()
+}
+
+/// Maps an RGB value to the nearest palette color.
+///
+/// The confidence compares the distances to the nearest and second-nearest colors, so
+/// a value halfway between two colors has confidence 0.
+fn classify(rgb: [f32; 3]) -> Sample {
+    let mut distances: Vec<(Color, f32)> = palette()
+        .into_iter()
+        .map(|(color, reference)| (color, distance(rgb, reference)))
+        .collect();
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let (color, nearest) = distances[0];
+    let (_, second) = distances[1];
+    let confidence = if second == 0.0 {
+        0.0
+    } else {
+        1.0 - nearest / second
+    };
+
+    Sample { color, confidence }
+}
+
+/// Averages the pixels in a square centered on the given fractional coordinates.
+fn sample_region(img: &RgbImage, (fx, fy): (f32, f32)) -> [f32; 3] {
+    let (width, height) = img.dimensions();
+    let radius = (SAMPLE_RADIUS * width.min(height) as f32).max(1.0) as i64;
+    let cx = (fx * width as f32) as i64;
+    let cy = (fy * height as f32) as i64;
+
+    let mut sum = [0.0; 3];
+    let mut count = 0.0;
+    for y in (cy - radius).max(0)..(cy + radius).min(height as i64) {
+        for x in (cx - radius).max(0)..(cx + radius).min(width as i64) {
+            let pixel = img.get_pixel(x as u32, y as u32);
+            for (total, channel) in sum.iter_mut().zip(pixel.0) {
+                *total += channel as f32;
+            }
+            count += 1.0;
+        }
+    }
+
+    sum.map(|total| total / count)
+}
+
+/// Reads a puzzle from a screenshot cropped to the face of the box.
+pub fn parse_image(img: &RgbImage) -> ImageParse {
+    let samples = std::array::from_fn(|i| {
+        let center = if i < 4 {
+            GOAL_CENTERS[i]
+        } else {
+            TILE_CENTERS[i - 4]
+        };
+        classify(sample_region(img, center))
+    });
+
+    ImageParse { samples }
+}
+
+pub fn parse_image_file(path: &Path) -> Result<ImageParse, image::ImageError> {
+    let img = image::open(path)?.into_rgb8();
+    Ok(parse_image(&img))
+}
+
+/// An image that was read and the confidence of its parse.
+#[derive(Debug, Serialize)]
+pub struct Source {
+    pub file: PathBuf,
+    pub confidence: f32,
+}
+
+/// A distinct puzzle found in one or more screenshots.
+#[derive(Debug, Serialize)]
+pub struct PackEntry {
+    pub notation: String,
+    pub sources: Vec<Source>,
+}
+
+/// A screenshot that could not be read with enough confidence.
+#[derive(Debug, Serialize)]
+pub struct ReviewEntry {
+    pub file: PathBuf,
+    /// The best guess at the puzzle, if the image could be decoded at all.
+    pub notation: Option<String>,
+    pub confidence: f32,
+    pub reason: String,
+}
+
+/// The result of importing a directory of screenshots.
+#[derive(Debug, Default, Serialize)]
+pub struct Pack {
+    pub puzzles: Vec<PackEntry>,
+    pub review: Vec<ReviewEntry>,
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "png" | "jpg" | "jpeg"))
+}
+
+/// Reads every screenshot in a directory into a pack of distinct puzzles.
+///
+/// Parses whose least certain cell falls below `min_confidence` are left out of the
+/// puzzles and listed for review instead. Puzzles are written with the codes of `labels`.
+pub fn import_dir(dir: &Path, min_confidence: f32, labels: &ColorLabels) -> std::io::Result<Pack> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    files.retain(|path| path.is_file() && is_image(path));
+    files.sort();
+
+    let mut pack = Pack::default();
+    let mut puzzles: BTreeMap<String, Vec<Source>> = BTreeMap::new();
+
+    for file in files {
+        let parse = match parse_image_file(&file) {
+            Ok(parse) => parse,
+            Err(e) => {
+                pack.review.push(ReviewEntry {
+                    file,
+                    notation: None,
+                    confidence: 0.0,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let confidence = parse.confidence();
+        if confidence < min_confidence {
+            pack.review.push(ReviewEntry {
+                file,
+                notation: Some(parse.notation_with_labels(labels)),
+                confidence,
+                reason: format!(
+                    "uncertain cells: {}",
+                    parse.uncertain_cells(min_confidence).join(", ")
+                ),
+            });
+        } else {
+            puzzles
+                .entry(parse.notation_with_labels(labels))
+                .or_default()
+                .push(Source { file, confidence });
+        }
+    }
+
+    pack.puzzles = puzzles
+        .into_iter()
+        .map(|(notation, sources)| PackEntry { notation, sources })
+        .collect();
+
+    Ok(pack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(color: Color) -> image::Rgb<u8> {
+        let (_, rgb) = PALETTE.iter().find(|(c, _)| *c == color).unwrap();
+        image::Rgb(rgb.map(|c| c as u8))
+    }
+
+    #[test]
+    fn parse_image_reads_synthetic_box() {
+        let colors = [
+            Color::Green,
+            Color::Red,
+            Color::Blue,
+            Color::Yellow,
+            Color::Black,
+            Color::White,
+            Color::Gray,
+            Color::Orange,
+            Color::Pink,
+            Color::Violet,
+            Color::Gray,
+            Color::Gray,
+            Color::Black,
+        ];
+
+        let mut img = RgbImage::from_pixel(200, 200, image::Rgb([60, 40, 20]));
+        for (i, color) in colors.iter().enumerate() {
+            let (fx, fy) = if i < 4 {
+                GOAL_CENTERS[i]
+            } else {
+                TILE_CENTERS[i - 4]
+            };
+            let (cx, cy) = ((fx * 200.0) as u32, (fy * 200.0) as u32);
+            for y in cy - 8..cy + 8 {
+                for x in cx - 8..cx + 8 {
+                    img.put_pixel(x, y, reference(*color));
+                }
+            }
+        }
+
+        let parse = parse_image(&img);
+        assert_eq!(parse.notation(), "grbykw-opv--k");
+        assert!(parse.confidence() > 0.99);
+
+        let labels = ColorLabels::new([
+            (Color::Pink, String::from("pink"), 'P'),
+            (Color::Violet, String::from("purple"), 'p'),
+        ])
+        .unwrap();
+        assert_eq!(parse.notation_with_labels(&labels), "grbykw-oPp--k");
+    }
+
+    #[test]
+    fn ambiguous_color_has_low_confidence() {
+        let gray = [128.0, 128.0, 128.0];
+        let black = [20.0, 20.0, 20.0];
+        let halfway = [0, 1, 2].map(|i| (gray[i] + black[i]) / 2.0);
+
+        assert!(classify(halfway).confidence < 0.05);
+    }
+}
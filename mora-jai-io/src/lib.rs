@@ -0,0 +1,10 @@
+//! Parsing and serialization of Mora Jai puzzles and related files.
+
+pub mod board;
+#[cfg(feature = "collection")]
+pub mod collection;
+#[cfg(feature = "image")]
+pub mod image_import;
+pub mod notation;
+#[cfg(feature = "config")]
+pub mod rule_config;
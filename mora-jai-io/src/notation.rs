@@ -0,0 +1,275 @@
+//! The compact puzzle notation: four goal colors (NW, NE, SW, SE) followed by the nine
+//! tiles, top row first, one character per color. The `_with_labels` functions read and
+//! write colors by the codes of a [`ColorLabels`] instead of the standard ones.
+//!
+//! Tiles whose colors are not known can be written as [`UNKNOWN`] and read with
+//! [`try_parse_partial_with_labels`], up to [`MAX_UNKNOWNS`] of them.
+
+use std::fmt;
+
+use mora_jai_core::{Color, ColorLabels, Grid, PartialPuzzle, Puzzle, RuleSet};
+
+/// The number of characters in a puzzle's notation.
+pub const NOTATION_LEN: usize = 13;
+
+/// The code for a tile whose color is not known.
+pub const UNKNOWN: char = '?';
+
+/// The most unknown tiles a puzzle may have, as each multiplies the puzzles to solve
+/// by the number of colors.
+pub const MAX_UNKNOWNS: usize = 2;
+
+/// Why a puzzle's notation could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The character at the given column (counting from 1) is not a color code
+    UnknownColor { column: usize, code: char },
+    /// The notation ended after the given number of characters
+    TooShort { len: usize },
+    /// There is an extra character at the given column (counting from 1)
+    TooLong { column: usize },
+    /// The tile at the given column (counting from 1) is unknown, one more than
+    /// [`MAX_UNKNOWNS`]
+    TooManyUnknowns { column: usize },
+}
+
+impl ParseError {
+    /// The column (counting from 1) of the offending character.
+    pub fn column(&self) -> usize {
+        match self {
+            ParseError::UnknownColor { column, .. }
+            | ParseError::TooLong { column }
+            | ParseError::TooManyUnknowns { column } => *column,
+            ParseError::TooShort { len } => len + 1,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownColor { code, .. } => write!(f, "unknown color code {code:?}"),
+            ParseError::TooShort { len } => write!(
+                f,
+                "expected {NOTATION_LEN} characters but the puzzle ends after {len}"
+            ),
+            ParseError::TooLong { .. } => {
+                write!(f, "expected {NOTATION_LEN} characters but found more")
+            }
+            ParseError::TooManyUnknowns { .. } => write!(
+                f,
+                "too many unknown tiles, at most {MAX_UNKNOWNS} can be solved for"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn char_to_color(c: char) -> Option<Color> {
+    Color::from_code(c)
+}
+
+pub fn color_to_char(color: Color) -> char {
+    color.code()
+}
+
+/// Parses a puzzle whose tiles follow the given rules.
+pub fn parse_puzzle(s: &str, rules: &RuleSet) -> Option<Puzzle> {
+    try_parse_puzzle(s, rules).ok()
+}
+
+/// Parses a puzzle whose tiles follow the given rules, reporting where parsing failed.
+pub fn try_parse_puzzle(s: &str, rules: &RuleSet) -> Result<Puzzle, ParseError> {
+    try_parse_puzzle_with_labels(s, rules, &ColorLabels::default())
+}
+
+/// Reads the colors of notation with the codes of `labels`, with `None` for tiles
+/// written as [`UNKNOWN`] if `unknowns` are allowed.
+fn parse_colors(
+    s: &str,
+    labels: &ColorLabels,
+    unknowns: bool,
+) -> Result<[Option<Color>; NOTATION_LEN], ParseError> {
+    let mut colors = [None; NOTATION_LEN];
+    let mut chars = s.chars();
+
+    for (i, color) in colors.iter_mut().enumerate() {
+        let code = chars.next().ok_or(ParseError::TooShort { len: i })?;
+        *color = match labels.from_code(code) {
+            // Goals are always known, as the box shows them
+            None if code == UNKNOWN && unknowns && i >= 4 => None,
+            None => {
+                return Err(ParseError::UnknownColor {
+                    column: i + 1,
+                    code,
+                });
+            }
+            known => known,
+        };
+    }
+    if chars.next().is_some() {
+        return Err(ParseError::TooLong {
+            column: NOTATION_LEN + 1,
+        });
+    }
+    Ok(colors)
+}
+
+/// Parses a puzzle whose colors are written with the codes of `labels`.
+pub fn try_parse_puzzle_with_labels(
+    s: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<Puzzle, ParseError> {
+    let colors = parse_colors(s, labels, false)?.map(|color| color.expect("colors are known"));
+
+    let [nw, ne, sw, se, tiles @ ..] = colors;
+    let [a, b, c, d, e, f, g, h, i] = tiles;
+    let grid = Grid::from_rows([a, b, c], [d, e, f], [g, h, i]);
+
+    Ok(Puzzle::with_rules([nw, ne, sw, se], grid, rules.clone()))
+}
+
+/// Whether notation has tiles whose colors are not known, to be read with
+/// [`try_parse_partial_with_labels`].
+pub fn has_unknowns(s: &str) -> bool {
+    s.contains(UNKNOWN)
+}
+
+/// Parses a puzzle whose colors are written with the codes of `labels`, where tiles
+/// whose colors are not known are written as [`UNKNOWN`]. Goals must be known.
+pub fn try_parse_partial_with_labels(
+    s: &str,
+    rules: &RuleSet,
+    labels: &ColorLabels,
+) -> Result<PartialPuzzle, ParseError> {
+    let colors = parse_colors(s, labels, true)?;
+    if let Some(extra) = colors
+        .iter()
+        .enumerate()
+        .filter(|(_, color)| color.is_none())
+        .nth(MAX_UNKNOWNS)
+    {
+        return Err(ParseError::TooManyUnknowns {
+            column: extra.0 + 1,
+        });
+    }
+    let [nw, ne, sw, se, tiles @ ..] = colors;
+    let goals = [nw, ne, sw, se].map(|color| color.expect("goals are known"));
+    let [a, b, c, d, e, f, g, h, i] = tiles;
+
+    Ok(PartialPuzzle::new(
+        goals,
+        [[g, h, i], [d, e, f], [a, b, c]],
+        rules.clone(),
+    ))
+}
+
+/// Writes a grid's tiles in notation form, top row first.
+pub fn grid_to_notation(grid: &Grid) -> String {
+    grid_to_notation_with_labels(grid, &ColorLabels::default())
+}
+
+/// Writes a grid's tiles with the codes of `labels`, top row first.
+pub fn grid_to_notation_with_labels(grid: &Grid, labels: &ColorLabels) -> String {
+    (0..3)
+        .rev()
+        .flat_map(|row| (0..3).map(move |col| *grid.get(row, col)))
+        .map(|color| labels.code(color))
+        .collect()
+}
+
+/// Writes a puzzle's goals and original grid in notation form.
+pub fn to_notation(puzzle: &Puzzle) -> String {
+    to_notation_with_labels(puzzle, &ColorLabels::default())
+}
+
+/// Writes a puzzle's goals and original grid with the codes of `labels`.
+pub fn to_notation_with_labels(puzzle: &Puzzle, labels: &ColorLabels) -> String {
+    let goals: String = puzzle
+        .goals()
+        .iter()
+        .map(|color| labels.code(*color))
+        .collect();
+    goals + &grid_to_notation_with_labels(puzzle.original(), labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notation_round_trips() {
+        let notation = "grbykw-opv--k";
+        let puzzle = parse_puzzle(notation, &RuleSet::standard()).unwrap();
+
+        assert_eq!(
+            puzzle.goals(),
+            &[Color::Green, Color::Red, Color::Blue, Color::Yellow]
+        );
+        assert_eq!(puzzle.get_tile(2, 0), Color::Black);
+        assert_eq!(puzzle.get_tile(0, 2), Color::Black);
+        assert_eq!(to_notation(&puzzle), notation);
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_problem() {
+        let rules = RuleSet::standard();
+
+        let err = try_parse_puzzle("wwwwwwx------", &rules).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::UnknownColor {
+                column: 7,
+                code: 'x'
+            }
+        );
+        assert_eq!(err.column(), 7);
+
+        let err = try_parse_puzzle("wwww-", &rules).unwrap_err();
+        assert_eq!(err, ParseError::TooShort { len: 5 });
+        assert_eq!(err.column(), 6);
+
+        let err = try_parse_puzzle("wwww---------w", &rules).unwrap_err();
+        assert_eq!(err, ParseError::TooLong { column: 14 });
+    }
+
+    #[test]
+    fn unknown_tiles_are_read_apart() {
+        let rules = RuleSet::standard();
+        let labels = ColorLabels::default();
+        let partial = try_parse_partial_with_labels("wwwwk?w-w---?", &rules, &labels).unwrap();
+        assert_eq!(partial.tile(2, 0), Some(Color::Black));
+        assert_eq!(partial.unknowns(), [(0, 2), (2, 1)]);
+
+        assert_eq!(
+            try_parse_partial_with_labels("?wwwwwww-w---", &rules, &labels),
+            Err(ParseError::UnknownColor {
+                column: 1,
+                code: '?'
+            })
+        );
+        assert!(try_parse_puzzle("wwwwk?w-w---w", &rules).is_err());
+        assert_eq!(
+            try_parse_partial_with_labels("wwwwk?w-?---?", &rules, &labels),
+            Err(ParseError::TooManyUnknowns { column: 13 })
+        );
+    }
+
+    #[test]
+    fn labels_change_the_codes_read_and_written() {
+        let labels = ColorLabels::new([
+            (Color::Pink, String::from("pink"), 'P'),
+            (Color::Violet, String::from("purple"), 'p'),
+        ])
+        .unwrap();
+        let rules = RuleSet::standard();
+        let puzzle = try_parse_puzzle_with_labels("pPwwwww-w---w", &rules, &labels).unwrap();
+
+        assert_eq!(puzzle.goal(mora_jai_core::Corner::NW), Color::Violet);
+        assert_eq!(puzzle.goal(mora_jai_core::Corner::NE), Color::Pink);
+        assert_eq!(to_notation(&puzzle), "vpwwwww-w---w");
+        assert_eq!(to_notation_with_labels(&puzzle, &labels), "pPwwwww-w---w");
+    }
+}
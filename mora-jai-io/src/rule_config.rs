@@ -0,0 +1,306 @@
+//! Loading rule sets from TOML or JSON files, so box variants can be described without
+//! writing Rust.
+//!
+//! A rule file starts from a base rule set and overrides the rules of individual colors:
+//!
+//! ```toml
+//! # "standard" (the default) or "empty"
+//! base = "standard"
+//!
+//! [rules.yellow]
+//! type = "swap"
+//! rows = 1
+//! cols = 0
+//! wrap = true
+//!
+//! [rules.red]
+//! type = "recolor"
+//! map = { black = "red", white = "black", gray = "white" }
+//! ```
+//!
+//! Extra colors can be defined alongside their rules, giving each a notation code and a
+//! display color:
+//!
+//! ```toml
+//! [colors.gold]
+//! code = "G"
+//! rgb = [212, 175, 55]
+//!
+//! [rules.gold]
+//! type = "swap-opposite"
+//! ```
+//!
+//! The available rule types are `inert`, `toggle-adjacent`, `rotate-row`, `recolor`,
+//! `majority`, `swap-opposite`, `swap`, `rotate-neighbours` and `mimic`, matching the
+//! rules in [`mora_jai_core::rules`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use mora_jai_core::rules::{
+    Inert, Majority, Mimic, Recolor, RotateNeighbours, RotateRow, RuleSet, SwapOffset,
+    SwapOpposite, ToggleAdjacent,
+};
+use mora_jai_core::{Color, RegisterColorError};
+
+/// The rule set a rule file builds on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Base {
+    #[default]
+    Standard,
+    Empty,
+}
+
+/// A single color's rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RuleSpec {
+    Inert,
+    ToggleAdjacent,
+    RotateRow,
+    Recolor {
+        map: BTreeMap<Color, Color>,
+    },
+    Majority,
+    SwapOpposite,
+    Swap {
+        rows: isize,
+        cols: isize,
+        #[serde(default)]
+        wrap: bool,
+    },
+    RotateNeighbours,
+    Mimic,
+}
+
+/// An extra color defined in a rule file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColorSpec {
+    /// The character representing the color in puzzle notation
+    pub code: char,
+    /// The color used to display tiles of this color
+    pub rgb: [u8; 3],
+}
+
+/// The colors defined in a rule file, which must be registered before the rules that use
+/// them can be read.
+#[derive(Debug, Default, Deserialize)]
+struct ColorDefinitions {
+    #[serde(default)]
+    colors: BTreeMap<String, ColorSpec>,
+}
+
+impl ColorDefinitions {
+    fn register(&self) -> Result<(), RuleConfigError> {
+        for (name, spec) in &self.colors {
+            Color::register(name, spec.code, spec.rgb).map_err(RuleConfigError::Color)?;
+        }
+        Ok(())
+    }
+}
+
+/// A rule set as described in a rule file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleConfig {
+    #[serde(default)]
+    pub base: Base,
+    #[serde(default)]
+    pub colors: BTreeMap<String, ColorSpec>,
+    #[serde(default)]
+    pub rules: BTreeMap<Color, RuleSpec>,
+}
+
+#[derive(Debug)]
+pub enum RuleConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    Color(RegisterColorError),
+}
+
+impl fmt::Display for RuleConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleConfigError::Io(e) => write!(f, "failed to read rule file: {e}"),
+            RuleConfigError::Toml(e) => write!(f, "invalid rule file: {e}"),
+            RuleConfigError::Json(e) => write!(f, "invalid rule file: {e}"),
+            RuleConfigError::Color(e) => write!(f, "invalid color definition: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RuleConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RuleConfigError::Io(e) => Some(e),
+            RuleConfigError::Toml(e) => Some(e),
+            RuleConfigError::Json(e) => Some(e),
+            RuleConfigError::Color(e) => Some(e),
+        }
+    }
+}
+
+impl RuleConfig {
+    /// Parses a TOML rule file, registering any colors it defines.
+    pub fn from_toml(s: &str) -> Result<Self, RuleConfigError> {
+        toml::from_str::<ColorDefinitions>(s)
+            .map_err(RuleConfigError::Toml)?
+            .register()?;
+        toml::from_str(s).map_err(RuleConfigError::Toml)
+    }
+
+    /// Parses a JSON rule file, registering any colors it defines.
+    pub fn from_json(s: &str) -> Result<Self, RuleConfigError> {
+        serde_json::from_str::<ColorDefinitions>(s)
+            .map_err(RuleConfigError::Json)?
+            .register()?;
+        serde_json::from_str(s).map_err(RuleConfigError::Json)
+    }
+
+    /// Reads a rule file, treating files with a `.json` extension as JSON and anything
+    /// else as TOML.
+    pub fn load(path: &Path) -> Result<Self, RuleConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(RuleConfigError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            _ => Self::from_toml(&contents),
+        }
+    }
+
+    /// Builds the described rule set.
+    pub fn build<const ROWS: usize, const COLS: usize>(&self) -> RuleSet<ROWS, COLS> {
+        let mut rules = match self.base {
+            Base::Standard => RuleSet::standard(),
+            Base::Empty => RuleSet::empty(),
+        };
+
+        for (&color, spec) in &self.rules {
+            match spec {
+                RuleSpec::Inert => rules.set_rule(color, Inert),
+                RuleSpec::ToggleAdjacent => rules.set_rule(color, ToggleAdjacent),
+                RuleSpec::RotateRow => rules.set_rule(color, RotateRow),
+                RuleSpec::Recolor { map } => {
+                    rules.set_rule(color, Recolor::new(map.iter().map(|(&k, &v)| (k, v))))
+                }
+                RuleSpec::Majority => rules.set_rule(color, Majority),
+                RuleSpec::SwapOpposite => rules.set_rule(color, SwapOpposite),
+                &RuleSpec::Swap { rows, cols, wrap } => {
+                    rules.set_rule(color, SwapOffset { rows, cols, wrap })
+                }
+                RuleSpec::RotateNeighbours => rules.set_rule(color, RotateNeighbours),
+                RuleSpec::Mimic => rules.set_rule(color, Mimic),
+            }
+        }
+
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Grid;
+
+    #[test]
+    fn toml_overrides_standard_rules() {
+        let config = RuleConfig::from_toml(
+            r#"
+            [rules.yellow]
+            type = "swap"
+            rows = 1
+            cols = 0
+            wrap = true
+
+            [rules.gray]
+            type = "recolor"
+            map = { white = "black" }
+            "#,
+        )
+        .unwrap();
+        let rules = config.build();
+
+        let grid = Grid::from_rows(
+            [Color::Yellow, Color::White, Color::Gray],
+            [Color::Gray, Color::Black, Color::Gray],
+            [Color::Red, Color::Gray, Color::Gray],
+        );
+
+        assert_eq!(
+            grid.press_with(&rules, 2, 0),
+            Grid::from_rows(
+                [Color::Red, Color::White, Color::Gray],
+                [Color::Gray, Color::Black, Color::Gray],
+                [Color::Yellow, Color::Gray, Color::Gray],
+            )
+        );
+        assert_eq!(
+            grid.press_with(&rules, 2, 2),
+            Grid::from_rows(
+                [Color::Yellow, Color::Black, Color::Gray],
+                [Color::Gray, Color::Black, Color::Gray],
+                [Color::Red, Color::Gray, Color::Gray],
+            )
+        );
+        // Untouched colors keep their standard rules
+        assert_eq!(grid.press_with(&rules, 1, 1), grid.press(1, 1));
+    }
+
+    #[test]
+    fn json_empty_base_has_only_listed_rules() {
+        let config = RuleConfig::from_json(
+            r#"{"base": "empty", "rules": {"black": {"type": "rotate-row"}}}"#,
+        )
+        .unwrap();
+        let rules = config.build();
+
+        let grid = Grid::from_rows(
+            [Color::Black, Color::White, Color::Gray],
+            [Color::Gray, Color::White, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+        );
+        assert_eq!(grid.press_with(&rules, 1, 1), grid);
+        assert_eq!(grid.press_with(&rules, 2, 0), grid.press(2, 0));
+    }
+
+    #[test]
+    fn unknown_rule_type_is_rejected() {
+        assert!(RuleConfig::from_toml("[rules.pink]\ntype = \"teleport\"").is_err());
+    }
+
+    #[test]
+    fn custom_colors_can_be_defined() {
+        let config = RuleConfig::from_toml(
+            r#"
+            [colors.teal]
+            code = "T"
+            rgb = [0, 128, 128]
+
+            [rules.teal]
+            type = "swap-opposite"
+            "#,
+        )
+        .unwrap();
+        let rules = config.build();
+
+        let teal = Color::from_name("teal").unwrap();
+        assert_eq!(teal.code(), 'T');
+
+        let grid = Grid::from_rows(
+            [teal, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Gray],
+            [Color::Gray, Color::Gray, Color::Red],
+        );
+        assert_eq!(
+            grid.press_with(&rules, 2, 0),
+            Grid::from_rows(
+                [Color::Red, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, teal],
+            )
+        );
+    }
+}
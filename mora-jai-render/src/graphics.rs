@@ -0,0 +1,199 @@
+//! Showing images inline in terminals which support a graphics protocol, so puzzles can
+//! be drawn as colored squares rather than colored text.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::Rgb;
+
+use crate::raster::RgbImage;
+
+/// A way of sending images to a terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// DEC sixel graphics, supported by xterm, foot, mlterm and others
+    Sixel,
+    /// The Kitty terminal graphics protocol, also supported by WezTerm and Ghostty
+    Kitty,
+}
+
+/// Sixel images can use at most this many colors.
+const SIXEL_COLORS: usize = 256;
+/// Kitty images are sent in chunks of at most this many bytes of base64.
+const KITTY_CHUNK: usize = 4096;
+
+impl GraphicsProtocol {
+    /// Guesses the protocol supported by the terminal from its environment variables,
+    /// if any. Terminals are not queried, so some supporting sixel are missed.
+    pub fn detect() -> Option<Self> {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || program == "WezTerm"
+        {
+            Some(GraphicsProtocol::Kitty)
+        } else if term.contains("sixel") || term == "foot" || term.starts_with("mlterm") {
+            Some(GraphicsProtocol::Sixel)
+        } else {
+            None
+        }
+    }
+
+    /// The escape sequences drawing `image` at the cursor.
+    pub fn encode(self, image: &RgbImage) -> String {
+        match self {
+            GraphicsProtocol::Sixel => encode_sixel(image),
+            GraphicsProtocol::Kitty => encode_kitty(image),
+        }
+    }
+}
+
+/// Encodes an image as sixels. Puzzle images have only a few colors; any past the
+/// 256 sixel allows are drawn as the closest color already used.
+pub fn encode_sixel(image: &RgbImage) -> String {
+    let mut palette: Vec<[u8; 3]> = vec![];
+    let indices: Vec<usize> = image
+        .pixels()
+        .map(|&Rgb(rgb)| {
+            if let Some(i) = palette.iter().position(|&c| c == rgb) {
+                i
+            } else if palette.len() < SIXEL_COLORS {
+                palette.push(rgb);
+                palette.len() - 1
+            } else {
+                closest(&palette, rgb)
+            }
+        })
+        .collect();
+
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut out = format!("\x1bPq\"1;1;{width};{height}");
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        // Sixel color channels are percentages
+        let percent = |c: &u8| u32::from(*c) * 100 / 255;
+        out += &format!("#{i};2;{};{};{}", percent(r), percent(g), percent(b));
+    }
+
+    // Each sixel covers a column of six pixels, drawn once per color in the band
+    for top in (0..height).step_by(6) {
+        let rows = top..(top + 6).min(height);
+        let mut colors: Vec<usize> = rows
+            .clone()
+            .flat_map(|y| indices[y * width..(y + 1) * width].iter().copied())
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, &color) in colors.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            out += &format!("#{color}");
+            let sixels = (0..width).map(|x| {
+                let bits = rows.clone().fold(0u8, |bits, y| {
+                    bits | (u8::from(indices[y * width + x] == color) << (y - top))
+                });
+                char::from(0x3f + bits)
+            });
+            push_runs(&mut out, sixels);
+        }
+        out.push('-');
+    }
+    out + "\x1b\\"
+}
+
+/// The index of the palette color nearest to `rgb`.
+fn closest(palette: &[[u8; 3]], rgb: [u8; 3]) -> usize {
+    let distance = |c: &[u8; 3]| -> u32 {
+        (0..3)
+            .map(|i| u32::from(c[i].abs_diff(rgb[i])).pow(2))
+            .sum()
+    };
+    (0..palette.len())
+        .min_by_key(|&i| distance(&palette[i]))
+        .unwrap_or(0)
+}
+
+/// Writes sixels with runs of four or more repeats shortened to `!<count><sixel>`.
+fn push_runs(out: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |out: &mut String, (sixel, count): (char, usize)| {
+        if count >= 4 {
+            *out += &format!("!{count}{sixel}");
+        } else {
+            out.extend(std::iter::repeat_n(sixel, count));
+        }
+    };
+    for sixel in sixels {
+        run = match run {
+            Some((current, count)) if current == sixel => Some((current, count + 1)),
+            Some(done) => {
+                flush(out, done);
+                Some((sixel, 1))
+            }
+            None => Some((sixel, 1)),
+        };
+    }
+    if let Some(done) = run {
+        flush(out, done);
+    }
+}
+
+/// Encodes an image for the Kitty graphics protocol as raw RGB pixels, split into the
+/// chunks the protocol requires.
+pub fn encode_kitty(image: &RgbImage) -> String {
+    let data = STANDARD.encode(image.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).expect("base64 is ASCII");
+        if i == 0 {
+            let (width, height) = image.dimensions();
+            out += &format!("\x1b_Ga=T,f=24,s={width},v={height},m={more};{chunk}\x1b\\");
+        } else {
+            out += &format!("\x1b_Gm={more};{chunk}\x1b\\");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sixels_stack_six_rows_per_band() {
+        // Two columns, red above blue, seven rows tall so the last band is partial
+        let mut image = RgbImage::from_pixel(2, 7, Rgb([255, 0, 0]));
+        for x in 0..2 {
+            image.put_pixel(x, 3, Rgb([0, 0, 255]));
+            image.put_pixel(x, 6, Rgb([0, 0, 255]));
+        }
+
+        assert_eq!(
+            encode_sixel(&image),
+            concat!(
+                "\x1bPq\"1;1;2;7#0;2;100;0;0#1;2;0;0;100",
+                // Rows 0-2 and 4-5 red (0b110111), row 3 blue (0b001000)
+                "#0vv$#1GG-",
+                // The last band has only row 6, which is blue
+                "#1@@-",
+                "\x1b\\"
+            )
+        );
+    }
+
+    #[test]
+    fn kitty_images_are_sent_in_chunks() {
+        let image = RgbImage::from_pixel(64, 64, Rgb([0, 0, 0]));
+        let encoded = encode_kitty(&image);
+
+        // 64 * 64 * 3 bytes is 16384 characters of base64, so four chunks
+        assert!(encoded.starts_with("\x1b_Ga=T,f=24,s=64,v=64,m=1;AAAA"));
+        assert_eq!(encoded.matches("\x1b_G").count(), 4);
+        assert_eq!(encoded.matches("m=0;").count(), 1);
+        assert!(encoded.ends_with("AAAA\x1b\\"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Drawing how often each tile is pressed, such as across the optimal solutions of a
+//! puzzle, as a grid shaded from rarely to often pressed.
+
+#[cfg(feature = "svg")]
+use std::fmt::Write;
+
+/// Shades for values from none to the most of any tile.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Each value as a fraction of the largest, or 0 if every value is 0.
+fn scaled(values: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let max = values.iter().flatten().copied().fold(0.0, f64::max);
+    values.map(|row| row.map(|value| if max > 0.0 { value / max } else { 0.0 }))
+}
+
+/// Draws values for each tile, indexed by row from the bottom and then column, as
+/// shaded cells in rows top first, each labelled with its value.
+pub fn render_heatmap(values: &[[f64; 3]; 3]) -> String {
+    let scaled = scaled(values);
+    let mut out = String::new();
+    for row in (0..3).rev() {
+        for col in 0..3 {
+            let shade = SHADES[(scaled[row][col] * (SHADES.len() - 1) as f64).round() as usize];
+            out += &format!("{shade}{shade}{:>5.2}{shade}{shade}", values[row][col]);
+            out.push(if col < 2 { '|' } else { '\n' });
+        }
+    }
+    out
+}
+
+/// Draws values for each tile as an SVG of the box's tiles, shaded from white for none
+/// to dark red for the most of any tile, each labelled with its value.
+#[cfg(feature = "svg")]
+pub fn render_heatmap_svg(values: &[[f64; 3]; 3]) -> String {
+    use crate::layout::{FRAME_RGB, GAP, OUTLINE_RGB, SIZE, TILE, tile_origin};
+
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+    // From white towards a dark red
+    let heat = |t: f64| {
+        let mix = |from: f64, to: f64| (from + (to - from) * t).round() as u8;
+        [
+            mix(255.0, 0x9b as f64),
+            mix(255.0, 0x1c as f64),
+            mix(255.0, 0x1c as f64),
+        ]
+    };
+
+    let scaled = scaled(values);
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SIZE}" height="{SIZE}" viewBox="0 0 {SIZE} {SIZE}">"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <rect width="{SIZE}" height="{SIZE}" rx="{GAP}" fill="{}"/>"#,
+        hex(FRAME_RGB),
+    )
+    .unwrap();
+    for row in 0..3 {
+        for col in 0..3 {
+            let (x, y) = tile_origin(row, col);
+            let t = scaled[row][col];
+            let text = if t > 0.5 { "#ffffff" } else { "#1a1a1a" };
+            writeln!(
+                svg,
+                r#"  <rect x="{x}" y="{y}" width="{TILE}" height="{TILE}" rx="4" fill="{}" stroke="{}" stroke-width="2"/>"#,
+                hex(heat(t)),
+                hex(OUTLINE_RGB),
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="16" text-anchor="middle" dominant-baseline="central" fill="{text}">{:.2}</text>"#,
+                x + TILE / 2,
+                y + TILE / 2,
+                values[row][col],
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busiest_tiles_are_darkest() {
+        let values = [[0.0, 0.5, 1.0], [0.0; 3], [0.0, 0.0, 0.25]];
+        assert_eq!(
+            render_heatmap(&values),
+            concat!(
+                "   0.00  |   0.00  |░░ 0.25░░\n",
+                "   0.00  |   0.00  |   0.00  \n",
+                "   0.00  |▒▒ 0.50▒▒|██ 1.00██\n",
+            )
+        );
+    }
+}
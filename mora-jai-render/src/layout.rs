@@ -0,0 +1,40 @@
+//! Where things go in diagrams of puzzles, shared by the SVG and raster backends.
+
+use mora_jai_core::Corner;
+
+/// The side of each tile, in pixels or SVG user units.
+pub const TILE: u32 = 60;
+/// The space between tiles.
+pub const GAP: u32 = 8;
+/// The border around the tiles, where the corners sit.
+pub const BORDER: u32 = 40;
+/// The radius of the circle drawn for each corner, to the middle of its goal ring.
+pub const CORNER_RADIUS: u32 = 15;
+/// The width of the ring around each corner showing its goal.
+pub const GOAL_WIDTH: u32 = 6;
+/// The width and height of a whole diagram.
+pub const SIZE: u32 = 3 * TILE + 2 * GAP + 2 * BORDER;
+
+pub const FRAME_RGB: [u8; 3] = [0x3b, 0x2a, 0x1a];
+pub const OUTLINE_RGB: [u8; 3] = [0x1a, 0x1a, 0x1a];
+
+/// The top left of a tile. Row 0 is the bottom row.
+pub fn tile_origin(row: usize, col: usize) -> (u32, u32) {
+    let x = BORDER + col as u32 * (TILE + GAP);
+    let y = BORDER + (2 - row as u32) * (TILE + GAP);
+    (x, y)
+}
+
+/// The centre of a corner's circle.
+pub fn corner_centre(corner: Corner) -> (u32, u32) {
+    let near = BORDER / 2;
+    let far = SIZE - BORDER / 2;
+    match corner {
+        Corner::NW => (near, near),
+        Corner::NE => (far, near),
+        Corner::SW => (near, far),
+        Corner::SE => (far, far),
+    }
+}
+
+pub const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
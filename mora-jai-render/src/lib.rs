@@ -0,0 +1,35 @@
+//! Rendering Mora Jai puzzles and solutions for display.
+
+#[cfg(feature = "graphics")]
+pub mod graphics;
+pub mod heatmap;
+#[cfg(any(feature = "raster", feature = "svg"))]
+mod layout;
+pub mod palette;
+pub mod plain;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(feature = "raster")]
+pub mod raster;
+mod solution;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+
+pub use solution::SolutionFormatter;
+
+/// Formats a solution as keypad numbers, where 1 is the bottom left tile and 9 the top
+/// right.
+pub fn render_solution(solution: &[(usize, usize)]) -> String {
+    render_solution_with(solution, SolutionFormatter::Keypad)
+}
+
+/// Formats a solution with the given labels for its tiles.
+pub fn render_solution_with(solution: &[(usize, usize)], labels: SolutionFormatter) -> String {
+    let mut out = String::from("Solution: ");
+    for &(row, col) in solution {
+        out.push_str(&format!("{} ", labels.label(row, col)));
+    }
+    out
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use mora_jai_core::Color;
+
+/// How a tile color is drawn: a foreground color and an optional background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swatch {
+    pub fg: [u8; 3],
+    pub bg: Option<[u8; 3]>,
+}
+
+impl Swatch {
+    pub const fn fg(fg: [u8; 3]) -> Self {
+        Self { fg, bg: None }
+    }
+
+    pub const fn on(fg: [u8; 3], bg: [u8; 3]) -> Self {
+        Self { fg, bg: Some(bg) }
+    }
+}
+
+/// How a tile color is drawn on a terminal with a limited palette, as color indices:
+/// 0-15 for the basic and bright colors, or 0-255 for xterm's 256 colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Indexed {
+    pub fg: u8,
+    pub bg: Option<u8>,
+}
+
+/// The names of the 16 basic terminal colors, in index order.
+pub const ANSI16_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// The usual xterm values of the 16 basic terminal colors. Terminals are free to change
+/// these, so matches against them are only approximate.
+const ANSI16_RGB: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// The channel levels of xterm's 6x6x6 color cube, which starts at index 16.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32)
+        .sum()
+}
+
+/// The index of the basic terminal color closest to `rgb`.
+pub fn nearest_ansi16(rgb: [u8; 3]) -> u8 {
+    (0..16)
+        .min_by_key(|&i| distance(rgb, ANSI16_RGB[i as usize]))
+        .unwrap()
+}
+
+/// The index of the xterm 256-color entry closest to `rgb`, from the color cube or the
+/// grayscale ramp. The first 16 entries are skipped as terminals often redefine them.
+pub fn nearest_ansi256(rgb: [u8; 3]) -> u8 {
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let cube_rgb = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+
+    // The grayscale ramp runs from 8 to 238 in steps of 10
+    let avg = rgb.iter().map(|&c| c as u32).sum::<u32>() / 3;
+    let step = (avg.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * step;
+
+    if distance(rgb, [gray; 3]) < distance(rgb, cube_rgb) {
+        232 + step
+    } else {
+        cube
+    }
+}
+
+/// The display colors used for each tile color. Colors without an entry, such as most
+/// custom colors, are drawn with [`Color::rgb`]. For terminals without truecolor
+/// support, each swatch falls back to the nearest indexed colors unless an explicit
+/// fallback is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    swatches: BTreeMap<Color, Swatch>,
+    ansi256: BTreeMap<Color, Indexed>,
+    ansi16: BTreeMap<Color, Indexed>,
+}
+
+impl Palette {
+    /// Colors close to those in the game.
+    pub fn standard() -> Self {
+        let mut palette = Self::from_rgb();
+        // Pure black text is invisible on most dark terminals
+        palette.set(Color::Black, Swatch::on([0, 0, 0], [64, 64, 64]));
+        // Pink is otherwise closest to light gray, barely distinct from white
+        palette.set_ansi16(Color::Pink, Indexed { fg: 13, bg: None });
+        palette
+    }
+
+    /// Solid backgrounds in colors chosen to stay distinct under common forms of color
+    /// blindness, with black or white text for contrast.
+    pub fn high_contrast() -> Self {
+        const DARK: [u8; 3] = [0, 0, 0];
+        const LIGHT: [u8; 3] = [255, 255, 255];
+
+        let mut palette = Self::from_rgb();
+        for (color, swatch) in [
+            (Color::Gray, Swatch::on(LIGHT, [96, 96, 96])),
+            (Color::White, Swatch::on(DARK, [255, 255, 255])),
+            (Color::Black, Swatch::on(LIGHT, [0, 0, 0])),
+            (Color::Red, Swatch::on(LIGHT, [213, 94, 0])),
+            (Color::Orange, Swatch::on(DARK, [230, 159, 0])),
+            (Color::Green, Swatch::on(LIGHT, [0, 158, 115])),
+            (Color::Yellow, Swatch::on(DARK, [240, 228, 66])),
+            (Color::Violet, Swatch::on(LIGHT, [117, 62, 179])),
+            (Color::Pink, Swatch::on(DARK, [204, 121, 167])),
+            (Color::Blue, Swatch::on(LIGHT, [0, 114, 178])),
+        ] {
+            palette.set(color, swatch);
+        }
+        palette
+    }
+
+    /// Each color's [`Color::rgb`] as a plain foreground.
+    fn from_rgb() -> Self {
+        let swatches = Color::BUILTIN
+            .into_iter()
+            .map(|color| (color, Swatch::fg(color.rgb())))
+            .collect();
+        Self {
+            swatches,
+            ansi256: BTreeMap::new(),
+            ansi16: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, color: Color) -> Swatch {
+        self.swatches
+            .get(&color)
+            .copied()
+            .unwrap_or_else(|| Swatch::fg(color.rgb()))
+    }
+
+    /// Sets how a color is drawn, clearing any indexed fallbacks set for it.
+    pub fn set(&mut self, color: Color, swatch: Swatch) {
+        self.swatches.insert(color, swatch);
+        self.ansi256.remove(&color);
+        self.ansi16.remove(&color);
+    }
+
+    /// How a color is drawn with xterm's 256 colors.
+    pub fn get_ansi256(&self, color: Color) -> Indexed {
+        self.ansi256.get(&color).copied().unwrap_or_else(|| {
+            let swatch = self.get(color);
+            Indexed {
+                fg: nearest_ansi256(swatch.fg),
+                bg: swatch.bg.map(nearest_ansi256),
+            }
+        })
+    }
+
+    pub fn set_ansi256(&mut self, color: Color, indexed: Indexed) {
+        self.ansi256.insert(color, indexed);
+    }
+
+    /// How a color is drawn with the 16 basic terminal colors.
+    pub fn get_ansi16(&self, color: Color) -> Indexed {
+        self.ansi16.get(&color).copied().unwrap_or_else(|| {
+            let swatch = self.get(color);
+            Indexed {
+                fg: nearest_ansi16(swatch.fg),
+                bg: swatch.bg.map(nearest_ansi16),
+            }
+        })
+    }
+
+    /// Sets how a color is drawn with the 16 basic terminal colors. Indices must be
+    /// below 16.
+    pub fn set_ansi16(&mut self, color: Color, indexed: Indexed) {
+        self.ansi16.insert(color, indexed);
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallbacks_pick_nearest_colors() {
+        assert_eq!(nearest_ansi256([255, 0, 0]), 196);
+        assert_eq!(nearest_ansi256([128, 128, 128]), 244);
+        assert_eq!(nearest_ansi16([0, 0, 255]), 4);
+
+        let palette = Palette::standard();
+        assert_eq!(
+            palette.get_ansi16(Color::Black),
+            Indexed { fg: 0, bg: Some(8) }
+        );
+        assert_eq!(palette.get_ansi16(Color::Pink).fg, 13);
+    }
+}
@@ -0,0 +1,275 @@
+use mora_jai_core::{ColorLabels, Corner, Move, PressPreview, Puzzle};
+
+use crate::SolutionFormatter;
+
+/// Draws a puzzle's goals, corners and tiles as plain text for output without colors.
+/// Goals are written by name, and corners and tiles by their notation codes.
+pub fn render_puzzle(puzzle: &Puzzle) -> String {
+    render_puzzle_with_labels(puzzle, &ColorLabels::default())
+}
+
+/// Draws a puzzle like [`render_puzzle`] with the names and codes of `labels`.
+pub fn render_puzzle_with_labels(puzzle: &Puzzle, labels: &ColorLabels) -> String {
+    let goal = |corner| labels.name(puzzle.goal(corner));
+    let corner = |corner| labels.code(puzzle.get_corner(corner));
+    let row = |row| -> String {
+        (0..3)
+            .map(|col| labels.code(puzzle.get_tile(row, col)))
+            .collect()
+    };
+
+    format!(
+        concat!(
+            "Goals: {} {} {} {}\n",
+            "{}|{}|{}\n",
+            " |{}| \n",
+            "{}|{}|{}\n"
+        ),
+        goal(Corner::NW),
+        goal(Corner::NE),
+        goal(Corner::SW),
+        goal(Corner::SE),
+        corner(Corner::NW),
+        row(2),
+        corner(Corner::NE),
+        row(1),
+        corner(Corner::SW),
+        row(0),
+        corner(Corner::SE),
+    )
+}
+
+const CORNERS: [Corner; 4] = [Corner::NW, Corner::NE, Corner::SW, Corner::SE];
+
+fn corner_name(corner: Corner) -> &'static str {
+    match corner {
+        Corner::NW => "northwest",
+        Corner::NE => "northeast",
+        Corner::SW => "southwest",
+        Corner::SE => "southeast",
+    }
+}
+
+fn is_locked(puzzle: &Puzzle, corner: Corner) -> bool {
+    puzzle.get_corner(corner) == puzzle.goal(corner)
+}
+
+/// Lists items in a sentence, or says `none` if there are none.
+fn list(items: &[String], none: &str) -> String {
+    if items.is_empty() {
+        none.to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Describes a puzzle in sentences for screen readers: the goals, each row of tiles from
+/// the top, and the locked corners, with the tile or corner pressed by `highlight`
+/// named last. Tiles are labelled with `labels` and colors named by `colors`.
+pub fn describe_puzzle(
+    puzzle: &Puzzle,
+    highlight: Option<Move>,
+    labels: SolutionFormatter,
+    colors: &ColorLabels,
+) -> String {
+    let goals: Vec<String> = CORNERS
+        .iter()
+        .map(|&corner| {
+            format!(
+                "{} {}",
+                corner_name(corner),
+                colors.name(puzzle.goal(corner))
+            )
+        })
+        .collect();
+    let mut description = format!("Goals: {}.\n", goals.join(", "));
+    for (name, row) in [("Top", 2), ("Middle", 1), ("Bottom", 0)] {
+        let tiles: Vec<&str> = (0..3)
+            .map(|col| colors.name(puzzle.get_tile(row, col)))
+            .collect();
+        description += &format!("{name} row: {}.\n", tiles.join(", "));
+    }
+    let locked: Vec<String> = CORNERS
+        .iter()
+        .filter(|&&corner| is_locked(puzzle, corner))
+        .map(|&corner| corner_name(corner).to_string())
+        .collect();
+    description += &format!("Locked corners: {}.\n", list(&locked, "none"));
+    match highlight {
+        Some(Move::Tile(row, col)) => {
+            description += &format!("Next, press tile {}.\n", labels.label(row, col));
+        }
+        Some(Move::Corner(corner)) => {
+            description += &format!("Next, press the {} corner.\n", corner_name(corner));
+        }
+        None => {}
+    }
+    description
+}
+
+/// Announces what changed between two states of a puzzle, such as after a press: the
+/// tiles which changed color and the corners locked or unlocked.
+pub fn describe_changes(
+    before: &Puzzle,
+    after: &Puzzle,
+    labels: SolutionFormatter,
+    colors: &ColorLabels,
+) -> String {
+    let tiles: Vec<String> = before
+        .current_state()
+        .diff(after.current_state())
+        .iter()
+        .map(|change| {
+            let tile = labels.label(change.row, change.col);
+            format!("tile {tile} became {}", colors.name(change.to))
+        })
+        .collect();
+    let corners = |locked: bool| -> Vec<String> {
+        CORNERS
+            .iter()
+            .filter(|&&corner| {
+                is_locked(before, corner) != locked && is_locked(after, corner) == locked
+            })
+            .map(|&corner| corner_name(corner).to_string())
+            .collect()
+    };
+    let (locked, unlocked) = (corners(true), corners(false));
+
+    let mut sentences = vec![];
+    if !tiles.is_empty() {
+        sentences.push(format!("Changed: {}.", tiles.join(", ")));
+    }
+    if !locked.is_empty() {
+        sentences.push(format!("Locked: {}.", locked.join(", ")));
+    }
+    if !unlocked.is_empty() {
+        sentences.push(format!("Unlocked: {}.", unlocked.join(", ")));
+    }
+    if sentences.is_empty() {
+        sentences.push(String::from("Nothing changed."));
+    }
+    sentences.join(" ") + "\n"
+}
+
+/// Explains each press of a solution, one line per press: the color pressed, what its
+/// rule does, and which tiles it changed, labelled with `labels`.
+pub fn render_explanation(
+    puzzle: &Puzzle,
+    solution: &[(usize, usize)],
+    labels: SolutionFormatter,
+) -> String {
+    puzzle
+        .trace(solution)
+        .iter()
+        .map(|preview| explain_press(preview, labels) + "\n")
+        .collect()
+}
+
+fn explain_press(preview: &PressPreview, labels: SolutionFormatter) -> String {
+    let key = |row, col| labels.label(row, col);
+    let rule = preview.rule.as_deref().unwrap_or("has no rule");
+    let changes: Vec<String> = preview
+        .changes
+        .iter()
+        .map(|change| {
+            format!(
+                "{} becomes {}",
+                key(change.row, change.col),
+                change.to.name()
+            )
+        })
+        .collect();
+    let changes = if changes.is_empty() {
+        String::from("nothing changes")
+    } else {
+        changes.join(", ")
+    };
+    format!(
+        "Press {} ({}): {rule}; {changes}",
+        key(preview.row, preview.col),
+        preview.color.name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::{Color, Grid};
+
+    #[test]
+    fn renders_codes_without_escapes() {
+        let puzzle = Puzzle::new(
+            [Color::White, Color::Red, Color::Black, Color::Blue],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::Red],
+                [Color::Gray, Color::Green, Color::Gray],
+                [Color::Black, Color::Gray, Color::Blue],
+            ),
+        );
+
+        assert_eq!(
+            render_puzzle(&puzzle),
+            "Goals: white red black blue\n-|wwr|-\n |-g-| \n-|k-b|-\n"
+        );
+    }
+
+    #[test]
+    fn describes_the_board_and_its_changes() {
+        let before = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+        let colors = ColorLabels::default();
+        assert_eq!(
+            describe_puzzle(
+                &before,
+                Some(Move::Tile(0, 2)),
+                SolutionFormatter::Keypad,
+                &colors
+            ),
+            "Goals: northwest white, northeast white, southwest white, southeast white.\n\
+             Top row: white, white, white.\n\
+             Middle row: white, gray, white.\n\
+             Bottom row: gray, gray, white.\n\
+             Locked corners: none.\n\
+             Next, press tile 3.\n"
+        );
+
+        let mut after = before.clone();
+        after.press_corner(Corner::NW);
+        after.press_tile(0, 2);
+        assert_eq!(
+            describe_changes(&before, &after, SolutionFormatter::Keypad, &colors),
+            "Changed: tile 2 became white, tile 3 became gray, tile 6 became gray. \
+             Locked: northwest.\n"
+        );
+        assert_eq!(
+            describe_changes(&after, &after, SolutionFormatter::Keypad, &colors),
+            "Nothing changed.\n"
+        );
+    }
+
+    #[test]
+    fn explains_each_press() {
+        let puzzle = Puzzle::new(
+            [Color::White; 4],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::White],
+                [Color::White, Color::Gray, Color::White],
+                [Color::Gray, Color::Gray, Color::White],
+            ),
+        );
+
+        assert_eq!(
+            render_explanation(&puzzle, &[(0, 2), (0, 1)], SolutionFormatter::Keypad),
+            "Press 3 (white): toggles itself and adjacent white and gray tiles; \
+             2 becomes white, 3 becomes gray, 6 becomes gray\n\
+             Press 2 (white): toggles itself and adjacent white and gray tiles; \
+             1 becomes white, 2 becomes gray, 3 becomes white, 5 becomes white\n"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! QR codes of puzzles' share codes, for scanning a puzzle into another device.
+
+use image::{GrayImage, Luma};
+use mora_jai_core::Puzzle;
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+
+/// The smallest size of QR code images, in pixels.
+const MIN_IMAGE_SIZE: u32 = 256;
+
+/// The QR code for a puzzle's share code, or `None` if the puzzle has custom colors,
+/// which share codes cannot describe.
+fn qr_code(puzzle: &Puzzle) -> Option<QrCode> {
+    let code = puzzle.to_share_code()?;
+    Some(QrCode::new(code.as_bytes()).expect("share codes fit in a QR code"))
+}
+
+/// Draws a puzzle's QR code with half-height block characters, light on dark so that it
+/// scans from a dark terminal.
+pub fn render_qr_terminal(puzzle: &Puzzle) -> Option<String> {
+    let rendered = qr_code(puzzle)?
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    Some(rendered + "\n")
+}
+
+/// Draws a puzzle's QR code as a grayscale image.
+pub fn render_qr_image(puzzle: &Puzzle) -> Option<GrayImage> {
+    let image = qr_code(puzzle)?
+        .render::<Luma<u8>>()
+        .min_dimensions(MIN_IMAGE_SIZE, MIN_IMAGE_SIZE)
+        .build();
+    Some(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::{Color, Grid};
+
+    #[test]
+    fn renders_share_codes() {
+        let puzzle = Puzzle::new(
+            [Color::White, Color::Red, Color::Black, Color::Blue],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::Red],
+                [Color::Gray, Color::Green, Color::Gray],
+                [Color::Black, Color::Gray, Color::Blue],
+            ),
+        );
+
+        let terminal = render_qr_terminal(&puzzle).unwrap();
+        let widths: Vec<_> = terminal.lines().map(|line| line.chars().count()).collect();
+        assert!(widths.len() > 1);
+        assert!(widths.iter().all(|&width| width == widths[0]));
+
+        let image = render_qr_image(&puzzle).unwrap();
+        assert!(image.width() >= MIN_IMAGE_SIZE && image.height() >= MIN_IMAGE_SIZE);
+    }
+}
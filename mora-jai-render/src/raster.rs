@@ -0,0 +1,227 @@
+//! Drawing puzzles and the steps of their solutions as images, laid out like the SVG
+//! diagrams.
+
+#[cfg(feature = "gif")]
+use std::io::Write;
+#[cfg(feature = "gif")]
+use std::time::Duration;
+
+use image::Rgb;
+pub use image::RgbImage;
+use mora_jai_core::{Move, Puzzle};
+
+use crate::layout::{
+    CORNER_RADIUS, CORNERS, FRAME_RGB, GOAL_WIDTH, OUTLINE_RGB, SIZE, TILE, corner_centre,
+    tile_origin,
+};
+
+/// The color of the outline around the tile or corner pressed next.
+const HIGHLIGHT_RGB: [u8; 3] = [255, 255, 255];
+/// The width of the outline around the tile or corner pressed next. It lies in the gap
+/// around the tile, so it never hides the tile's color.
+const HIGHLIGHT_WIDTH: u32 = 3;
+/// The space around each diagram on a contact sheet.
+const SHEET_MARGIN: u32 = 16;
+const SHEET_RGB: [u8; 3] = [255, 255, 255];
+/// How many times longer than the others the last frame of an animation is shown, so the
+/// solved puzzle can be seen before the animation loops.
+#[cfg(feature = "gif")]
+const FINAL_FRAME_HOLD: u32 = 3;
+
+fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, rgb: [u8; 3]) {
+    for py in y..y + height {
+        for px in x..x + width {
+            image.put_pixel(px, py, Rgb(rgb));
+        }
+    }
+}
+
+/// Fills the pixels whose centres lie between two distances of a point.
+fn fill_ring(image: &mut RgbImage, (cx, cy): (u32, u32), inner: f32, outer: f32, rgb: [u8; 3]) {
+    let reach = outer.ceil() as u32;
+    let (width, height) = image.dimensions();
+    for py in cy.saturating_sub(reach)..(cy + reach).min(height) {
+        for px in cx.saturating_sub(reach)..(cx + reach).min(width) {
+            let dx = px as f32 + 0.5 - cx as f32;
+            let dy = py as f32 + 0.5 - cy as f32;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= inner && distance < outer {
+                image.put_pixel(px, py, Rgb(rgb));
+            }
+        }
+    }
+}
+
+/// Draws a puzzle like [`render_svg`](crate::svg::render_svg), outlining the tile or
+/// corner pressed by `highlight`.
+pub fn render_image(puzzle: &Puzzle, highlight: Option<Move>) -> RgbImage {
+    let mut image = RgbImage::from_pixel(SIZE, SIZE, Rgb(FRAME_RGB));
+
+    for row in 0..3 {
+        for col in 0..3 {
+            let (x, y) = tile_origin(row, col);
+            if highlight == Some(Move::Tile(row, col)) {
+                let h = HIGHLIGHT_WIDTH;
+                fill_rect(
+                    &mut image,
+                    x - h,
+                    y - h,
+                    TILE + 2 * h,
+                    TILE + 2 * h,
+                    HIGHLIGHT_RGB,
+                );
+            }
+            fill_rect(&mut image, x, y, TILE, TILE, OUTLINE_RGB);
+            let rgb = puzzle.get_tile(row, col).rgb();
+            fill_rect(&mut image, x + 2, y + 2, TILE - 4, TILE - 4, rgb);
+        }
+    }
+
+    let half = GOAL_WIDTH as f32 / 2.0;
+    let radius = CORNER_RADIUS as f32;
+    for corner in CORNERS {
+        let centre = corner_centre(corner);
+        if highlight == Some(Move::Corner(corner)) {
+            // Leave a gap so the outline can't merge with a goal ring of the same color
+            let inner = radius + half + 2.0;
+            let outer = inner + HIGHLIGHT_WIDTH as f32;
+            fill_ring(&mut image, centre, inner, outer, HIGHLIGHT_RGB);
+        }
+        let goal = puzzle.goal(corner).rgb();
+        fill_ring(&mut image, centre, radius - half, radius + half, goal);
+        let color = puzzle.get_corner(corner).rgb();
+        fill_ring(&mut image, centre, 0.0, radius - half, color);
+    }
+
+    image
+}
+
+/// Draws a puzzle before each move, outlining the move about to be made, and then once
+/// more after the last move.
+pub fn render_steps(puzzle: &Puzzle, moves: &[Move]) -> Vec<RgbImage> {
+    let mut puzzle = puzzle.clone();
+    let mut images = Vec::with_capacity(moves.len() + 1);
+    for &action in moves {
+        images.push(render_image(&puzzle, Some(action)));
+        puzzle.apply(action);
+    }
+    images.push(render_image(&puzzle, None));
+    images
+}
+
+/// Lays images of the same size out in rows of `columns`, left to right.
+pub fn contact_sheet(images: &[RgbImage], columns: u32) -> RgbImage {
+    let columns = columns.clamp(1, images.len().max(1) as u32);
+    let rows = (images.len() as u32).div_ceil(columns);
+    let (width, height) = images.first().map_or((0, 0), |image| image.dimensions());
+    let mut sheet = RgbImage::from_pixel(
+        SHEET_MARGIN + columns * (width + SHEET_MARGIN),
+        SHEET_MARGIN + rows * (height + SHEET_MARGIN),
+        Rgb(SHEET_RGB),
+    );
+    for (i, image) in images.iter().enumerate() {
+        let i = i as u32;
+        let x = SHEET_MARGIN + (i % columns) * (width + SHEET_MARGIN);
+        let y = SHEET_MARGIN + (i / columns) * (height + SHEET_MARGIN);
+        image::imageops::replace(&mut sheet, image, x.into(), y.into());
+    }
+    sheet
+}
+
+/// Encodes images as a looping animated GIF, showing each for `delay` and the last for
+/// longer.
+#[cfg(feature = "gif")]
+pub fn encode_gif(
+    images: Vec<RgbImage>,
+    delay: Duration,
+    writer: impl Write,
+) -> image::ImageResult<()> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::{Delay, DynamicImage, Frame};
+
+    let count = images.len();
+    let mut encoder = GifEncoder::new(writer);
+    encoder.set_repeat(Repeat::Infinite)?;
+    let frames = images.into_iter().enumerate().map(|(i, image)| {
+        let shown = if i + 1 == count {
+            delay * FINAL_FRAME_HOLD
+        } else {
+            delay
+        };
+        let rgba = DynamicImage::ImageRgb8(image).into_rgba8();
+        Frame::from_parts(rgba, 0, 0, Delay::from_saturating_duration(shown))
+    });
+    encoder.encode_frames(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{BORDER, GAP};
+    use mora_jai_core::{Color, Corner, Grid};
+
+    #[test]
+    fn draws_each_step() {
+        let puzzle = Puzzle::new(
+            [Color::Black; 4],
+            Grid::from_rows(
+                [Color::Black, Color::Gray, Color::Black],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Black, Color::Gray, Color::Black],
+            ),
+        );
+        let moves = [Corner::NW, Corner::NE, Corner::SW, Corner::SE].map(Move::Corner);
+        let images = render_steps(&puzzle, &moves);
+        assert_eq!(images.len(), 5);
+
+        let (cx, cy) = corner_centre(Corner::NW);
+        // Gray before the NW corner is pressed, and black after
+        assert_eq!(images[0].get_pixel(cx, cy), &Rgb(Color::Gray.rgb()));
+        assert_eq!(images[1].get_pixel(cx, cy), &Rgb(Color::Black.rgb()));
+        // Only the first image outlines the NW corner
+        let outline = (cx, cy + CORNER_RADIUS + GOAL_WIDTH / 2 + 3);
+        assert_eq!(
+            images[0].get_pixel(outline.0, outline.1),
+            &Rgb(HIGHLIGHT_RGB)
+        );
+        assert_eq!(images[1].get_pixel(outline.0, outline.1), &Rgb(FRAME_RGB));
+
+        let (x, y) = tile_origin(2, 0);
+        assert_eq!(
+            images[0].get_pixel(x + 10, y + 10),
+            &Rgb(Color::Black.rgb())
+        );
+        assert_eq!(images[0].get_pixel(x - GAP / 2, y), &Rgb(FRAME_RGB));
+        assert_eq!(BORDER, x);
+
+        let sheet = contact_sheet(&images, 4);
+        assert_eq!(sheet.width(), SHEET_MARGIN + 4 * (SIZE + SHEET_MARGIN));
+        assert_eq!(sheet.height(), SHEET_MARGIN + 2 * (SIZE + SHEET_MARGIN));
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn encodes_one_gif_frame_per_step() {
+        use image::AnimationDecoder;
+        use image::codecs::gif::GifDecoder;
+
+        let puzzle = Puzzle::new([Color::Black; 4], Grid::new([Color::Black; 9]));
+        let moves = [Corner::NW, Corner::NE].map(Move::Corner);
+        let mut gif = vec![];
+        encode_gif(
+            render_steps(&puzzle, &moves),
+            Duration::from_millis(100),
+            &mut gif,
+        )
+        .unwrap();
+
+        let frames = GifDecoder::new(std::io::Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].delay().numer_denom_ms(), (100, 1));
+        assert_eq!(frames[2].delay().numer_denom_ms(), (300, 1));
+    }
+}
@@ -0,0 +1,76 @@
+//! Labels for the tiles pressed in a solution.
+
+use mora_jai_core::{Corner, Move};
+
+/// How the tiles pressed in a solution are labelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SolutionFormatter {
+    /// Numbers laid out like a keypad, where 1 is the bottom left tile and 9 the top
+    /// right
+    #[default]
+    Keypad,
+    /// A letter for the column from `a` on the left, and a number for the row from `1`
+    /// at the bottom, so the bottom left tile is `a1` and the top right `c3`
+    Chess,
+    /// The row and column as used by the library, counting from `(0, 0)` at the bottom
+    /// left
+    RowCol,
+}
+
+impl SolutionFormatter {
+    /// The label for one tile.
+    pub fn label(self, row: usize, col: usize) -> String {
+        match self {
+            SolutionFormatter::Keypad => (1 + 3 * row + col).to_string(),
+            SolutionFormatter::Chess => format!("{}{}", char::from(b'a' + col as u8), row + 1),
+            SolutionFormatter::RowCol => format!("({row}, {col})"),
+        }
+    }
+
+    /// The label for a tile press, or for a corner press `nw`, `ne`, `sw` or `se`.
+    pub fn move_label(self, action: Move) -> String {
+        match action {
+            Move::Tile(row, col) => self.label(row, col),
+            Move::Corner(Corner::NW) => String::from("nw"),
+            Move::Corner(Corner::NE) => String::from("ne"),
+            Move::Corner(Corner::SW) => String::from("sw"),
+            Move::Corner(Corner::SE) => String::from("se"),
+        }
+    }
+
+    /// The labels of a sequence of moves separated by spaces, with nothing else.
+    pub fn format_moves(self, moves: &[Move]) -> String {
+        let labels: Vec<String> = moves
+            .iter()
+            .map(|&action| self.move_label(action))
+            .collect();
+        labels.join(" ")
+    }
+
+    /// The labels of a solution's presses separated by spaces, with nothing else.
+    pub fn format(self, solution: &[(usize, usize)]) -> String {
+        let labels: Vec<String> = solution
+            .iter()
+            .map(|&(row, col)| self.label(row, col))
+            .collect();
+        labels.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_tiles() {
+        let solution = [(0, 0), (1, 2), (2, 1)];
+        assert_eq!(SolutionFormatter::Keypad.format(&solution), "1 6 8");
+        assert_eq!(SolutionFormatter::Chess.format(&solution), "a1 c2 b3");
+        assert_eq!(
+            SolutionFormatter::RowCol.format(&solution),
+            "(0, 0) (1, 2) (2, 1)"
+        );
+        let moves = [Move::Tile(0, 0), Move::Corner(Corner::SE)];
+        assert_eq!(SolutionFormatter::Chess.format_moves(&moves), "a1 se");
+    }
+}
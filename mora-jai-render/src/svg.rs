@@ -0,0 +1,91 @@
+//! Drawing puzzles as SVG diagrams, for printing or embedding in documents.
+
+use std::fmt::Write;
+
+use mora_jai_core::Puzzle;
+
+use crate::layout::{
+    CORNER_RADIUS, CORNERS, FRAME_RGB, GAP, GOAL_WIDTH, OUTLINE_RGB, SIZE, TILE, corner_centre,
+    tile_origin,
+};
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Draws a puzzle's tiles on a frame, with a circle in each corner of the frame filled
+/// with the corner's color and ringed with its goal's.
+pub fn render_svg(puzzle: &Puzzle) -> String {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SIZE}" height="{SIZE}" viewBox="0 0 {SIZE} {SIZE}">"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <rect width="{SIZE}" height="{SIZE}" rx="{GAP}" fill="{}"/>"#,
+        hex(FRAME_RGB),
+    )
+    .unwrap();
+
+    for row in 0..3 {
+        for col in 0..3 {
+            let color = puzzle.get_tile(row, col);
+            let (x, y) = tile_origin(row, col);
+            writeln!(
+                svg,
+                r#"  <rect x="{x}" y="{y}" width="{TILE}" height="{TILE}" rx="4" fill="{}" stroke="{}" stroke-width="2"><title>{}</title></rect>"#,
+                hex(color.rgb()),
+                hex(OUTLINE_RGB),
+                color.name(),
+            )
+            .unwrap();
+        }
+    }
+
+    for corner in CORNERS {
+        let (cx, cy) = corner_centre(corner);
+        let color = puzzle.get_corner(corner);
+        let goal = puzzle.goal(corner);
+        writeln!(
+            svg,
+            r#"  <circle cx="{cx}" cy="{cy}" r="{CORNER_RADIUS}" fill="{}" stroke="{}" stroke-width="{GOAL_WIDTH}"><title>{} (goal: {})</title></circle>"#,
+            hex(color.rgb()),
+            hex(goal.rgb()),
+            color.name(),
+            goal.name(),
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::{Color, Grid};
+
+    #[test]
+    fn draws_tiles_top_row_first() {
+        let puzzle = Puzzle::new(
+            [Color::White, Color::Red, Color::Black, Color::Blue],
+            Grid::from_rows(
+                [Color::Red, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Gray],
+                [Color::Gray, Color::Gray, Color::Blue],
+            ),
+        );
+        let svg = render_svg(&puzzle);
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 1 + 9);
+        assert_eq!(svg.matches("<circle").count(), 4);
+        let red = svg.find(r##"x="40" y="40" width="60" height="60" rx="4" fill="#ff0000""##);
+        let blue = svg.find(r##"x="176" y="176" width="60" height="60" rx="4" fill="#0000ff""##);
+        assert!(red.is_some() && blue.is_some());
+        assert!(svg.contains("<title>gray (goal: white)</title>"));
+    }
+}
@@ -0,0 +1,212 @@
+use mora_jai_core::{Color, ColorLabels, Corner, Move, Puzzle};
+
+use crate::palette::{Indexed, Palette};
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit color
+    #[default]
+    TrueColor,
+    /// xterm's 256 colors
+    Ansi256,
+    /// The 8 basic colors and their bright variants
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guesses the terminal's color depth from the `COLORTERM` and `TERM` variables.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Options for drawing puzzles in a terminal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    pub palette: Palette,
+    pub depth: ColorDepth,
+    /// Show each tile's notation code next to its label, so tiles can be told apart
+    /// without relying on color
+    pub symbols: bool,
+    /// The names given to goals and the codes shown by `symbols`
+    pub colors: ColorLabels,
+}
+
+/// Draws a puzzle's goals, corners and tiles with ANSI colors. Tiles are labelled with
+/// their keypad numbers and corners with the keys used to press them in play mode.
+pub fn render_puzzle(puzzle: &Puzzle) -> String {
+    render_puzzle_with(puzzle, &Style::default())
+}
+
+/// Draws a puzzle like [`render_puzzle`] in the given style.
+pub fn render_puzzle_with(puzzle: &Puzzle, style: &Style) -> String {
+    render_puzzle_highlighted(puzzle, style, None)
+}
+
+/// Draws a puzzle like [`render_puzzle_with`], showing the tile or corner pressed by
+/// `highlight` in reverse video, such as to point out a hint.
+pub fn render_puzzle_highlighted(
+    puzzle: &Puzzle,
+    style: &Style,
+    highlight: Option<Move>,
+) -> String {
+    let tile = |label: &str, color: Color, target: Move| {
+        let label = if style.symbols {
+            format!("{label}{}", style.colors.code(color))
+        } else {
+            label.to_string()
+        };
+        let painted = paint(&label, color, style);
+        if highlight == Some(target) {
+            // The reset at the end of the painted text also ends the reverse video
+            format!("\x1b[7m{painted}")
+        } else {
+            painted
+        }
+    };
+    let corner =
+        |label: &str, corner: Corner| tile(label, puzzle.get_corner(corner), Move::Corner(corner));
+    let goal = |corner| {
+        let color = puzzle.goal(corner);
+        paint(style.colors.name(color), color, style)
+    };
+    let sep = if style.symbols { " " } else { "" };
+    let row = |row: usize| {
+        (0..3)
+            .map(|col| {
+                let label = (1 + 3 * row + col).to_string();
+                tile(&label, puzzle.get_tile(row, col), Move::Tile(row, col))
+            })
+            .collect::<Vec<_>>()
+            .join(sep)
+    };
+    let pad = if style.symbols { "  " } else { " " };
+
+    format!(
+        concat!(
+            "Goals: {} {} {} {}\n",
+            "{}|{}|{}\n",
+            "{}|{}|{}\n",
+            "{}|{}|{}\n"
+        ),
+        goal(Corner::NW),
+        goal(Corner::NE),
+        goal(Corner::SW),
+        goal(Corner::SE),
+        corner("q", Corner::NW),
+        row(2),
+        corner("w", Corner::NE),
+        pad,
+        row(1),
+        pad,
+        corner("a", Corner::SW),
+        row(0),
+        corner("s", Corner::SE),
+    )
+}
+
+/// Colors text with the standard palette.
+pub fn colorize(s: &str, color: Color) -> String {
+    paint(s, color, &Style::default())
+}
+
+/// Colors text with the given style's palette and color depth.
+pub fn paint(s: &str, color: Color, style: &Style) -> String {
+    let (fg, bg) = match style.depth {
+        ColorDepth::TrueColor => {
+            let swatch = style.palette.get(color);
+            let rgb = |[r, g, b]: [u8; 3]| format!("2;{r};{g};{b}");
+            (
+                format!("38;{}", rgb(swatch.fg)),
+                swatch.bg.map(|bg| format!("48;{}", rgb(bg))),
+            )
+        }
+        ColorDepth::Ansi256 => {
+            let Indexed { fg, bg } = style.palette.get_ansi256(color);
+            (format!("38;5;{fg}"), bg.map(|bg| format!("48;5;{bg}")))
+        }
+        ColorDepth::Ansi16 => {
+            // Bright colors have their own codes, 90-97 and 100-107
+            let code = |i: u8, base: u8| if i < 8 { base + i } else { base + 60 + i - 8 };
+            let Indexed { fg, bg } = style.palette.get_ansi16(color);
+            (
+                code(fg, 30).to_string(),
+                bg.map(|bg| code(bg, 40).to_string()),
+            )
+        }
+    };
+
+    match bg {
+        Some(bg) => format!("\x1b[{fg};{bg}m{s}\x1b[0m"),
+        None => format!("\x1b[{fg}m{s}\x1b[0m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mora_jai_core::Grid;
+
+    /// Removes ANSI escape sequences, leaving the text.
+    fn strip_escapes(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn paint_uses_color_depth() {
+        let mut style = Style::default();
+        assert_eq!(
+            paint("k", Color::Black, &style),
+            "\x1b[38;2;0;0;0;48;2;64;64;64mk\x1b[0m"
+        );
+        style.depth = ColorDepth::Ansi256;
+        assert_eq!(paint("r", Color::Red, &style), "\x1b[38;5;196mr\x1b[0m");
+        style.depth = ColorDepth::Ansi16;
+        assert_eq!(paint("k", Color::Black, &style), "\x1b[30;100mk\x1b[0m");
+    }
+
+    #[test]
+    fn symbols_show_codes() {
+        let puzzle = Puzzle::new(
+            [Color::White, Color::Red, Color::Black, Color::Blue],
+            Grid::from_rows(
+                [Color::White, Color::White, Color::Red],
+                [Color::Gray, Color::Violet, Color::Gray],
+                [Color::Black, Color::Gray, Color::Blue],
+            ),
+        );
+        let style = Style {
+            palette: Palette::high_contrast(),
+            symbols: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            strip_escapes(&render_puzzle_with(&puzzle, &style)),
+            concat!(
+                "Goals: white red black blue\n",
+                "q-|7w 8w 9r|w-\n",
+                "  |4- 5v 6-|  \n",
+                "a-|1k 2- 3b|s-\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Browser bindings for running Mora Jai puzzles and the solver client-side.
+//!
+//! Tiles are addressed by row and column with row 0 at the bottom, as in the core
+//! crate, and corners by the names `"nw"`, `"ne"`, `"sw"` and `"se"`.
+
+use mora_jai_core::{Corner, Puzzle, RuleSet, SolveOptions};
+use mora_jai_io::notation;
+use wasm_bindgen::prelude::*;
+
+/// The default cap on solver states, chosen to keep searches well within a browser's
+/// WASM heap. Each state costs roughly a hundred bytes including its path.
+pub const DEFAULT_MAX_STATES: usize = 2_000_000;
+
+fn parse_corner(corner: &str) -> Result<Corner, JsError> {
+    match corner.to_ascii_lowercase().as_str() {
+        "nw" => Ok(Corner::NW),
+        "ne" => Ok(Corner::NE),
+        "sw" => Ok(Corner::SW),
+        "se" => Ok(Corner::SE),
+        _ => Err(JsError::new(&format!("unknown corner {corner:?}"))),
+    }
+}
+
+fn check_tile(row: usize, col: usize) -> Result<(), JsError> {
+    if row < 3 && col < 3 {
+        Ok(())
+    } else {
+        Err(JsError::new("row and column must be 0, 1, or 2"))
+    }
+}
+
+#[wasm_bindgen(js_name = Puzzle)]
+pub struct WasmPuzzle {
+    inner: Puzzle,
+    max_states: usize,
+    bounded: bool,
+}
+
+#[wasm_bindgen(js_class = Puzzle)]
+impl WasmPuzzle {
+    /// Parses a puzzle from its 13-character notation.
+    #[wasm_bindgen(constructor)]
+    pub fn new(notation: &str) -> Result<WasmPuzzle, JsError> {
+        let inner = notation::parse_puzzle(notation, &RuleSet::standard())
+            .ok_or_else(|| JsError::new("failed to parse puzzle"))?;
+        Ok(Self::from_puzzle(inner))
+    }
+
+    /// Generates a random solvable puzzle.
+    pub fn random() -> WasmPuzzle {
+        Self::from_puzzle(Puzzle::new_random())
+    }
+
+    fn from_puzzle(inner: Puzzle) -> Self {
+        Self {
+            inner,
+            max_states: DEFAULT_MAX_STATES,
+            bounded: false,
+        }
+    }
+
+    /// The most states the solver may hold in memory before giving up.
+    #[wasm_bindgen(getter, js_name = maxStates)]
+    pub fn max_states(&self) -> usize {
+        self.max_states
+    }
+
+    #[wasm_bindgen(setter, js_name = maxStates)]
+    pub fn set_max_states(&mut self, max_states: usize) {
+        self.max_states = max_states;
+    }
+
+    /// Whether the solver stays within `maxStates` by forgetting old states instead of
+    /// giving up, at the cost of sometimes longer solutions or none at all.
+    #[wasm_bindgen(getter)]
+    pub fn bounded(&self) -> bool {
+        self.bounded
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_bounded(&mut self, bounded: bool) {
+        self.bounded = bounded;
+    }
+
+    /// The puzzle's goals and original grid in notation form.
+    pub fn notation(&self) -> String {
+        notation::to_notation(&self.inner)
+    }
+
+    /// The current tiles as notation characters, top row first.
+    pub fn tiles(&self) -> String {
+        (0..3)
+            .rev()
+            .flat_map(|row| (0..3).map(move |col| self.inner.get_tile(row, col)))
+            .map(notation::color_to_char)
+            .collect()
+    }
+
+    /// The locked corner colors as notation characters in NW, NE, SW, SE order, with
+    /// unlocked corners shown as gray.
+    pub fn corners(&self) -> String {
+        [Corner::NW, Corner::NE, Corner::SW, Corner::SE]
+            .into_iter()
+            .map(|corner| notation::color_to_char(self.inner.get_corner(corner)))
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = pressTile)]
+    pub fn press_tile(&mut self, row: usize, col: usize) -> Result<(), JsError> {
+        check_tile(row, col)?;
+        self.inner.press_tile(row, col);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = pressCorner)]
+    pub fn press_corner(&mut self, corner: &str) -> Result<(), JsError> {
+        self.inner.press_corner(parse_corner(corner)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.inner.is_solved()
+    }
+
+    /// Solves the puzzle from its original grid, returning the presses as keypad numbers
+    /// (1 is the bottom left tile, 9 the top right), or `undefined` if it has no solution
+    /// (or with `bounded`, if none was found).
+    pub fn solve(&self) -> Result<Option<Vec<u8>>, JsError> {
+        let options = if self.bounded {
+            SolveOptions {
+                memory_bound: Some(self.max_states),
+                ..Default::default()
+            }
+        } else {
+            SolveOptions {
+                max_states: Some(self.max_states),
+                ..Default::default()
+            }
+        };
+        match self.inner.solve_with(&options) {
+            Ok(solution) => Ok(solution.map(|solution| {
+                solution
+                    .presses
+                    .into_iter()
+                    .map(|(row, col)| (1 + 3 * row + col) as u8)
+                    .collect()
+            })),
+            Err(e) => Err(JsError::new(&e.to_string())),
+        }
+    }
+}
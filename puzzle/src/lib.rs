@@ -1,4 +0,0 @@
-mod puzzle;
-mod solver;
-
-pub use puzzle::{Color, Grid, Puzzle, Corner};